@@ -1002,22 +1002,24 @@ pub async fn command_runner(
                 )?;
 
                 // Script signature
-                let challenge = TransactionInput::build_script_signature_challenge(
-                    &TransactionInputVersion::get_current_version(),
-                    &leader_info.script_signature_ephemeral_commitment,
-                    &leader_info.script_signature_ephemeral_pubkey,
+                let txi_version = TransactionInputVersion::get_current_version();
+                let script_message = TransactionInput::build_script_signature_message(
+                    &txi_version,
                     &leader_info.input_script,
                     &leader_info.input_stack,
-                    &leader_info.total_script_key,
-                    &Commitment::from_hex(&session_info.commitment_to_spend)?,
                 );
 
                 let mut script_signature = Signature::default();
                 match key_manager_service
-                    .sign_with_nonce_and_challenge(
+                    .get_script_signature_share(
                         &party_info.wallet_spend_key_id,
                         &party_info.script_nonce_key_id,
-                        &challenge,
+                        &txi_version,
+                        &leader_info.script_signature_ephemeral_commitment,
+                        &leader_info.script_signature_ephemeral_pubkey,
+                        &leader_info.total_script_key,
+                        &Commitment::from_hex(&session_info.commitment_to_spend)?,
+                        &script_message,
                     )
                     .await
                 {
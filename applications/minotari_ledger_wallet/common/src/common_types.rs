@@ -40,6 +40,13 @@ pub enum AppSW {
     KeyDeriveFromUniform = 0xB00A,
     RandomNonceFail = 0xB00B,
     BadBranchKey = 0xB00C,
+    VersionMismatch = 0xB00D,
+    PayloadTooLarge = 0xB00E,
+    AmountMismatch = 0xB00F,
+    EncryptionFail = 0xB010,
+    AccountMismatch = 0xB011,
+    SessionLimitExceeded = 0xB012,
+    OffsetNotUnique = 0xB013,
     WrongApduLength = 0x6e03, // See ledger-device-rust-sdk/ledger_device_sdk/src/io.rs:16
     UserCancelled = 0x6e04,   // See ledger-device-rust-sdk/ledger_device_sdk/src/io.rs:16
 }
@@ -61,6 +68,13 @@ impl TryFrom<u16> for AppSW {
             0xB00A => Ok(AppSW::KeyDeriveFromUniform),
             0xB00B => Ok(AppSW::RandomNonceFail),
             0xB00C => Ok(AppSW::BadBranchKey),
+            0xB00D => Ok(AppSW::VersionMismatch),
+            0xB00E => Ok(AppSW::PayloadTooLarge),
+            0xB00F => Ok(AppSW::AmountMismatch),
+            0xB010 => Ok(AppSW::EncryptionFail),
+            0xB011 => Ok(AppSW::AccountMismatch),
+            0xB012 => Ok(AppSW::SessionLimitExceeded),
+            0xB013 => Ok(AppSW::OffsetNotUnique),
             0x6e03 => Ok(AppSW::WrongApduLength),
             0x6e04 => Ok(AppSW::UserCancelled),
             _ => Err(String::from("Invalid value for AppSW (") + utils::u16_to_string(value).as_str() + ")"),
@@ -82,6 +96,35 @@ pub enum Instruction {
     GetDHSharedSecret = 0x08,
     GetRawSchnorrSignature = 0x09,
     GetScriptSchnorrSignature = 0x10,
+    ConfirmAddress = 0x11,
+    GetNetworkInfo = 0x12,
+    RecoverValue = 0x13,
+    ScriptSigCommit = 0x14,
+    // 0x15 was the unconfirmed round 2 of the interactive script signature and must not be reused
+    ScriptSigFinalizeConfirmed = 0x16,
+    GetSenderOffsetPublicKey = 0x17,
+    ProveOwnership = 0x18,
+    PartialSigCommit = 0x19,
+    PartialSigSign = 0x1A,
+    GetDerivationInfo = 0x1B,
+    EncryptValue = 0x1C,
+    GetKernelSignature = 0x1D,
+    KernelBatch = 0x1E,
+    KernelBatchSignature = 0x1F,
+    ComputeViewTag = 0x20,
+    GetProtocolConstants = 0x21,
+    SignPaymentId = 0x22,
+    ResetSigningSession = 0x23,
+    GetEncodingInfo = 0x24,
+    GetMetadataSignature = 0x25,
+    ResetReassembly = 0x26,
+    SelectAccount = 0x27,
+    GetEphemeralKey = 0x28,
+    ResetSessionLimit = 0x29,
+    GetAddressChecksum = 0x2A,
+    FinalizeOffsets = 0x2B,
+    GetCommitment = 0x2C,
+    GetScriptSignatureShare = 0x2D,
 }
 
 impl Instruction {
@@ -101,6 +144,34 @@ impl Instruction {
             0x08 => Some(Instruction::GetDHSharedSecret),
             0x09 => Some(Instruction::GetRawSchnorrSignature),
             0x10 => Some(Instruction::GetScriptSchnorrSignature),
+            0x11 => Some(Instruction::ConfirmAddress),
+            0x12 => Some(Instruction::GetNetworkInfo),
+            0x13 => Some(Instruction::RecoverValue),
+            0x14 => Some(Instruction::ScriptSigCommit),
+            0x16 => Some(Instruction::ScriptSigFinalizeConfirmed),
+            0x17 => Some(Instruction::GetSenderOffsetPublicKey),
+            0x18 => Some(Instruction::ProveOwnership),
+            0x19 => Some(Instruction::PartialSigCommit),
+            0x1A => Some(Instruction::PartialSigSign),
+            0x1B => Some(Instruction::GetDerivationInfo),
+            0x1C => Some(Instruction::EncryptValue),
+            0x1D => Some(Instruction::GetKernelSignature),
+            0x1E => Some(Instruction::KernelBatch),
+            0x1F => Some(Instruction::KernelBatchSignature),
+            0x20 => Some(Instruction::ComputeViewTag),
+            0x21 => Some(Instruction::GetProtocolConstants),
+            0x22 => Some(Instruction::SignPaymentId),
+            0x23 => Some(Instruction::ResetSigningSession),
+            0x24 => Some(Instruction::GetEncodingInfo),
+            0x25 => Some(Instruction::GetMetadataSignature),
+            0x26 => Some(Instruction::ResetReassembly),
+            0x27 => Some(Instruction::SelectAccount),
+            0x28 => Some(Instruction::GetEphemeralKey),
+            0x29 => Some(Instruction::ResetSessionLimit),
+            0x2A => Some(Instruction::GetAddressChecksum),
+            0x2B => Some(Instruction::FinalizeOffsets),
+            0x2C => Some(Instruction::GetCommitment),
+            0x2D => Some(Instruction::GetScriptSignatureShare),
             _ => None,
         }
     }
@@ -119,6 +190,7 @@ pub enum Branch {
     SenderOffsetLedger = 0x06,
     Spend = 0x07,
     RandomKey = 0x08,
+    OneSidedEphemeralLedger = 0x09,
 }
 
 impl Branch {
@@ -137,6 +209,7 @@ impl Branch {
             0x06 => Some(Branch::SenderOffsetLedger),
             0x07 => Some(Branch::Spend),
             0x08 => Some(Branch::RandomKey),
+            0x09 => Some(Branch::OneSidedEphemeralLedger),
             _ => None,
         }
     }
@@ -161,6 +234,13 @@ mod test {
             (0xB00A, AppSW::KeyDeriveFromUniform),
             (0xB00B, AppSW::RandomNonceFail),
             (0xB00C, AppSW::BadBranchKey),
+            (0xB00D, AppSW::VersionMismatch),
+            (0xB00E, AppSW::PayloadTooLarge),
+            (0xB00F, AppSW::AmountMismatch),
+            (0xB010, AppSW::EncryptionFail),
+            (0xB011, AppSW::AccountMismatch),
+            (0xB012, AppSW::SessionLimitExceeded),
+            (0xB013, AppSW::OffsetNotUnique),
             (0x6e03, AppSW::WrongApduLength),
             (0x6e04, AppSW::UserCancelled),
         ];
@@ -203,6 +283,27 @@ mod test {
                 AppSW::BadBranchKey => {
                     assert_eq!(AppSW::try_from(*value).unwrap(), *expected_app_sw);
                 },
+                AppSW::VersionMismatch => {
+                    assert_eq!(AppSW::try_from(*value).unwrap(), *expected_app_sw);
+                },
+                AppSW::PayloadTooLarge => {
+                    assert_eq!(AppSW::try_from(*value).unwrap(), *expected_app_sw);
+                },
+                AppSW::AmountMismatch => {
+                    assert_eq!(AppSW::try_from(*value).unwrap(), *expected_app_sw);
+                },
+                AppSW::EncryptionFail => {
+                    assert_eq!(AppSW::try_from(*value).unwrap(), *expected_app_sw);
+                },
+                AppSW::AccountMismatch => {
+                    assert_eq!(AppSW::try_from(*value).unwrap(), *expected_app_sw);
+                },
+                AppSW::SessionLimitExceeded => {
+                    assert_eq!(AppSW::try_from(*value).unwrap(), *expected_app_sw);
+                },
+                AppSW::OffsetNotUnique => {
+                    assert_eq!(AppSW::try_from(*value).unwrap(), *expected_app_sw);
+                },
                 AppSW::WrongApduLength => {
                     assert_eq!(AppSW::try_from(*value).unwrap(), *expected_app_sw);
                 },
@@ -226,6 +327,34 @@ mod test {
             (0x08, Instruction::GetDHSharedSecret),
             (0x09, Instruction::GetRawSchnorrSignature),
             (0x10, Instruction::GetScriptSchnorrSignature),
+            (0x11, Instruction::ConfirmAddress),
+            (0x12, Instruction::GetNetworkInfo),
+            (0x13, Instruction::RecoverValue),
+            (0x14, Instruction::ScriptSigCommit),
+            (0x16, Instruction::ScriptSigFinalizeConfirmed),
+            (0x17, Instruction::GetSenderOffsetPublicKey),
+            (0x18, Instruction::ProveOwnership),
+            (0x19, Instruction::PartialSigCommit),
+            (0x1A, Instruction::PartialSigSign),
+            (0x1B, Instruction::GetDerivationInfo),
+            (0x1C, Instruction::EncryptValue),
+            (0x1D, Instruction::GetKernelSignature),
+            (0x1E, Instruction::KernelBatch),
+            (0x1F, Instruction::KernelBatchSignature),
+            (0x20, Instruction::ComputeViewTag),
+            (0x21, Instruction::GetProtocolConstants),
+            (0x22, Instruction::SignPaymentId),
+            (0x23, Instruction::ResetSigningSession),
+            (0x24, Instruction::GetEncodingInfo),
+            (0x25, Instruction::GetMetadataSignature),
+            (0x26, Instruction::ResetReassembly),
+            (0x27, Instruction::SelectAccount),
+            (0x28, Instruction::GetEphemeralKey),
+            (0x29, Instruction::ResetSessionLimit),
+            (0x2A, Instruction::GetAddressChecksum),
+            (0x2B, Instruction::FinalizeOffsets),
+            (0x2C, Instruction::GetCommitment),
+            (0x2D, Instruction::GetScriptSignatureShare),
         ];
 
         for (expected_byte, instruction) in &mappings {
@@ -270,8 +399,122 @@ mod test {
                     assert_eq!(instruction.as_byte(), *expected_byte);
                     assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
                 },
+                Instruction::ConfirmAddress => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::GetNetworkInfo => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::RecoverValue => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::ScriptSigCommit => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::ScriptSigFinalizeConfirmed => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::GetSenderOffsetPublicKey => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::ProveOwnership => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::PartialSigCommit => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::PartialSigSign => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::GetDerivationInfo => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::EncryptValue => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::GetKernelSignature => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::KernelBatch => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::KernelBatchSignature => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::ComputeViewTag => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::GetProtocolConstants => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::SignPaymentId => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::ResetSigningSession => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::GetEncodingInfo => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::GetMetadataSignature => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::ResetReassembly => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::SelectAccount => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::GetEphemeralKey => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::ResetSessionLimit => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::GetAddressChecksum => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::FinalizeOffsets => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::GetCommitment => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
+                Instruction::GetScriptSignatureShare => {
+                    assert_eq!(instruction.as_byte(), *expected_byte);
+                    assert_eq!(Instruction::from_byte(*expected_byte), Some(*instruction));
+                },
             }
         }
+        // Retired instructions must not map to anything
+        assert_eq!(Instruction::from_byte(0x15), None);
     }
 
     #[test]
@@ -288,6 +531,7 @@ mod test {
             (0x06, Branch::SenderOffsetLedger),
             (0x07, Branch::Spend),
             (0x08, Branch::RandomKey),
+            (0x09, Branch::OneSidedEphemeralLedger),
         ];
 
         for (expected_byte, branch) in &mappings {
@@ -328,6 +572,10 @@ mod test {
                     assert_eq!(branch.as_byte(), *expected_byte);
                     assert_eq!(Branch::from_byte(*expected_byte), Some(*branch));
                 },
+                Branch::OneSidedEphemeralLedger => {
+                    assert_eq!(branch.as_byte(), *expected_byte);
+                    assert_eq!(Branch::from_byte(*expected_byte), Some(*branch));
+                },
             }
         }
     }
@@ -22,23 +22,62 @@
 
 use std::sync::Mutex;
 
-use minotari_ledger_wallet_common::common_types::{AppSW, Instruction};
+use minotari_ledger_wallet_common::common_types::{AppSW, Branch, Instruction};
 use once_cell::sync::Lazy;
 use rand::{rngs::OsRng, RngCore};
 use tari_common::configuration::Network;
 use tari_common_types::{
     key_branches::TransactionKeyManagerBranch,
+    tari_address::TariAddress,
     types::{ComAndPubSignature, Commitment, PrivateKey, PublicKey, Signature},
 };
-use tari_crypto::dhke::DiffieHellmanSharedSecret;
+use tari_crypto::{dhke::DiffieHellmanSharedSecret, hash_domain, signatures::SchnorrSignature};
 use tari_script::CheckSigSchnorrSignature;
 use tari_utilities::ByteArray;
 
 use crate::{
     error::LedgerDeviceError,
-    ledger_wallet::{Command, EXPECTED_NAME, EXPECTED_VERSION},
+    ledger_wallet::{Command, EXPECTED_NAME, EXPECTED_RESPONSE_VERSION, EXPECTED_VERSION},
 };
 
+/// The script signature payload format version understood by the ledger application
+const SCRIPT_SIGNATURE_PAYLOAD_VERSION: u8 = 1;
+/// The kernel version the ledger application builds kernel signature challenges for
+const KERNEL_SIGNATURE_VERSION: u64 = 0;
+/// The domain separation labels of the script signature challenge and the kernel signature message and challenge, in
+/// the order the ledger application reports them. They must match the labels used by the consensus code.
+pub const EXPECTED_CHALLENGE_LABELS: [&str; 5] = [
+    "script_challenge",
+    "kernel_message",
+    "kernel_signature",
+    "metadata_message",
+    "metadata_signature",
+];
+
+// Must match the domain used by the ledger application's ownership proof handler
+hash_domain!(OwnershipProofHashDomain, "com.tari.ledger.ownership_proof", 1);
+
+/// A signature proving knowledge of an account's spend key over a verifier-supplied challenge
+pub type OwnershipProofSignature = SchnorrSignature<PublicKey, PrivateKey, OwnershipProofHashDomain>;
+
+// Must match the domain used by the ledger application's payment id handler
+hash_domain!(PaymentIdHashDomain, "com.tari.ledger.payment_id", 1);
+
+/// A signature by an account's spend key over a transaction context followed by a payment id
+pub type PaymentIdSignature = SchnorrSignature<PublicKey, PrivateKey, PaymentIdHashDomain>;
+
+// Must match the domain used by the ledger application's interactive script signature handler
+hash_domain!(TransferApprovalHashDomain, "com.tari.ledger.transfer_approval", 1);
+
+/// A signature by an account's spend key over an approved script signature challenge, amount and recipient address
+pub type TransferApprovalSignature = SchnorrSignature<PublicKey, PrivateKey, TransferApprovalHashDomain>;
+
+// Must match the domain used by the ledger application's partial signature handler
+hash_domain!(PartialSignatureHashDomain, "com.tari.ledger.partial_signature", 1);
+
+/// A multi-party signature made from ledger devices' signature shares, which verifies against the aggregate public key
+pub type PartialSignature = SchnorrSignature<PublicKey, PrivateKey, PartialSignatureHashDomain>;
+
 // hash_domain!(CheckSigHashDomain, "com.tari.script.check_sig", 1);
 // type CheckSigSchnorrSignature = SchnorrSignature<RistrettoPublicKey, RistrettoSecretKey, CheckSigHashDomain>;
 
@@ -231,6 +270,18 @@ pub fn ledger_get_public_key(
     }
 }
 
+/// Get the public sender offset key for the given output index from the ledger device
+pub fn ledger_get_sender_offset_public_key(account: u64, index: u64) -> Result<PublicKey, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let data = index.to_le_bytes().to_vec();
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::GetSenderOffsetPublicKey, data)
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 33)?;
+    let sender_offset_public_key = PublicKey::from_canonical_bytes(&result.data()[1..33])?;
+    Ok(sender_offset_public_key)
+}
+
 /// Get the script signature from the ledger device
 pub fn ledger_get_script_signature(
     account: u64,
@@ -241,6 +292,56 @@ pub fn ledger_get_script_signature(
     commitment_private_key: &PrivateKey,
     commitment: &Commitment,
     script_message: [u8; 32],
+) -> Result<ComAndPubSignature, LedgerDeviceError> {
+    get_script_signature(
+        account,
+        None,
+        network,
+        version,
+        branch_key,
+        value,
+        commitment_private_key,
+        commitment,
+        script_message,
+    )
+}
+
+/// Get the script signature from the ledger device, signing with the spend key derived at `spend_index` instead of
+/// the device's default spend index
+pub fn ledger_get_script_signature_with_spend_index(
+    account: u64,
+    spend_index: u64,
+    network: Network,
+    version: u8,
+    branch_key: &PrivateKey,
+    value: &PrivateKey,
+    commitment_private_key: &PrivateKey,
+    commitment: &Commitment,
+    script_message: [u8; 32],
+) -> Result<ComAndPubSignature, LedgerDeviceError> {
+    get_script_signature(
+        account,
+        Some(spend_index),
+        network,
+        version,
+        branch_key,
+        value,
+        commitment_private_key,
+        commitment,
+        script_message,
+    )
+}
+
+fn get_script_signature(
+    account: u64,
+    spend_index: Option<u64>,
+    network: Network,
+    version: u8,
+    branch_key: &PrivateKey,
+    value: &PrivateKey,
+    commitment_private_key: &PrivateKey,
+    commitment: &Commitment,
+    script_message: [u8; 32],
 ) -> Result<ComAndPubSignature, LedgerDeviceError> {
     verify_ledger_application()?;
 
@@ -258,8 +359,18 @@ pub fn ledger_get_script_signature(
     let commitment = commitment.to_vec();
     data.extend_from_slice(&commitment);
     data.extend_from_slice(&script_message);
+    if let Some(spend_index) = spend_index {
+        data.extend_from_slice(&spend_index.to_le_bytes());
+    }
 
-    match Command::<Vec<u8>>::build_command(account, Instruction::GetScriptSignature, data).execute() {
+    match Command::<Vec<u8>>::build_versioned_command(
+        SCRIPT_SIGNATURE_PAYLOAD_VERSION,
+        account,
+        Instruction::GetScriptSignature,
+        data,
+    )
+    .execute()
+    {
         Ok(result) => {
             if result.data().len() < 161 {
                 return Err(LedgerDeviceError::Processing(format!(
@@ -308,30 +419,10 @@ pub fn ledger_get_script_offset(
         data.push(derived_key_commitment.to_vec());
     }
 
-    let commands = Command::<Vec<u8>>::chunk_command(account, Instruction::GetScriptOffset, data);
-
-    let mut result = None;
-    for command in commands {
-        match command.execute() {
-            Ok(r) => result = Some(r),
-            Err(e) => return Err(LedgerDeviceError::Processing(format!("GetScriptOffset: {}", e))),
-        }
-    }
-
-    match result {
-        Some(result) => {
-            if result.data().len() < 33 {
-                return Err(LedgerDeviceError::Processing(format!(
-                    "GetScriptOffset: expected 33 bytes, got {} ({:?})",
-                    result.data().len(),
-                    AppSW::try_from(result.retcode())?
-                )));
-            }
-            let script_offset = PrivateKey::from_canonical_bytes(&result.data()[1..33])?;
-            Ok(script_offset)
-        },
-        None => Err(LedgerDeviceError::Processing("GetScriptOffset: No result".to_string())),
-    }
+    let result = Command::<Vec<u8>>::reset_and_send_chunked(account, Instruction::GetScriptOffset, data, 33)
+        .map_err(|e| LedgerDeviceError::Processing(format!("GetScriptOffset: {}", e)))?;
+    let script_offset = PrivateKey::from_canonical_bytes(&result.data()[1..33])?;
+    Ok(script_offset)
 }
 
 /// Get the view key from the ledger device
@@ -360,12 +451,21 @@ pub fn ledger_get_dh_shared_secret(
     index: u64,
     branch: TransactionKeyManagerBranch,
     public_key: &PublicKey,
+) -> Result<DiffieHellmanSharedSecret<PublicKey>, LedgerDeviceError> {
+    get_dh_shared_secret(account, index, branch.as_byte(), public_key)
+}
+
+fn get_dh_shared_secret(
+    account: u64,
+    index: u64,
+    branch: u8,
+    public_key: &PublicKey,
 ) -> Result<DiffieHellmanSharedSecret<PublicKey>, LedgerDeviceError> {
     verify_ledger_application()?;
 
     let mut data = Vec::new();
     data.extend_from_slice(&index.to_le_bytes());
-    data.extend_from_slice(&u64::from(branch.as_byte()).to_le_bytes());
+    data.extend_from_slice(&u64::from(branch).to_le_bytes());
     data.extend_from_slice(&public_key.to_vec());
 
     match Command::<Vec<u8>>::build_command(account, Instruction::GetDHSharedSecret, data).execute() {
@@ -384,7 +484,19 @@ pub fn ledger_get_dh_shared_secret(
     }
 }
 
-///  Get the raw schnorr signature from the ledger device
+/// Get the view tag of an output from the DH secret of the account's view key and the sender public key, computed on
+/// the ledger device. Neither the view key nor the DH secret leaves the device. The sender computes the same tag with
+/// `shared_secret_to_view_tag` in `tari_core::one_sided`.
+pub fn ledger_compute_view_tag(account: u64, sender_public_key: &PublicKey) -> Result<u8, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::ComputeViewTag, sender_public_key.to_vec())
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 2)?;
+    Ok(result.data()[1])
+}
+
+///  Get the raw schnorr signature from the ledger device. The device refuses spend branch keys, which must be signed
+/// for with `ledger_get_kernel_signature` or `ledger_get_script_signature_share`.
 pub fn ledger_get_raw_schnorr_signature(
     account: u64,
     private_key_index: u64,
@@ -422,6 +534,215 @@ pub fn ledger_get_raw_schnorr_signature(
     }
 }
 
+/// Get a share of an aggregated script signature from the ledger device. The device builds the script signature
+/// challenge from the aggregated ephemeral commitment and public key, the total script public key, the commitment and
+/// the script message, and asks the user to approve the share. Unlike `ledger_get_raw_schnorr_signature`, this accepts
+/// spend branch keys.
+pub fn ledger_get_script_signature_share(
+    account: u64,
+    network: Network,
+    version: u8,
+    private_key_index: u64,
+    private_key_branch: TransactionKeyManagerBranch,
+    nonce_index: u64,
+    nonce_branch: TransactionKeyManagerBranch,
+    ephemeral_commitment: &Commitment,
+    ephemeral_pubkey: &PublicKey,
+    script_public_key: &PublicKey,
+    commitment: &Commitment,
+    script_message: [u8; 32],
+) -> Result<Signature, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&u64::from(network.as_byte()).to_le_bytes());
+    data.extend_from_slice(&u64::from(version).to_le_bytes());
+    data.extend_from_slice(&private_key_index.to_le_bytes());
+    data.extend_from_slice(&u64::from(private_key_branch.as_byte()).to_le_bytes());
+    data.extend_from_slice(&nonce_index.to_le_bytes());
+    data.extend_from_slice(&u64::from(nonce_branch.as_byte()).to_le_bytes());
+    data.extend_from_slice(ephemeral_commitment.as_bytes());
+    data.extend_from_slice(ephemeral_pubkey.as_bytes());
+    data.extend_from_slice(script_public_key.as_bytes());
+    data.extend_from_slice(commitment.as_bytes());
+    data.extend_from_slice(&script_message);
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::GetScriptSignatureShare, data)
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 65)?;
+    let signature = Signature::new(
+        PublicKey::from_canonical_bytes(&result.data()[1..33])?,
+        PrivateKey::from_canonical_bytes(&result.data()[33..65])?,
+    );
+    Ok(signature)
+}
+
+/// The fields of a kernel that the ledger device builds the kernel signature challenge from, and the keys it signs
+/// with
+#[derive(Debug, Clone)]
+pub struct KernelSigningFields {
+    pub private_key_index: u64,
+    pub private_key_branch: TransactionKeyManagerBranch,
+    pub nonce_index: u64,
+    pub nonce_branch: TransactionKeyManagerBranch,
+    /// The sum of the public nonces of the other signers. The device adds its own nonce, which it derives from the
+    /// kernel fields, to get the total nonce.
+    pub other_public_nonces: PublicKey,
+    pub total_excess: PublicKey,
+    pub fee: u64,
+    pub lock_height: u64,
+    pub features: u8,
+    pub burn_commitment: Option<Commitment>,
+}
+
+impl KernelSigningFields {
+    fn to_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&KERNEL_SIGNATURE_VERSION.to_le_bytes());
+        data.extend_from_slice(&self.private_key_index.to_le_bytes());
+        data.extend_from_slice(&u64::from(self.private_key_branch.as_byte()).to_le_bytes());
+        data.extend_from_slice(&self.nonce_index.to_le_bytes());
+        data.extend_from_slice(&u64::from(self.nonce_branch.as_byte()).to_le_bytes());
+        data.extend_from_slice(self.other_public_nonces.as_bytes());
+        data.extend_from_slice(self.total_excess.as_bytes());
+        data.extend_from_slice(&self.fee.to_le_bytes());
+        data.extend_from_slice(&self.lock_height.to_le_bytes());
+        data.push(self.features);
+        if let Some(burn_commitment) = &self.burn_commitment {
+            data.extend_from_slice(burn_commitment.as_bytes());
+        }
+        data
+    }
+}
+
+/// Get this device's share of a kernel excess signature from the ledger device. The device builds the kernel
+/// signature challenge itself and asks the user to approve the fee before signing. The public nonce of the returned
+/// share is the device's own nonce, so the total nonce is `other_public_nonces` plus that nonce.
+pub fn ledger_get_kernel_signature(
+    account: u64,
+    network: Network,
+    kernel: &KernelSigningFields,
+) -> Result<Signature, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let mut data = u64::from(network.as_byte()).to_le_bytes().to_vec();
+    data.extend_from_slice(&kernel.to_bytes());
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::GetKernelSignature, data)
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 65)?;
+    let signature = Signature::new(
+        PublicKey::from_canonical_bytes(&result.data()[1..33])?,
+        PrivateKey::from_canonical_bytes(&result.data()[33..65])?,
+    );
+    Ok(signature)
+}
+
+/// Get this device's shares of the excess signatures of several kernels from the ledger device, in the order of the
+/// kernels. The user approves the number of kernels and their total fee once for the whole batch.
+pub fn ledger_get_kernel_signatures(
+    account: u64,
+    network: Network,
+    kernels: &[KernelSigningFields],
+) -> Result<Vec<Signature>, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let mut instructions = u64::from(network.as_byte()).to_le_bytes().to_vec();
+    instructions.extend_from_slice(&(kernels.len() as u64).to_le_bytes());
+    let mut data = vec![instructions];
+    data.extend(kernels.iter().map(KernelSigningFields::to_bytes));
+
+    // Only the last chunk, which the user approves, replies with data
+    Command::<Vec<u8>>::reset_and_send_chunked(account, Instruction::KernelBatch, data, 1)?;
+
+    let mut signatures = Vec::with_capacity(kernels.len());
+    for index in 0..kernels.len() as u64 {
+        let result =
+            Command::<Vec<u8>>::build_command(account, Instruction::KernelBatchSignature, index.to_le_bytes().to_vec())
+                .execute_checking_version(EXPECTED_RESPONSE_VERSION, 65)?;
+        signatures.push(Signature::new(
+            PublicKey::from_canonical_bytes(&result.data()[1..33])?,
+            PrivateKey::from_canonical_bytes(&result.data()[33..65])?,
+        ));
+    }
+    Ok(signatures)
+}
+
+/// The largest part of the encoded output fields sent in a single `GetMetadataSignature` chunk
+const METADATA_FIELDS_CHUNK_LENGTH: usize = 250;
+
+/// The fields of an output that the ledger device builds the metadata signature challenge from, and the keys it signs
+/// with. The script, features, covenant and encrypted data are consensus encoded.
+#[derive(Debug, Clone)]
+pub struct MetadataSigningFields {
+    pub version: u8,
+    pub sender_offset_index: u64,
+    pub nonce_index: u64,
+    pub nonce_branch: TransactionKeyManagerBranch,
+    pub commitment: Commitment,
+    pub ephemeral_commitment: Commitment,
+    pub script: Vec<u8>,
+    pub features: Vec<u8>,
+    pub covenant: Vec<u8>,
+    pub encrypted_data: Vec<u8>,
+    pub minimum_value_promise: u64,
+}
+
+impl MetadataSigningFields {
+    fn header_bytes(&self, network: Network) -> Vec<u8> {
+        let mut data = u64::from(network.as_byte()).to_le_bytes().to_vec();
+        data.extend_from_slice(&u64::from(self.version).to_le_bytes());
+        data.extend_from_slice(&self.sender_offset_index.to_le_bytes());
+        data.extend_from_slice(&self.nonce_index.to_le_bytes());
+        data.extend_from_slice(&u64::from(self.nonce_branch.as_byte()).to_le_bytes());
+        data.extend_from_slice(self.commitment.as_bytes());
+        data.extend_from_slice(self.ephemeral_commitment.as_bytes());
+        data.extend_from_slice(&(self.script.len() as u64).to_le_bytes());
+        data.extend_from_slice(&(self.features.len() as u64).to_le_bytes());
+        data.extend_from_slice(&(self.covenant.len() as u64).to_le_bytes());
+        data
+    }
+
+    fn message_bytes(&self) -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&self.script);
+        data.extend_from_slice(&self.features);
+        data.extend_from_slice(&self.covenant);
+        data.extend_from_slice(&self.encrypted_data);
+        data.extend_from_slice(&self.minimum_value_promise.to_le_bytes());
+        data
+    }
+}
+
+/// Get the sender's part of an output's metadata signature from the ledger device, signed with the sender offset key.
+/// The device builds the metadata signature challenge itself and asks the user to approve the output's covenant
+/// before signing. The commitment part of the signature is left for the receiver to add.
+pub fn ledger_get_sender_metadata_signature(
+    account: u64,
+    network: Network,
+    output: &MetadataSigningFields,
+) -> Result<ComAndPubSignature, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let mut data = vec![output.header_bytes(network)];
+    data.extend(
+        output
+            .message_bytes()
+            .chunks(METADATA_FIELDS_CHUNK_LENGTH)
+            .map(<[u8]>::to_vec),
+    );
+
+    // Only the last chunk, which the user approves, replies with the signature
+    let result = Command::<Vec<u8>>::reset_and_send_chunked(account, Instruction::GetMetadataSignature, data, 161)?;
+    let data = result.data();
+    let signature = ComAndPubSignature::new(
+        Commitment::from_canonical_bytes(&data[1..33])?,
+        PublicKey::from_canonical_bytes(&data[33..65])?,
+        PrivateKey::from_canonical_bytes(&data[65..97])?,
+        PrivateKey::from_canonical_bytes(&data[97..129])?,
+        PrivateKey::from_canonical_bytes(&data[129..161])?,
+    );
+    Ok(signature)
+}
+
 /// Get the script schnorr signature from the ledger device
 pub fn ledger_get_script_schnorr_signature(
     account: u64,
@@ -461,3 +782,461 @@ pub fn ledger_get_script_schnorr_signature(
         ))),
     }
 }
+
+/// Ask the ledger device to prove ownership of the account's spend key by signing the verifier's challenge. The
+/// user has to confirm the challenge on the device. The proof verifies against the account's public spend key.
+pub fn ledger_prove_ownership(
+    account: u64,
+    challenge: &[u8; 32],
+) -> Result<OwnershipProofSignature, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::ProveOwnership, challenge.to_vec())
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 65)?;
+    let signature = OwnershipProofSignature::new(
+        PublicKey::from_canonical_bytes(&result.data()[1..33])?,
+        PrivateKey::from_canonical_bytes(&result.data()[33..65])?,
+    );
+    Ok(signature)
+}
+
+/// The protocol constants the ledger application was built with
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LedgerProtocolConstants {
+    pub response_version: u8,
+    pub static_spend_index: u64,
+    pub static_view_index: u64,
+    pub challenge_labels: Vec<String>,
+}
+
+/// Get the protocol constants the ledger application was built with
+pub fn ledger_get_protocol_constants(account: u64) -> Result<LedgerProtocolConstants, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::GetProtocolConstants, vec![])
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 18)?;
+    let data = result.data();
+    let read_u64 = |offset: usize| u64::from_le_bytes(std::array::from_fn(|i| data[offset + i]));
+    let static_spend_index = read_u64(1);
+    let static_view_index = read_u64(9);
+
+    let num_labels = usize::from(data[17]);
+    let mut challenge_labels = Vec::with_capacity(num_labels);
+    let mut offset = 18;
+    for _ in 0..num_labels {
+        let len = usize::from(*data.get(offset).ok_or(LedgerDeviceError::ShortResponse {
+            expected: offset + 1,
+            actual: data.len(),
+        })?);
+        let label = data
+            .get(offset + 1..offset + 1 + len)
+            .ok_or(LedgerDeviceError::ShortResponse {
+                expected: offset + 1 + len,
+                actual: data.len(),
+            })?;
+        challenge_labels.push(String::from_utf8_lossy(label).into_owned());
+        offset += 1 + len;
+    }
+
+    Ok(LedgerProtocolConstants {
+        response_version: data[0],
+        static_spend_index,
+        static_view_index,
+        challenge_labels,
+    })
+}
+
+/// The lengths of the encodings the ledger application uses in its responses
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LedgerEncodingInfo {
+    /// The length of a compressed public key or commitment
+    pub point_length: usize,
+    pub scalar_length: usize,
+    pub schnorr_signature_length: usize,
+    pub com_and_pub_signature_length: usize,
+}
+
+/// Get the lengths of the encodings the ledger application uses in its responses
+pub fn ledger_get_encoding_info(account: u64) -> Result<LedgerEncodingInfo, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::GetEncodingInfo, vec![])
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 5)?;
+    let data = result.data();
+    Ok(LedgerEncodingInfo {
+        point_length: usize::from(data[1]),
+        scalar_length: usize::from(data[2]),
+        schnorr_signature_length: usize::from(data[3]),
+        com_and_pub_signature_length: usize::from(data[4]),
+    })
+}
+
+/// Check that the ledger application uses the response version and challenge labels this host was built for, e.g. at
+/// startup, so that a mismatch is reported before it produces signatures that do not verify
+pub fn ledger_verify_protocol_constants(account: u64) -> Result<LedgerProtocolConstants, LedgerDeviceError> {
+    let constants = ledger_get_protocol_constants(account)?;
+    if constants.response_version != EXPECTED_RESPONSE_VERSION {
+        return Err(LedgerDeviceError::ResponseVersionMismatch {
+            expected: EXPECTED_RESPONSE_VERSION,
+            found: constants.response_version,
+        });
+    }
+    if constants.challenge_labels != EXPECTED_CHALLENGE_LABELS {
+        return Err(LedgerDeviceError::ProtocolMismatch(format!(
+            "challenge labels: expected {:?}, found {:?}",
+            EXPECTED_CHALLENGE_LABELS, constants.challenge_labels
+        )));
+    }
+    Ok(constants)
+}
+
+/// Ask the ledger device to sign the payment id of a transaction with the account's spend key, binding it to the
+/// 32 byte transaction `context`. The user has to confirm the payment id on the device. The signature verifies
+/// against the account's public spend key over the context followed by the payment id.
+pub fn ledger_sign_payment_id(
+    account: u64,
+    context: &[u8; 32],
+    payment_id: &[u8],
+) -> Result<PaymentIdSignature, LedgerDeviceError> {
+    verify_ledger_application()?;
+    if payment_id.is_empty() {
+        return Err(LedgerDeviceError::Processing(
+            "SignPaymentId: the payment id is empty".to_string(),
+        ));
+    }
+
+    let mut data = context.to_vec();
+    data.extend_from_slice(payment_id);
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::SignPaymentId, data)
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 65)?;
+    let signature = PaymentIdSignature::new(
+        PublicKey::from_canonical_bytes(&result.data()[1..33])?,
+        PrivateKey::from_canonical_bytes(&result.data()[33..65])?,
+    );
+    Ok(signature)
+}
+
+/// Get the BIP32 paths the ledger device derives the account's spend and view keys from, in that order. Hardened path
+/// components have the top bit set.
+pub fn ledger_get_derivation_info(account: u64) -> Result<([u32; 6], [u32; 6]), LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::GetDerivationInfo, vec![])
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 49)?;
+    let data = &result.data()[1..49];
+    let component = |i: usize| u32::from_le_bytes([data[4 * i], data[4 * i + 1], data[4 * i + 2], data[4 * i + 3]]);
+    let spend_path = std::array::from_fn(component);
+    let view_path = std::array::from_fn(|i| component(i + 6));
+    Ok((spend_path, view_path))
+}
+
+/// Encrypt the value, mask and encoded payment id of the output with the given commitment to the account's view key on
+/// the ledger device. Returns the bytes of the encrypted data.
+pub fn ledger_encrypt_value(
+    account: u64,
+    commitment: &Commitment,
+    value: u64,
+    mask: &PrivateKey,
+    payment_id: &[u8],
+) -> Result<Vec<u8>, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let mut data = Vec::new();
+    data.extend_from_slice(commitment.as_bytes());
+    data.extend_from_slice(&value.to_le_bytes());
+    data.extend_from_slice(mask.as_bytes());
+    data.extend_from_slice(payment_id);
+
+    // tag, nonce, value, mask and payment id
+    let expected_len = 1 + 16 + 24 + 8 + 32 + payment_id.len();
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::EncryptValue, data)
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, expected_len)?;
+    Ok(result.data()[1..expected_len].to_vec())
+}
+
+/// Ask the ledger device to display the account's receive address and wait for the user to confirm it
+pub fn ledger_confirm_address(account: u64, network: Network) -> Result<(), LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let data = u64::from(network.as_byte()).to_le_bytes().to_vec();
+
+    match Command::<Vec<u8>>::build_command(account, Instruction::ConfirmAddress, data).execute() {
+        Ok(result) => {
+            if result.data().is_empty() {
+                return Err(LedgerDeviceError::Processing(format!(
+                    "ConfirmAddress: expected 1 byte, got 0 ({:?})",
+                    AppSW::try_from(result.retcode())?
+                )));
+            }
+            Ok(())
+        },
+        Err(e) => Err(LedgerDeviceError::Processing(format!("ConfirmAddress: {}", e))),
+    }
+}
+
+/// Get the account's receive address as computed on the ledger device, which also shows it in base58. It must match
+/// the address the wallet displays, including the checksum at its end.
+pub fn ledger_get_address_checksum(account: u64, network: Network) -> Result<TariAddress, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let data = u64::from(network.as_byte()).to_le_bytes().to_vec();
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::GetAddressChecksum, data)
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 2)?;
+    let base58 = std::str::from_utf8(&result.data()[1..])
+        .map_err(|e| LedgerDeviceError::Processing(format!("GetAddressChecksum: {}", e)))?;
+    TariAddress::from_base58(base58).map_err(|e| LedgerDeviceError::Processing(format!("GetAddressChecksum: {}", e)))
+}
+
+/// Get the network the ledger application is pinned to, or `None` if it is not pinned to a network
+pub fn ledger_get_network_info(account: u64) -> Result<Option<Network>, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::GetNetworkInfo, vec![])
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 3)?;
+    let data = result.data();
+    if data[1] == 0 {
+        return Ok(None);
+    }
+    let network =
+        Network::try_from(data[2]).map_err(|e| LedgerDeviceError::Processing(format!("GetNetworkInfo: {}", e)))?;
+    Ok(Some(network))
+}
+
+/// Get the key that decrypts the encrypted value and mask of the output with the given commitment, derived on the
+/// ledger device from its private view key. The range proofs cannot be rewound, so this is how the opening of an
+/// output is recovered; the recovered mask can be checked against the commitment with the range proof service's
+/// `verify_mask`.
+pub fn ledger_get_value_recovery_key(account: u64, commitment: &Commitment) -> Result<[u8; 32], LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let data = commitment.to_vec();
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::RecoverValue, data)
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 33)?;
+    let mut recovery_key = [0u8; 32];
+    recovery_key.copy_from_slice(&result.data()[1..33]);
+    Ok(recovery_key)
+}
+
+/// Start a multi-party Schnorr signature on the ledger device. The device keeps its nonce and returns the public
+/// nonce, which must be added to the other parties' public nonces to form the aggregate nonce.
+pub fn ledger_partial_sig_commit(account: u64) -> Result<PublicKey, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::PartialSigCommit, vec![])
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 33)?;
+    let public_nonce = PublicKey::from_canonical_bytes(&result.data()[1..33])?;
+    Ok(public_nonce)
+}
+
+/// Get the device's share of a multi-party Schnorr signature started with `ledger_partial_sig_commit`. The device
+/// builds the challenge from the aggregate nonce, the aggregate public key and the message, and refuses spend branch
+/// keys. The shares of all parties sum to a `PartialSignature` over the message, with the aggregate nonce as its public
+/// nonce, that verifies against the aggregate public key.
+pub fn ledger_partial_sig_sign(
+    account: u64,
+    private_key_index: u64,
+    private_key_branch: TransactionKeyManagerBranch,
+    aggregate_nonce: &PublicKey,
+    aggregate_public_key: &PublicKey,
+    message: &[u8; 32],
+) -> Result<PrivateKey, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&private_key_index.to_le_bytes());
+    data.extend_from_slice(&u64::from(private_key_branch.as_byte()).to_le_bytes());
+    data.extend_from_slice(aggregate_nonce.as_bytes());
+    data.extend_from_slice(aggregate_public_key.as_bytes());
+    data.extend_from_slice(message);
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::PartialSigSign, data)
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 33)?;
+    let share = PrivateKey::from_canonical_bytes(&result.data()[1..33])?;
+    Ok(share)
+}
+
+/// Start an interactive script signature on the ledger device. The device keeps the nonces and returns the ephemeral
+/// commitment and ephemeral public key.
+pub fn ledger_script_sig_commit(account: u64) -> Result<(Commitment, PublicKey), LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::ScriptSigCommit, vec![])
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 65)?;
+    let data = result.data();
+    let ephemeral_commitment = Commitment::from_canonical_bytes(&data[1..33])?;
+    let ephemeral_pubkey = PublicKey::from_canonical_bytes(&data[33..65])?;
+    Ok((ephemeral_commitment, ephemeral_pubkey))
+}
+
+/// Complete an interactive script signature started with `ledger_script_sig_commit` by signing the challenge, once the
+/// user has approved the transfer amount and recipient address on the device. The amount must equal the committed
+/// value. Along with the script signature the device returns a signature by the account's spend key over the
+/// challenge, followed by the amount as little endian bytes and the recipient address bytes.
+pub fn ledger_script_sig_finalize_confirmed(
+    account: u64,
+    branch_key: &PrivateKey,
+    commitment_private_key: &PrivateKey,
+    challenge: &[u8; 64],
+    amount: u64,
+    recipient: &TariAddress,
+) -> Result<(ComAndPubSignature, TransferApprovalSignature), LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let mut data = Vec::new();
+    data.extend_from_slice(&branch_key.to_vec());
+    data.extend_from_slice(&PrivateKey::from(amount).to_vec());
+    data.extend_from_slice(&commitment_private_key.to_vec());
+    data.extend_from_slice(challenge);
+    data.extend_from_slice(&amount.to_le_bytes());
+    data.extend_from_slice(&recipient.to_vec());
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::ScriptSigFinalizeConfirmed, data)
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 225)?;
+    let data = result.data();
+    let signature = ComAndPubSignature::new(
+        Commitment::from_canonical_bytes(&data[1..33])?,
+        PublicKey::from_canonical_bytes(&data[33..65])?,
+        PrivateKey::from_canonical_bytes(&data[65..97])?,
+        PrivateKey::from_canonical_bytes(&data[97..129])?,
+        PrivateKey::from_canonical_bytes(&data[129..161])?,
+    );
+    let approval = TransferApprovalSignature::new(
+        PublicKey::from_canonical_bytes(&data[161..193])?,
+        PrivateKey::from_canonical_bytes(&data[193..225])?,
+    );
+    Ok((signature, approval))
+}
+
+/// Discard any interactive or multi-party signing round that was started on the ledger device but not finished. The
+/// device also discards it when it receives any other signing command, but keeps it across commands that do not sign.
+pub fn ledger_reset_signing_session(account: u64) -> Result<(), LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    Command::<Vec<u8>>::build_command(account, Instruction::ResetSigningSession, vec![])
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 1)?;
+    Ok(())
+}
+
+/// Ask the user to confirm switching the ledger device to `account`, which shows a fingerprint of the account's public
+/// spend key. Once confirmed, the device rejects signing commands for any other account with
+/// [AppSW::AccountMismatch]. Returns the account's public spend key.
+pub fn ledger_select_account(account: u64) -> Result<PublicKey, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::SelectAccount, vec![])
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 33)?;
+    let public_spend_key = PublicKey::from_canonical_bytes(&result.data()[1..33])?;
+    Ok(public_spend_key)
+}
+
+/// Ask the user to allow the ledger device to make more signatures. The device rejects signing commands with
+/// [AppSW::SessionLimitExceeded] once it made its maximum number of signatures since it was opened or since the user
+/// last allowed more.
+pub fn ledger_reset_session_limit(account: u64) -> Result<(), LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    Command::<Vec<u8>>::build_command(account, Instruction::ResetSessionLimit, vec![])
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 1)?;
+    Ok(())
+}
+
+/// Get the public ephemeral sender key of the one-sided payment output at `output_index` from the ledger device. The
+/// device derives the private key from the output index, so the shared secret with the recipient's view key can be
+/// requested later with [ledger_get_ephemeral_dh_shared_secret] without the private key leaving the device.
+pub fn ledger_get_ephemeral_public_key(account: u64, output_index: u64) -> Result<PublicKey, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let data = output_index.to_le_bytes().to_vec();
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::GetEphemeralKey, data)
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 33)?;
+    let ephemeral_public_key = PublicKey::from_canonical_bytes(&result.data()[1..33])?;
+    Ok(ephemeral_public_key)
+}
+
+/// Get the Diffie-Hellman shared secret of the ephemeral sender key of the output at `output_index`, as returned by
+/// [ledger_get_ephemeral_public_key], and the recipient's public view key from the ledger device
+pub fn ledger_get_ephemeral_dh_shared_secret(
+    account: u64,
+    output_index: u64,
+    recipient_view_key: &PublicKey,
+) -> Result<DiffieHellmanSharedSecret<PublicKey>, LedgerDeviceError> {
+    get_dh_shared_secret(
+        account,
+        output_index,
+        Branch::OneSidedEphemeralLedger.as_byte(),
+        recipient_view_key,
+    )
+}
+
+/// The most offset contributions sent in a single `FinalizeOffsets` chunk
+const OFFSET_CONTRIBUTIONS_PER_CHUNK: usize = 10;
+
+/// A key of the ledger device that is added to, or subtracted from, a transaction offset
+#[derive(Debug, Clone)]
+pub struct OffsetContribution {
+    pub index: u64,
+    pub branch: TransactionKeyManagerBranch,
+    pub subtract: bool,
+}
+
+impl OffsetContribution {
+    fn to_bytes(&self, offset: u8) -> Vec<u8> {
+        let mut data = vec![offset, u8::from(self.subtract)];
+        data.extend_from_slice(&self.index.to_le_bytes());
+        data.extend_from_slice(&u64::from(self.branch.as_byte()).to_le_bytes());
+        data
+    }
+}
+
+/// Get the kernel offset and the script offset of a transaction the ledger device fully owns, combined on the device
+/// from its keys so the individual keys never leave it. Only sender offset and random keys may contribute, and the
+/// user must approve the offsets. A key may only contribute to one offset while the ledger application is open, and
+/// each offset must be combined from at least two keys, otherwise the device replies with [AppSW::OffsetNotUnique].
+pub fn ledger_finalize_offsets(
+    account: u64,
+    kernel_offset_keys: &[OffsetContribution],
+    script_offset_keys: &[OffsetContribution],
+) -> Result<(PrivateKey, PrivateKey), LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let contributions = kernel_offset_keys
+        .iter()
+        .map(|key| key.to_bytes(0))
+        .chain(script_offset_keys.iter().map(|key| key.to_bytes(1)))
+        .collect::<Vec<_>>();
+    let mut data = vec![vec![]];
+    data.extend(
+        contributions
+            .chunks(OFFSET_CONTRIBUTIONS_PER_CHUNK)
+            .map(|chunk| chunk.concat()),
+    );
+
+    let result = Command::<Vec<u8>>::reset_and_send_chunked(account, Instruction::FinalizeOffsets, data, 65)?;
+    let kernel_offset = PrivateKey::from_canonical_bytes(&result.data()[1..33])?;
+    let script_offset = PrivateKey::from_canonical_bytes(&result.data()[33..65])?;
+    Ok((kernel_offset, script_offset))
+}
+
+/// Get the commitment to `value` with the blinding factor the ledger device derives for the output at `output_index`
+/// on `branch`. The host can compare it with the commitment it built to catch a key derivation mismatch before it asks
+/// the device for signatures.
+pub fn ledger_get_commitment(
+    account: u64,
+    value: u64,
+    output_index: u64,
+    branch: TransactionKeyManagerBranch,
+) -> Result<Commitment, LedgerDeviceError> {
+    verify_ledger_application()?;
+
+    let mut data = value.to_le_bytes().to_vec();
+    data.extend_from_slice(&output_index.to_le_bytes());
+    data.extend_from_slice(&u64::from(branch.as_byte()).to_le_bytes());
+
+    let result = Command::<Vec<u8>>::build_command(account, Instruction::GetCommitment, data)
+        .execute_checking_version(EXPECTED_RESPONSE_VERSION, 33)?;
+    let commitment = Commitment::from_canonical_bytes(&result.data()[1..33])?;
+    Ok(commitment)
+}
@@ -20,6 +20,8 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
+use std::time::Duration;
+
 use serde::{Deserialize, Serialize};
 use tari_crypto::tari_utilities::ByteArrayError;
 use thiserror::Error;
@@ -45,6 +47,21 @@ pub enum LedgerDeviceError {
     /// Conversion error to or from ledger
     #[error("Conversion failed: {0}")]
     ByteArrayError(String),
+    /// The ledger application returned fewer bytes than expected
+    #[error("Short response from ledger: expected at least {expected} bytes, got {actual}")]
+    ShortResponse { expected: usize, actual: usize },
+    /// The ledger application answered in a different response version than the host understands
+    #[error("Ledger response version mismatch: expected {expected}, found {found}")]
+    ResponseVersionMismatch { expected: u8, found: u8 },
+    /// The ledger application was built with different protocol constants than the host
+    #[error("Ledger protocol mismatch: {0}")]
+    ProtocolMismatch(String),
+    /// The device did not answer an exchange in time
+    #[error("Ledger exchange timed out after {0:?}")]
+    Timeout(Duration),
+    /// Error exchanging APDUs over a relay
+    #[error("Ledger relay error `{0}`")]
+    Relay(String),
     /// Not yet supported
     #[error("Ledger is not fully supported")]
     NotSupported,
@@ -20,19 +20,44 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::ops::Deref;
+use std::{
+    ops::Deref,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        mpsc::{self, RecvTimeoutError},
+        Arc,
+        Mutex,
+    },
+    thread,
+    time::Duration,
+};
 
 use ledger_transport::{APDUAnswer, APDUCommand};
 use ledger_transport_hid::{hidapi::HidApi, TransportNativeHID};
-use minotari_ledger_wallet_common::common_types::Instruction;
+use minotari_ledger_wallet_common::common_types::{AppSW, Instruction};
 use once_cell::sync::Lazy;
 use tari_utilities::ByteArray;
+use tokio::task;
 
-use crate::error::LedgerDeviceError;
+use crate::{error::LedgerDeviceError, transport::LedgerTransport};
 
 pub const EXPECTED_NAME: &str = "minotari_ledger_wallet";
 pub const EXPECTED_VERSION: &str = "1.0.0-pre.16";
+/// The version byte the ledger application prepends to every successful response
+pub const EXPECTED_RESPONSE_VERSION: u8 = 1;
+/// How long an exchange with the device may take before it is abandoned, unless changed with
+/// [set_exchange_timeout]. Signing commands only complete once the user has approved them on the device, so this
+/// leaves time for that.
+pub const DEFAULT_EXCHANGE_TIMEOUT: Duration = Duration::from_secs(120);
 const WALLET_CLA: u8 = 0x80;
+const SW_SUCCESS: u16 = 0x9000;
+
+static EXCHANGE_TIMEOUT_MS: AtomicU64 = AtomicU64::new(DEFAULT_EXCHANGE_TIMEOUT.as_millis() as u64);
+/// The HID handle exchanges go through, opened by the first exchange and closed by [reset_transport]
+static TRANSPORT: Mutex<Option<Arc<TransportNativeHID>>> = Mutex::new(None);
+/// Serializes exchanges with the device. [reset_transport] replaces the lock, so that an exchange that never
+/// completes does not hold up the exchanges after it.
+static EXCHANGE_LOCK: Lazy<Mutex<Arc<Mutex<()>>>> = Lazy::new(|| Mutex::new(Arc::new(Mutex::new(()))));
 
 pub fn get_transport() -> Result<TransportNativeHID, LedgerDeviceError> {
     let hid = hidapi()?;
@@ -40,6 +65,90 @@ pub fn get_transport() -> Result<TransportNativeHID, LedgerDeviceError> {
     Ok(transport)
 }
 
+/// Sets how long an exchange with the device may take before it fails with [LedgerDeviceError::Timeout]
+pub fn set_exchange_timeout(timeout: Duration) {
+    let timeout_ms = u64::try_from(timeout.as_millis()).unwrap_or(u64::MAX);
+    EXCHANGE_TIMEOUT_MS.store(timeout_ms, Ordering::Relaxed);
+}
+
+/// Returns how long an exchange with the device may take before it fails with [LedgerDeviceError::Timeout]
+pub fn exchange_timeout() -> Duration {
+    Duration::from_millis(EXCHANGE_TIMEOUT_MS.load(Ordering::Relaxed))
+}
+
+/// Recovers from an exchange that timed out because the device stopped answering. The HID handle is closed and
+/// reopened, and later exchanges no longer wait for the stuck one, whose answer is discarded if it ever arrives. The
+/// old handle is only released once the stuck exchange returns.
+pub fn reset_transport() -> Result<(), LedgerDeviceError> {
+    *EXCHANGE_LOCK.lock().unwrap_or_else(|e| e.into_inner()) = Arc::new(Mutex::new(()));
+    let mut transport = TRANSPORT.lock().unwrap_or_else(|e| e.into_inner());
+    *transport = None;
+    *transport = Some(Arc::new(get_transport()?));
+    Ok(())
+}
+
+fn shared_transport() -> Result<Arc<TransportNativeHID>, LedgerDeviceError> {
+    let mut transport = TRANSPORT.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(transport) = transport.as_ref() {
+        return Ok(transport.clone());
+    }
+    let opened = Arc::new(get_transport()?);
+    *transport = Some(opened.clone());
+    Ok(opened)
+}
+
+fn exchange_with_device(command: &APDUCommand<Vec<u8>>) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError> {
+    shared_transport()?.exchange(command).map_err(|e| {
+        // The device may have been unplugged, so the next exchange reopens the handle
+        *TRANSPORT.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        LedgerDeviceError::NativeTransport(e.to_string())
+    })
+}
+
+/// Serializes exchanges with the device, so that an exchange always completes before the next one starts
+fn exclusive<T>(exchange: impl FnOnce() -> T) -> T {
+    let lock = EXCHANGE_LOCK.lock().unwrap_or_else(|e| e.into_inner()).clone();
+    let _guard = lock.lock().unwrap_or_else(|e| e.into_inner());
+    exchange()
+}
+
+/// Runs the exchange exclusively on its own thread and waits for it for at most `timeout`. An exchange that times out
+/// keeps running, and keeps the exchanges after it waiting until it finishes or [reset_transport] is called.
+fn exclusive_with_timeout<T, F>(timeout: Duration, exchange: F) -> Result<T, LedgerDeviceError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, LedgerDeviceError> + Send + 'static,
+{
+    let (sender, receiver) = mpsc::channel();
+    thread::Builder::new()
+        .name("ledger-exchange".to_string())
+        .spawn(move || {
+            // The receiver is gone if the exchange timed out, in which case its result is not needed
+            let _result = sender.send(exclusive(exchange));
+        })
+        .map_err(|e| LedgerDeviceError::Processing(format!("Could not start the ledger exchange: {}", e)))?;
+    match receiver.recv_timeout(timeout) {
+        Ok(result) => result,
+        Err(RecvTimeoutError::Timeout) => Err(LedgerDeviceError::Timeout(timeout)),
+        Err(RecvTimeoutError::Disconnected) => Err(LedgerDeviceError::Processing(
+            "Ledger exchange thread panicked".to_string(),
+        )),
+    }
+}
+
+/// Runs the exchange to completion on a blocking thread. Dropping the returned future does not interrupt the exchange;
+/// its result is discarded and the next exchange waits for it to finish.
+async fn spawn_exclusive<T, F>(exchange: F) -> Result<T, LedgerDeviceError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, LedgerDeviceError> + Send + 'static,
+{
+    let timeout = exchange_timeout();
+    task::spawn_blocking(move || exclusive_with_timeout(timeout, exchange))
+        .await
+        .map_err(|e| LedgerDeviceError::Processing(format!("Ledger exchange task failed: {}", e)))?
+}
+
 fn hidapi() -> Result<&'static HidApi, LedgerDeviceError> {
     static HIDAPI: Lazy<Result<HidApi, String>> =
         Lazy::new(|| HidApi::new().map_err(|e| format!("Unable to get HIDAPI: {}", e)));
@@ -47,6 +156,18 @@ fn hidapi() -> Result<&'static HidApi, LedgerDeviceError> {
     HIDAPI.as_ref().map_err(|e| LedgerDeviceError::HidApi(e.to_string()))
 }
 
+/// Checks the version byte that starts the response data
+fn check_response_version(data: &[u8], expected: u8) -> Result<(), LedgerDeviceError> {
+    match data.first() {
+        Some(found) if *found == expected => Ok(()),
+        Some(found) => Err(LedgerDeviceError::ResponseVersionMismatch {
+            expected,
+            found: *found,
+        }),
+        None => Err(LedgerDeviceError::ShortResponse { expected: 1, actual: 0 }),
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Command<D> {
     inner: APDUCommand<D>,
@@ -57,19 +178,73 @@ impl<D: Deref<Target = [u8]>> Command<D> {
         Self { inner }
     }
 
+    /// Execute the command on the attached HID device. Fails with [LedgerDeviceError::Timeout] if the device does not
+    /// answer within the [exchange_timeout]; call [reset_transport] before sending more commands after that.
     pub fn execute(&self) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError> {
-        get_transport()?
-            .exchange(&self.inner)
-            .map_err(|e| LedgerDeviceError::NativeTransport(e.to_string()))
+        let command = self.to_owned_command();
+        exclusive_with_timeout(exchange_timeout(), move || exchange_with_device(&command))
+    }
+
+    /// Execute the command and check that the device answered with a success status word and at least `min_len` bytes
+    /// of data, so that callers can slice the response without panicking
+    pub fn execute_expecting(&self, min_len: usize) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError> {
+        let answer = self.execute()?;
+        if answer.retcode() != SW_SUCCESS {
+            let status = AppSW::try_from(answer.retcode()).map_or_else(|e| e, |sw| format!("{:?}", sw));
+            return Err(LedgerDeviceError::Instruction(status));
+        }
+        if answer.data().len() < min_len {
+            return Err(LedgerDeviceError::ShortResponse {
+                expected: min_len,
+                actual: answer.data().len(),
+            });
+        }
+        Ok(answer)
     }
 
-    pub fn execute_with_transport(
+    /// Execute the command and check that the device answered with a success status word, that the first byte of the
+    /// response is the `expected` response version and that the response holds at least `min_len` bytes including the
+    /// version byte, so that callers neither misparse a response in another format nor panic slicing a short one
+    pub fn execute_checking_version(
         &self,
-        transport: &TransportNativeHID,
+        expected: u8,
+        min_len: usize,
     ) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError> {
-        transport
-            .exchange(&self.inner)
-            .map_err(|e| LedgerDeviceError::NativeTransport(e.to_string()))
+        let answer = self.execute_expecting(1)?;
+        check_response_version(answer.data(), expected)?;
+        if answer.data().len() < min_len {
+            return Err(LedgerDeviceError::ShortResponse {
+                expected: min_len,
+                actual: answer.data().len(),
+            });
+        }
+        Ok(answer)
+    }
+
+    /// Asynchronous version of [Command::execute]. This is cancel-safe: if the returned future is dropped, the exchange
+    /// still runs to completion so the device is never left part way through an APDU exchange.
+    pub async fn execute_async(&self) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError> {
+        let command = self.to_owned_command();
+        spawn_exclusive(move || exchange_with_device(&command)).await
+    }
+
+    /// Execute the command over the given transport instead of the attached HID device, e.g. over a
+    /// [TransportRelay](crate::transport::TransportRelay)
+    pub fn execute_with_transport<T: LedgerTransport>(
+        &self,
+        transport: &T,
+    ) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError> {
+        transport.exchange(&self.inner)
+    }
+
+    fn to_owned_command(&self) -> APDUCommand<Vec<u8>> {
+        APDUCommand {
+            cla: self.inner.cla,
+            ins: self.inner.ins,
+            p1: self.inner.p1,
+            p2: self.inner.p2,
+            data: self.inner.data.to_vec(),
+        }
     }
 
     pub fn build_command(account: u64, instruction: Instruction, data: Vec<u8>) -> Command<Vec<u8>> {
@@ -85,6 +260,26 @@ impl<D: Deref<Target = [u8]>> Command<D> {
         })
     }
 
+    /// Builds a command whose payload starts with a format version byte, which the device reads before the account
+    pub fn build_versioned_command(
+        format_version: u8,
+        account: u64,
+        instruction: Instruction,
+        data: Vec<u8>,
+    ) -> Command<Vec<u8>> {
+        let mut base_data = vec![format_version];
+        base_data.extend_from_slice(&account.to_le_bytes());
+        base_data.extend_from_slice(&data);
+
+        Command::new(APDUCommand {
+            cla: WALLET_CLA,
+            ins: instruction.as_byte(),
+            p1: 0x00,
+            p2: 0x00,
+            data: base_data,
+        })
+    }
+
     pub fn chunk_command(account: u64, instruction: Instruction, data: Vec<Vec<u8>>) -> Vec<Command<Vec<u8>>> {
         let num_chunks = data.len();
         let mut more;
@@ -115,4 +310,90 @@ impl<D: Deref<Target = [u8]>> Command<D> {
 
         commands
     }
+
+    /// Discards any chunked command the device was part way through, then sends `data` as the chunks of `instruction`
+    /// and checks that the answer to the last chunk is in the expected response version and holds at least `min_len`
+    /// bytes. A transfer that failed part way can be retried by calling this again, because the device never mixes
+    /// the chunks of two attempts.
+    pub fn reset_and_send_chunked(
+        account: u64,
+        instruction: Instruction,
+        data: Vec<Vec<u8>>,
+        min_len: usize,
+    ) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError> {
+        Command::<Vec<u8>>::build_command(account, Instruction::ResetReassembly, vec![])
+            .execute_checking_version(EXPECTED_RESPONSE_VERSION, 1)?;
+
+        let mut commands = Command::<Vec<u8>>::chunk_command(account, instruction, data);
+        let last_command = commands
+            .pop()
+            .ok_or_else(|| LedgerDeviceError::Processing(format!("{:?}: no data to send", instruction)))?;
+        for command in commands {
+            command.execute_expecting(0)?;
+        }
+        last_command.execute_checking_version(EXPECTED_RESPONSE_VERSION, min_len)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            Arc,
+        },
+        thread,
+        time::Duration,
+    };
+
+    use tokio::time::timeout;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn it_completes_exchanges_whose_future_was_dropped() {
+        let completed = Arc::new(AtomicBool::new(false));
+
+        let in_flight = {
+            let completed = completed.clone();
+            spawn_exclusive(move || {
+                thread::sleep(Duration::from_millis(200));
+                completed.store(true, Ordering::SeqCst);
+                Ok(())
+            })
+        };
+        // Drop the future while the exchange is still running
+        assert!(timeout(Duration::from_millis(20), in_flight).await.is_err());
+
+        // The next exchange only starts once the abandoned one has finished
+        let next = spawn_exclusive(move || Ok(completed.load(Ordering::SeqCst)))
+            .await
+            .unwrap();
+        assert!(next);
+    }
+
+    #[test]
+    fn it_times_out_exchanges_the_device_does_not_answer() {
+        let result = exclusive_with_timeout(Duration::from_millis(20), || {
+            thread::sleep(Duration::from_millis(200));
+            Ok(())
+        });
+        assert_eq!(result, Err(LedgerDeviceError::Timeout(Duration::from_millis(20))));
+    }
+
+    #[test]
+    fn it_checks_the_response_version() {
+        assert!(check_response_version(&[EXPECTED_RESPONSE_VERSION, 0xaa], EXPECTED_RESPONSE_VERSION).is_ok());
+        assert_eq!(
+            check_response_version(&[2, 0xaa], EXPECTED_RESPONSE_VERSION),
+            Err(LedgerDeviceError::ResponseVersionMismatch {
+                expected: EXPECTED_RESPONSE_VERSION,
+                found: 2
+            })
+        );
+        assert_eq!(
+            check_response_version(&[], EXPECTED_RESPONSE_VERSION),
+            Err(LedgerDeviceError::ShortResponse { expected: 1, actual: 0 })
+        );
+    }
 }
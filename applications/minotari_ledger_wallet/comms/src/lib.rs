@@ -23,3 +23,4 @@
 pub mod accessor_methods;
 pub mod error;
 pub mod ledger_wallet;
+pub mod transport;
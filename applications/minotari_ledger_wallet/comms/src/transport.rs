@@ -0,0 +1,320 @@
+// Copyright 2024 The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    io::{self, Read, Write},
+    net::TcpStream,
+    ops::Deref,
+    sync::{Mutex, PoisonError},
+    time::Duration,
+};
+
+use ledger_transport::{APDUAnswer, APDUCommand};
+use ledger_transport_hid::TransportNativeHID;
+
+use crate::error::LedgerDeviceError;
+
+/// The largest frame either end of a relay accepts. A short APDU is at most 5 header bytes and 255 data bytes, and its
+/// answer at most 256 data bytes and the 2 status bytes.
+const MAX_FRAME_LENGTH: usize = 512;
+/// The length of the header of a serialized APDU command: class, instruction, P1, P2 and the data length
+const APDU_HEADER_LENGTH: usize = 5;
+
+/// A way of exchanging APDUs with a ledger device, see
+/// [Command::execute_with_transport](crate::ledger_wallet::Command::execute_with_transport)
+pub trait LedgerTransport {
+    fn exchange<D: Deref<Target = [u8]>>(
+        &self,
+        command: &APDUCommand<D>,
+    ) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError>;
+}
+
+impl LedgerTransport for TransportNativeHID {
+    fn exchange<D: Deref<Target = [u8]>>(
+        &self,
+        command: &APDUCommand<D>,
+    ) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError> {
+        TransportNativeHID::exchange(self, command).map_err(|e| LedgerDeviceError::NativeTransport(e.to_string()))
+    }
+}
+
+/// A stream a [TransportRelay] can forward APDUs over
+pub trait RelayStream: Read + Write {
+    /// Limits how long a single read or write may block. Streams that cannot time out may ignore it.
+    fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()>;
+}
+
+impl RelayStream for TcpStream {
+    fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.set_read_timeout(timeout)?;
+        self.set_write_timeout(timeout)
+    }
+}
+
+#[cfg(unix)]
+impl RelayStream for std::os::unix::net::UnixStream {
+    fn set_timeout(&self, timeout: Option<Duration>) -> io::Result<()> {
+        self.set_read_timeout(timeout)?;
+        self.set_write_timeout(timeout)
+    }
+}
+
+/// Forwards APDUs to a ledger device attached to another process, e.g. a remote signing daemon that runs
+/// [serve_relay] against its local device.
+///
+/// Each command is sent as a frame holding the serialized APDU, and the relay answers with a frame holding the answer
+/// data followed by the status word. A frame is its length as a big endian `u32` followed by that many bytes.
+///
+/// The stream is opened with `connect` on the first exchange. If an exchange fails, the stream is dropped and the next
+/// exchange opens a new one. A failed command is not sent again, since the device may already have acted on it.
+pub struct TransportRelay<S, F> {
+    connect: F,
+    timeout: Option<Duration>,
+    stream: Mutex<Option<S>>,
+}
+
+impl<S, F> TransportRelay<S, F>
+where
+    S: RelayStream,
+    F: Fn() -> io::Result<S>,
+{
+    pub fn new(connect: F) -> Self {
+        Self {
+            connect,
+            timeout: None,
+            stream: Mutex::new(None),
+        }
+    }
+
+    /// Fails an exchange when the relay does not accept a frame or answer within `timeout`. The user may take a while
+    /// to approve a command on the device, so the timeout must allow for that.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    fn connect(&self) -> Result<S, LedgerDeviceError> {
+        let stream = (self.connect)().map_err(|e| relay_error("connecting", &e))?;
+        stream
+            .set_timeout(self.timeout)
+            .map_err(|e| relay_error("setting the timeout", &e))?;
+        Ok(stream)
+    }
+
+    fn exchange_frame(&self, frame: &[u8]) -> Result<Vec<u8>, LedgerDeviceError> {
+        let mut stream = self.stream.lock().unwrap_or_else(PoisonError::into_inner);
+        let mut connected = match stream.take() {
+            Some(connected) => connected,
+            None => self.connect()?,
+        };
+        let answer = write_frame(&mut connected, frame)
+            .map_err(|e| relay_error("sending", &e))
+            .and_then(|()| read_frame(&mut connected).map_err(|e| relay_error("receiving", &e)))?;
+        // A stream that failed may be part way through a frame, so it is only kept after a complete exchange
+        *stream = Some(connected);
+        Ok(answer)
+    }
+}
+
+impl<S, F> LedgerTransport for TransportRelay<S, F>
+where
+    S: RelayStream,
+    F: Fn() -> io::Result<S>,
+{
+    fn exchange<D: Deref<Target = [u8]>>(
+        &self,
+        command: &APDUCommand<D>,
+    ) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError> {
+        let answer = self.exchange_frame(&command.serialize())?;
+        APDUAnswer::from_answer(answer).map_err(|e| LedgerDeviceError::Relay(format!("invalid answer: {:?}", e)))
+    }
+}
+
+/// Answers the commands received on `stream` by exchanging them with `transport`, until the other end closes the
+/// stream. This is the device side of a [TransportRelay].
+pub fn serve_relay<S: Read + Write, T: LedgerTransport>(mut stream: S, transport: &T) -> Result<(), LedgerDeviceError> {
+    loop {
+        let frame = match read_frame(&mut stream) {
+            Ok(frame) => frame,
+            Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(()),
+            Err(e) => return Err(relay_error("receiving", &e)),
+        };
+        let command = parse_command(&frame)?;
+        let answer = transport.exchange(&command)?;
+        let mut reply = answer.data().to_vec();
+        reply.extend_from_slice(&answer.retcode().to_be_bytes());
+        write_frame(&mut stream, &reply).map_err(|e| relay_error("sending", &e))?;
+    }
+}
+
+fn parse_command(frame: &[u8]) -> Result<APDUCommand<Vec<u8>>, LedgerDeviceError> {
+    match frame {
+        [cla, ins, p1, p2, length, data @ ..] if usize::from(*length) == data.len() => Ok(APDUCommand {
+            cla: *cla,
+            ins: *ins,
+            p1: *p1,
+            p2: *p2,
+            data: data.to_vec(),
+        }),
+        _ => Err(LedgerDeviceError::Relay(format!(
+            "invalid command: expected a {} byte header followed by the data, got {} bytes",
+            APDU_HEADER_LENGTH,
+            frame.len()
+        ))),
+    }
+}
+
+fn write_frame<W: Write>(writer: &mut W, frame: &[u8]) -> io::Result<()> {
+    if frame.len() > MAX_FRAME_LENGTH {
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, "frame too long"));
+    }
+    writer.write_all(&(frame.len() as u32).to_be_bytes())?;
+    writer.write_all(frame)?;
+    writer.flush()
+}
+
+fn read_frame<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let mut length = [0u8; 4];
+    reader.read_exact(&mut length)?;
+    let length = usize::try_from(u32::from_be_bytes(length)).unwrap_or(usize::MAX);
+    if length > MAX_FRAME_LENGTH {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "frame too long"));
+    }
+    let mut frame = vec![0u8; length];
+    reader.read_exact(&mut frame)?;
+    Ok(frame)
+}
+
+fn relay_error(action: &str, e: &io::Error) -> LedgerDeviceError {
+    match e.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => {
+            LedgerDeviceError::Relay(format!("timed out {}", action))
+        },
+        _ => LedgerDeviceError::Relay(format!("{} failed: {}", action, e)),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::{
+        net::TcpListener,
+        sync::atomic::{AtomicUsize, Ordering},
+        thread,
+    };
+
+    use super::*;
+
+    /// Echoes the command data back with a success status word
+    struct EchoTransport;
+
+    impl LedgerTransport for EchoTransport {
+        fn exchange<D: Deref<Target = [u8]>>(
+            &self,
+            command: &APDUCommand<D>,
+        ) -> Result<APDUAnswer<Vec<u8>>, LedgerDeviceError> {
+            let mut answer = vec![command.ins];
+            answer.extend_from_slice(&command.data);
+            answer.extend_from_slice(&[0x90, 0x00]);
+            Ok(APDUAnswer::from_answer(answer).unwrap())
+        }
+    }
+
+    fn command(data: Vec<u8>) -> APDUCommand<Vec<u8>> {
+        APDUCommand {
+            cla: 0x80,
+            ins: 0x01,
+            p1: 0,
+            p2: 0,
+            data,
+        }
+    }
+
+    #[test]
+    fn it_forwards_commands_over_the_relay() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            serve_relay(stream, &EchoTransport)
+        });
+
+        let relay = TransportRelay::new(|| TcpStream::connect(address)).with_timeout(Duration::from_secs(5));
+        for data in [vec![], vec![1, 2, 3]] {
+            let answer = relay.exchange(&command(data.clone())).unwrap();
+            assert_eq!(answer.retcode(), 0x9000);
+            assert_eq!(answer.data()[0], 0x01);
+            assert_eq!(&answer.data()[1..], data.as_slice());
+        }
+
+        drop(relay);
+        assert!(server.join().unwrap().is_ok());
+    }
+
+    #[test]
+    fn it_reconnects_after_a_failed_exchange() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let server = thread::spawn(move || {
+            // Drop the first connection without answering, then serve the second
+            let (stream, _) = listener.accept().unwrap();
+            drop(stream);
+            let (stream, _) = listener.accept().unwrap();
+            serve_relay(stream, &EchoTransport)
+        });
+
+        let connections = AtomicUsize::new(0);
+        let relay = TransportRelay::new(|| {
+            connections.fetch_add(1, Ordering::SeqCst);
+            TcpStream::connect(address)
+        })
+        .with_timeout(Duration::from_secs(5));
+        assert!(matches!(
+            relay.exchange(&command(vec![1])),
+            Err(LedgerDeviceError::Relay(_))
+        ));
+        let answer = relay.exchange(&command(vec![2])).unwrap();
+        assert_eq!(&answer.data()[1..], &[2]);
+        assert_eq!(connections.load(Ordering::SeqCst), 2);
+
+        drop(relay);
+        assert!(server.join().unwrap().is_ok());
+    }
+
+    #[test]
+    fn it_times_out_when_the_relay_does_not_answer() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let relay = TransportRelay::new(|| TcpStream::connect(address)).with_timeout(Duration::from_millis(50));
+        // The listener accepts the connection into its backlog, but nothing ever answers
+        let err = relay.exchange(&command(vec![1])).unwrap_err();
+        assert_eq!(err, LedgerDeviceError::Relay("timed out receiving".to_string()));
+        drop(listener);
+    }
+
+    #[test]
+    fn it_rejects_malformed_commands() {
+        assert!(parse_command(&[0x80, 0x01, 0, 0, 2, 0xaa]).is_err());
+        assert!(parse_command(&[0x80, 0x01, 0]).is_err());
+        let command = parse_command(&[0x80, 0x01, 2, 3, 1, 0xaa]).unwrap();
+        assert_eq!((command.p1, command.p2, command.data), (2, 3, vec![0xaa]));
+    }
+}
@@ -0,0 +1,57 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use core::ops::Deref;
+
+use blake2::Blake2b;
+use digest::consts::U32;
+use ledger_device_sdk::{io::Comm, ui::gadgets::SingleMessage};
+use tari_crypto::{
+    hash_domain,
+    hashing::DomainSeparatedHasher,
+    ristretto::RistrettoPublicKey,
+    tari_utilities::ByteArray,
+};
+
+use crate::{
+    utils::{derive_from_bip32_key, get_key_from_canonical_bytes},
+    AppSW,
+    KeyType,
+    RESPONSE_VERSION,
+    STATIC_VIEW_INDEX,
+};
+
+// Must match `WalletOutputViewTagDomain`, which the sender uses to compute the view tag of an output with
+// `shared_secret_to_view_tag` in the base layer
+hash_domain!(ViewTagHashDomain, "com.tari.base_layer.wallet.output_view_tag", 1);
+
+/// Returns the one byte view tag of an output from the DH secret of the account view key and the sender public key.
+///
+/// Only the tag is returned, so the host can skip outputs whose tag does not match without learning the view key or
+/// the DH secret.
+pub fn handler_compute_view_tag(comm: &mut Comm) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 40 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let sender_public_key: RistrettoPublicKey = get_key_from_canonical_bytes(&data[8..40])?;
+
+    let view_key = derive_from_bip32_key(account, STATIC_VIEW_INDEX, KeyType::ViewKey)?;
+    let shared_secret = view_key.deref() * sender_public_key;
+    let view_tag = DomainSeparatedHasher::<Blake2b<U32>, ViewTagHashDomain>::new_with_label("view_tag")
+        .chain(shared_secret.as_bytes())
+        .finalize()
+        .as_ref()[0];
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(&[view_tag]);
+    comm.reply_ok();
+
+    Ok(())
+}
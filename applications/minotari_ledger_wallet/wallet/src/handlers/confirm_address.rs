@@ -0,0 +1,54 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{MessageScroller, SingleMessage, Validator},
+};
+use tari_crypto::{keys::PublicKey, ristretto::RistrettoPublicKey};
+
+use crate::{
+    utils::{derive_from_bip32_key, tari_address_to_base58, tari_dual_address},
+    AppSW,
+    KeyType,
+    RESPONSE_VERSION,
+    STATIC_SPEND_INDEX,
+    STATIC_VIEW_INDEX,
+};
+
+pub fn handler_confirm_address(comm: &mut Comm) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 16 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let mut network_bytes = [0u8; 8];
+    network_bytes.clone_from_slice(&data[8..16]);
+    let network = u64::from_le_bytes(network_bytes);
+
+    let public_spend_key = match derive_from_bip32_key(account, STATIC_SPEND_INDEX, KeyType::Spend) {
+        Ok(k) => RistrettoPublicKey::from_secret_key(&k),
+        Err(e) => return Err(e),
+    };
+    let public_view_key = match derive_from_bip32_key(account, STATIC_VIEW_INDEX, KeyType::ViewKey) {
+        Ok(k) => RistrettoPublicKey::from_secret_key(&k),
+        Err(e) => return Err(e),
+    };
+
+    let address = tari_dual_address(network, &public_view_key, &public_spend_key);
+    // Shown in the same base58 form as the wallet shows the address
+    MessageScroller::new(&tari_address_to_base58(&address)).event_loop();
+    if !Validator::new("Confirm address").ask() {
+        return Err(AppSW::UserCancelled);
+    }
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.reply_ok();
+
+    Ok(())
+}
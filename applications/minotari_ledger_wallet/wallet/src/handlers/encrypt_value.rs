@@ -0,0 +1,75 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use alloc::vec::Vec;
+
+use chacha20poly1305::{
+    aead::{generic_array::GenericArray, AeadInPlace, KeyInit},
+    XChaCha20Poly1305,
+    XNonce,
+};
+use ledger_device_sdk::{io::Comm, random::LedgerRng, ui::gadgets::SingleMessage};
+use rand_core::RngCore;
+use tari_crypto::ristretto::{pedersen::PedersenCommitment, RistrettoSecretKey};
+use zeroize::Zeroizing;
+
+use crate::{
+    handlers::recover_value::value_encryption_key,
+    utils::{derive_from_bip32_key, get_key_from_canonical_bytes},
+    AppSW,
+    KeyType,
+    RESPONSE_VERSION,
+    STATIC_VIEW_INDEX,
+};
+
+/// Must match `ENCRYPTED_DATA_AAD` in the base layer's `encrypted_data`
+const ENCRYPTED_DATA_AAD: &[u8] = b"TARI_AAD_VALUE_AND_MASK_EXTEND_NONCE_VARIANT";
+/// Length of the fixed part of the payload: account, commitment, value and mask
+const PAYLOAD_LENGTH: usize = 80;
+
+/// Encrypts the value, mask and payment id of the output with the given commitment to the account's view key, so that
+/// the encryption key is derived on the device. The payload is the account, commitment, value and mask, followed by
+/// the encoded payment id (empty for no payment id). Returns the encrypted data in the base layer's `EncryptedData`
+/// layout: tag, nonce, ciphertext.
+pub fn handler_encrypt_value(comm: &mut Comm) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() < PAYLOAD_LENGTH {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let commitment: PedersenCommitment = get_key_from_canonical_bytes(&data[8..40])?;
+    // Only checked to be a valid key; it is encrypted as given
+    get_key_from_canonical_bytes::<RistrettoSecretKey>(&data[48..80])?;
+
+    // value, mask and payment id, in the order `EncryptedData` expects them
+    let mut bytes = Zeroizing::new(Vec::with_capacity(data.len() - 40));
+    bytes.extend_from_slice(&data[40..]);
+
+    let view_key = derive_from_bip32_key(account, STATIC_VIEW_INDEX, KeyType::ViewKey)?;
+    let aead_key = value_encryption_key(&view_key, &commitment);
+
+    let mut nonce = [0u8; 24];
+    LedgerRng.fill_bytes(&mut nonce);
+
+    let cipher = XChaCha20Poly1305::new(GenericArray::from_slice(aead_key.as_ref()));
+    let tag = match cipher.encrypt_in_place_detached(XNonce::from_slice(&nonce), ENCRYPTED_DATA_AAD, &mut bytes) {
+        Ok(tag) => tag,
+        Err(_) => {
+            SingleMessage::new("Encryption failed").show_and_wait();
+            return Err(AppSW::EncryptionFail);
+        },
+    };
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(tag.as_slice());
+    comm.append(&nonce);
+    comm.append(&bytes);
+    comm.reply_ok();
+
+    Ok(())
+}
@@ -0,0 +1,206 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use alloc::{format, vec::Vec};
+use core::{mem, ops::Deref};
+
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{MessageScroller, SingleMessage, Validator},
+};
+use tari_crypto::{ristretto::RistrettoSecretKey, tari_utilities::ByteArray};
+use zeroize::Zeroizing;
+
+use crate::{utils::derive_from_bip32_key, AppSW, KeyType, MAX_REASSEMBLY_LEN, RESPONSE_VERSION};
+
+/// Length of one offset contribution: the offset, the sign, the key index and the key branch
+const CONTRIBUTION_LENGTH: usize = 18;
+/// The offset byte of a contribution to the kernel offset
+const KERNEL_OFFSET: u8 = 0;
+/// The offset byte of a contribution to the script offset
+const SCRIPT_OFFSET: u8 = 1;
+/// The sign byte of a contribution that is added to its offset
+const SIGN_ADD: u8 = 0;
+/// The sign byte of a contribution that is subtracted from its offset
+const SIGN_SUBTRACT: u8 = 1;
+/// The fewest keys an offset is combined from, so that the offset does not reveal a single private key
+const MIN_UNIQUE_KEYS: usize = 2;
+const PRIVATE_KEY_SIZE: usize = 32;
+/// The most keys that may be consumed by offsets while the app is open
+const MAX_CONSUMED_KEYS: usize = 256;
+
+/// The sums of the kernel and script offset contributions received so far, and the keys that returned offsets were
+/// already combined from
+pub struct FinalizeOffsetsCtx {
+    account: u64,
+    kernel_offset: Zeroizing<RistrettoSecretKey>,
+    script_offset: Zeroizing<RistrettoSecretKey>,
+    num_kernel_offset_keys: usize,
+    num_script_offset_keys: usize,
+    keys: Vec<Zeroizing<RistrettoSecretKey>>,
+    key_ids: Vec<(u64, u8, u64)>,
+    consumed_key_ids: Vec<(u64, u8, u64)>,
+    next_chunk: u8,
+}
+
+impl FinalizeOffsetsCtx {
+    pub fn new() -> Self {
+        Self {
+            account: 0,
+            kernel_offset: Zeroizing::new(RistrettoSecretKey::default()),
+            script_offset: Zeroizing::new(RistrettoSecretKey::default()),
+            num_kernel_offset_keys: 0,
+            num_script_offset_keys: 0,
+            keys: Vec::new(),
+            key_ids: Vec::new(),
+            consumed_key_ids: Vec::new(),
+            next_chunk: 0,
+        }
+    }
+
+    /// Discards the contributions received so far. The keys that returned offsets were combined from stay consumed.
+    pub fn reset(&mut self) {
+        let consumed_key_ids = mem::take(&mut self.consumed_key_ids);
+        *self = Self::new();
+        self.consumed_key_ids = consumed_key_ids;
+    }
+
+    fn add_contribution(&mut self, contribution: &[u8]) -> Result<(), AppSW> {
+        let mut index_bytes = [0u8; 8];
+        index_bytes.clone_from_slice(&contribution[2..10]);
+        let index = u64::from_le_bytes(index_bytes);
+
+        let mut branch_bytes = [0u8; 8];
+        branch_bytes.clone_from_slice(&contribution[10..18]);
+        let key_type = KeyType::from_branch_key(u64::from_le_bytes(branch_bytes))?;
+        // Offsets are only made of sender offset and random keys. Spend keys sign kernels and must never be combined.
+        if !matches!(key_type, KeyType::OneSidedSenderOffset | KeyType::Random) {
+            SingleMessage::new("Invalid offset key").show_and_wait();
+            return Err(AppSW::BadBranchKey);
+        }
+
+        // A key that is used twice, even in different offsets or in offsets returned earlier, could be solved for from
+        // the offsets
+        let key_id = (self.account, key_type.as_byte(), index);
+        if self.key_ids.contains(&key_id) || self.consumed_key_ids.contains(&key_id) {
+            SingleMessage::new("Offset key reused").show_and_wait();
+            return Err(AppSW::OffsetNotUnique);
+        }
+        let key = derive_from_bip32_key(self.account, index, key_type)?;
+        if self.keys.contains(&key) {
+            SingleMessage::new("Offset key reused").show_and_wait();
+            return Err(AppSW::OffsetNotUnique);
+        }
+        if (self.keys.len() + 1) * PRIVATE_KEY_SIZE > MAX_REASSEMBLY_LEN {
+            SingleMessage::new("Payload too large").show_and_wait();
+            return Err(AppSW::PayloadTooLarge);
+        }
+
+        let (offset, num_keys) = match contribution[0] {
+            KERNEL_OFFSET => (&mut self.kernel_offset, &mut self.num_kernel_offset_keys),
+            SCRIPT_OFFSET => (&mut self.script_offset, &mut self.num_script_offset_keys),
+            _ => return Err(AppSW::WrongApduLength),
+        };
+        *offset = match contribution[1] {
+            SIGN_ADD => Zeroizing::new(&**offset + key.deref()),
+            SIGN_SUBTRACT => Zeroizing::new(&**offset - key.deref()),
+            _ => return Err(AppSW::WrongApduLength),
+        };
+        *num_keys += 1;
+        self.keys.push(key);
+        self.key_ids.push(key_id);
+        Ok(())
+    }
+
+    /// Marks the keys of the current offsets as consumed, so that no later offsets can be combined from them
+    fn consume_keys(&mut self) -> Result<(), AppSW> {
+        if self.consumed_key_ids.len() + self.key_ids.len() > MAX_CONSUMED_KEYS {
+            SingleMessage::new("Offset key limit reached").show_and_wait();
+            return Err(AppSW::PayloadTooLarge);
+        }
+        self.consumed_key_ids.append(&mut self.key_ids);
+        Ok(())
+    }
+}
+
+/// Combines the device's contributions to the kernel offset and the script offset of a transaction it fully owns, so
+/// the host never learns the individual offset keys.
+///
+/// Chunk 0 holds the account. The following chunks hold any number of contributions each, where a contribution is the
+/// offset it goes into (0 for the kernel offset, 1 for the script offset), whether its key is added (0) or subtracted
+/// (1), and the index and branch of the key. Only sender offset and random keys may contribute. Each offset must be
+/// combined from at least two keys, and the user must approve the offsets before they are returned.
+///
+/// The keys of returned offsets are consumed: a key may only contribute to one offset while the app is open, so
+/// offsets returned across several commands cannot be combined to solve for a key. The response holds the kernel
+/// offset followed by the script offset.
+pub fn handler_finalize_offsets(
+    comm: &mut Comm,
+    chunk: u8,
+    more: bool,
+    ctx: &mut FinalizeOffsetsCtx,
+) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+
+    if chunk == 0 {
+        ctx.reset();
+        if data.len() != 8 {
+            SingleMessage::new("Invalid data length").show_and_wait();
+            return Err(AppSW::WrongApduLength);
+        }
+        let mut account_bytes = [0u8; 8];
+        account_bytes.clone_from_slice(&data[0..8]);
+        ctx.account = u64::from_le_bytes(account_bytes);
+        ctx.next_chunk = 1;
+        return Ok(());
+    }
+
+    // The contributions must arrive in order, and only after the account was given
+    if chunk != ctx.next_chunk {
+        ctx.reset();
+        return Err(AppSW::WrongP1P2);
+    }
+    if data.is_empty() || data.len() % CONTRIBUTION_LENGTH != 0 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        ctx.reset();
+        return Err(AppSW::WrongApduLength);
+    }
+    for contribution in data.chunks_exact(CONTRIBUTION_LENGTH) {
+        if let Err(e) = ctx.add_contribution(contribution) {
+            ctx.reset();
+            return Err(e);
+        }
+    }
+    ctx.next_chunk = ctx.next_chunk.saturating_add(1);
+
+    if more {
+        return Ok(());
+    }
+
+    if ctx.num_kernel_offset_keys < MIN_UNIQUE_KEYS || ctx.num_script_offset_keys < MIN_UNIQUE_KEYS {
+        ctx.reset();
+        return Err(AppSW::OffsetNotUnique);
+    }
+
+    MessageScroller::new(&format!(
+        "Combine {} kernel and {} script offset keys",
+        ctx.num_kernel_offset_keys, ctx.num_script_offset_keys
+    ))
+    .event_loop();
+    if !Validator::new("Approve offsets").ask() {
+        ctx.reset();
+        return Err(AppSW::UserCancelled);
+    }
+    if let Err(e) = ctx.consume_keys() {
+        ctx.reset();
+        return Err(e);
+    }
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(&ctx.kernel_offset.to_vec());
+    comm.append(&ctx.script_offset.to_vec());
+    ctx.reset();
+    comm.reply_ok();
+
+    Ok(())
+}
@@ -0,0 +1,54 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{MessageScroller, SingleMessage},
+};
+use tari_crypto::{keys::PublicKey, ristretto::RistrettoPublicKey};
+
+use crate::{
+    utils::{derive_from_bip32_key, tari_address_to_base58, tari_dual_address},
+    AppSW,
+    KeyType,
+    RESPONSE_VERSION,
+    STATIC_SPEND_INDEX,
+    STATIC_VIEW_INDEX,
+};
+
+/// Computes the account's receive address, including its checksum, from keys derived on the device and shows it, so
+/// the user can compare it with the address the host displays. A single checksum byte would be easy for a swapped
+/// address to match, so the whole address is shown and returned.
+///
+/// The payload holds the account and the network. The response holds the base58 string of the address, in the form of
+/// `TariAddress::to_base58`, which includes the DammSum checksum of the address.
+pub fn handler_get_address_checksum(comm: &mut Comm) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 16 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let mut network_bytes = [0u8; 8];
+    network_bytes.clone_from_slice(&data[8..16]);
+    let network = u64::from_le_bytes(network_bytes);
+
+    let public_spend_key =
+        RistrettoPublicKey::from_secret_key(&derive_from_bip32_key(account, STATIC_SPEND_INDEX, KeyType::Spend)?);
+    let public_view_key =
+        RistrettoPublicKey::from_secret_key(&derive_from_bip32_key(account, STATIC_VIEW_INDEX, KeyType::ViewKey)?);
+
+    let address = tari_dual_address(network, &public_view_key, &public_spend_key);
+    let base58 = tari_address_to_base58(&address);
+    MessageScroller::new(&base58).event_loop();
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(base58.as_bytes());
+    comm.reply_ok();
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use ledger_device_sdk::{io::Comm, ui::gadgets::SingleMessage};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    ristretto::pedersen::extended_commitment_factory::ExtendedPedersenCommitmentFactory,
+    tari_utilities::ByteArray,
+};
+
+use crate::{utils::derive_from_bip32_key, AppSW, KeyType, RESPONSE_VERSION};
+
+/// Commits to a value with the blinding factor of an output derived on the device, so the host can check the
+/// commitment it built against the device's key derivation before asking for signatures. The blinding factor does
+/// not leave the device.
+///
+/// The payload holds the account, the value, the output index and the branch of the blinding factor. The response
+/// holds the commitment.
+pub fn handler_get_commitment(comm: &mut Comm) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 32 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let mut value_bytes = [0u8; 8];
+    value_bytes.clone_from_slice(&data[8..16]);
+    let value = u64::from_le_bytes(value_bytes);
+
+    let mut index_bytes = [0u8; 8];
+    index_bytes.clone_from_slice(&data[16..24]);
+    let output_index = u64::from_le_bytes(index_bytes);
+
+    let mut branch_bytes = [0u8; 8];
+    branch_bytes.clone_from_slice(&data[24..32]);
+    let key_type = KeyType::from_branch_key(u64::from_le_bytes(branch_bytes))?;
+
+    let blinding_factor = derive_from_bip32_key(account, output_index, key_type)?;
+    let commitment = ExtendedPedersenCommitmentFactory::default().commit_value(&blinding_factor, value);
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(commitment.as_bytes());
+    comm.reply_ok();
+
+    Ok(())
+}
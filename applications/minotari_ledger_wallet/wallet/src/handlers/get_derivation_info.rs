@@ -0,0 +1,34 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use ledger_device_sdk::{io::Comm, ui::gadgets::SingleMessage};
+
+use crate::{utils::bip32_key_path, AppSW, KeyType, RESPONSE_VERSION, STATIC_SPEND_INDEX, STATIC_VIEW_INDEX};
+
+/// Returns the BIP32 paths of the account's spend and view keys, exactly as `derive_from_bip32_key` uses them, so that
+/// hosts do not need to hardcode the key schema. Each path is six little-endian `u32` components, with hardened
+/// components having the top bit set.
+pub fn handler_get_derivation_info(comm: &mut Comm) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 8 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    for (index, key_type) in [
+        (STATIC_SPEND_INDEX, KeyType::Spend),
+        (STATIC_VIEW_INDEX, KeyType::ViewKey),
+    ] {
+        for component in bip32_key_path(account, index, key_type) {
+            comm.append(&component.to_le_bytes());
+        }
+    }
+    comm.reply_ok();
+
+    Ok(())
+}
@@ -0,0 +1,36 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use ledger_device_sdk::io::Comm;
+use tari_crypto::{
+    keys::{PublicKey, SecretKey},
+    ristretto::{RistrettoPublicKey, RistrettoSecretKey},
+};
+
+use crate::{AppSW, RESPONSE_VERSION};
+
+/// Length of a compressed Ristretto point, which is also the length of a commitment
+const POINT_LENGTH: usize = RistrettoPublicKey::KEY_LEN;
+/// Length of a canonical Ristretto scalar
+const SCALAR_LENGTH: usize = RistrettoSecretKey::KEY_LEN;
+/// A Schnorr signature is the public nonce followed by the signature scalar
+const SCHNORR_SIGNATURE_LENGTH: usize = POINT_LENGTH + SCALAR_LENGTH;
+/// A commitment and public key signature is the ephemeral commitment and public key followed by three scalars
+const COM_AND_PUB_SIGNATURE_LENGTH: usize = 2 * POINT_LENGTH + 3 * SCALAR_LENGTH;
+
+/// Returns the lengths of the encodings the app uses in its responses, so that the host does not have to assume them.
+///
+/// The response holds the point length, the scalar length, the Schnorr signature length and the commitment and
+/// public key signature length, one byte each.
+pub fn handler_get_encoding_info(comm: &mut Comm) -> Result<(), AppSW> {
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(&[
+        POINT_LENGTH as u8,
+        SCALAR_LENGTH as u8,
+        SCHNORR_SIGNATURE_LENGTH as u8,
+        COM_AND_PUB_SIGNATURE_LENGTH as u8,
+    ]);
+    comm.reply_ok();
+
+    Ok(())
+}
@@ -0,0 +1,253 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use alloc::format;
+use core::ops::Deref;
+
+use blake2::Blake2b;
+use digest::consts::{U32, U64};
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{SingleMessage, Validator},
+};
+use tari_crypto::{
+    hash_domain,
+    hashing::DomainSeparatedHasher,
+    keys::PublicKey,
+    ristretto::{pedersen::PedersenCommitment, RistrettoPublicKey, RistrettoSchnorr, RistrettoSecretKey},
+    tari_utilities::ByteArray,
+};
+use tari_hashing::TransactionHashDomain;
+use zeroize::Zeroizing;
+
+use crate::{
+    alloc::string::ToString,
+    hashing::DomainSeparatedConsensusHasher,
+    utils::{derive_from_bip32_key, get_key_from_canonical_bytes, get_key_from_uniform_bytes},
+    AppSW,
+    KeyType,
+    RESPONSE_VERSION,
+};
+
+/// Length of the kernel fields of a payload, without a burn commitment
+pub const KERNEL_DATA_LENGTH: usize = 121;
+/// Length of the kernel fields of a payload when a burn commitment is appended
+pub const KERNEL_DATA_LENGTH_WITH_BURN_COMMITMENT: usize = KERNEL_DATA_LENGTH + 32;
+/// Domain separation label of the kernel signature message
+pub const KERNEL_MESSAGE_LABEL: &str = "kernel_message";
+/// Domain separation label of the kernel signature challenge
+pub const KERNEL_SIGNATURE_LABEL: &str = "kernel_signature";
+/// Domain separation label of the device's kernel signature nonce
+const KERNEL_NONCE_LABEL: &str = "kernel_nonce";
+/// The only kernel version the signature challenge can be built for
+const KERNEL_VERSION_V0: u64 = 0;
+
+hash_domain!(KernelNonceHashDomain, "com.tari.ledger.kernel_nonce", 1);
+
+/// A kernel signature share the host asked for, with the nonce and the challenge built on the device from the kernel
+/// fields
+pub struct KernelSigningRequest {
+    private_key: Zeroizing<RistrettoSecretKey>,
+    private_nonce: Zeroizing<RistrettoSecretKey>,
+    fee: u64,
+    lock_height: u64,
+    challenge: [u8; 64],
+}
+
+impl KernelSigningRequest {
+    /// Reads the kernel fields of a payload, derives the device's nonce from them and builds the kernel signature
+    /// challenge.
+    ///
+    /// The payload holds the sum of the public nonces of the other signers rather than the total nonce. The device's
+    /// nonce is derived from its nonce key, its signing key and every input of the challenge, and the total nonce is
+    /// completed with it. The same nonce can therefore only ever sign the same challenge, however the host picks the
+    /// nonce key.
+    pub fn read(account: u64, network: u64, data: &[u8]) -> Result<Self, AppSW> {
+        if data.len() != KERNEL_DATA_LENGTH && data.len() != KERNEL_DATA_LENGTH_WITH_BURN_COMMITMENT {
+            SingleMessage::new("Invalid data length").show_and_wait();
+            return Err(AppSW::WrongApduLength);
+        }
+
+        let mut kernel_version_bytes = [0u8; 8];
+        kernel_version_bytes.clone_from_slice(&data[0..8]);
+        let kernel_version = u64::from_le_bytes(kernel_version_bytes);
+        if kernel_version != KERNEL_VERSION_V0 {
+            SingleMessage::new("Unsupported kernel version").show_and_wait();
+            return Err(AppSW::VersionMismatch);
+        }
+
+        let mut private_key_index_bytes = [0u8; 8];
+        private_key_index_bytes.clone_from_slice(&data[8..16]);
+        let private_key_index = u64::from_le_bytes(private_key_index_bytes);
+
+        let mut private_key_type_bytes = [0u8; 8];
+        private_key_type_bytes.clone_from_slice(&data[16..24]);
+        let private_key_type = KeyType::from_branch_key(u64::from_le_bytes(private_key_type_bytes))?;
+
+        let mut private_nonce_index_bytes = [0u8; 8];
+        private_nonce_index_bytes.clone_from_slice(&data[24..32]);
+        let private_nonce_index = u64::from_le_bytes(private_nonce_index_bytes);
+
+        let mut nonce_key_type_bytes = [0u8; 8];
+        nonce_key_type_bytes.clone_from_slice(&data[32..40]);
+        let nonce_key_type = KeyType::from_branch_key(u64::from_le_bytes(nonce_key_type_bytes))?;
+
+        let other_public_nonces = get_key_from_canonical_bytes::<RistrettoPublicKey>(&data[40..72])?;
+        let total_excess = get_key_from_canonical_bytes::<RistrettoPublicKey>(&data[72..104])?;
+
+        let mut fee_bytes = [0u8; 8];
+        fee_bytes.clone_from_slice(&data[104..112]);
+        let fee = u64::from_le_bytes(fee_bytes);
+
+        let mut lock_height_bytes = [0u8; 8];
+        lock_height_bytes.clone_from_slice(&data[112..120]);
+        let lock_height = u64::from_le_bytes(lock_height_bytes);
+
+        let features = data[120];
+
+        let burn_commitment = if data.len() == KERNEL_DATA_LENGTH_WITH_BURN_COMMITMENT {
+            Some(get_key_from_canonical_bytes::<PedersenCommitment>(&data[121..153])?)
+        } else {
+            None
+        };
+
+        let message = build_kernel_signature_message(
+            kernel_version as u8,
+            network,
+            fee,
+            lock_height,
+            features,
+            &burn_commitment,
+        );
+
+        let private_key = derive_from_bip32_key(account, private_key_index, private_key_type)?;
+        let nonce_key = derive_from_bip32_key(account, private_nonce_index, nonce_key_type)?;
+        let nonce_hash =
+            DomainSeparatedHasher::<Blake2b<U64>, KernelNonceHashDomain>::new_with_label(KERNEL_NONCE_LABEL)
+                .chain(nonce_key.as_bytes())
+                .chain(private_key.as_bytes())
+                .chain(network.to_le_bytes())
+                .chain(message)
+                .chain(other_public_nonces.as_bytes())
+                .chain(total_excess.as_bytes())
+                .finalize();
+        let private_nonce = get_key_from_uniform_bytes(nonce_hash.as_ref())?;
+
+        let total_nonce = &other_public_nonces + &RistrettoPublicKey::from_secret_key(&private_nonce);
+        let challenge = finalize_kernel_signature_challenge(network, &total_nonce, &total_excess, &message);
+
+        Ok(Self {
+            private_key,
+            private_nonce,
+            fee,
+            lock_height,
+            challenge,
+        })
+    }
+
+    /// The fee bound into the challenge
+    pub fn fee(&self) -> u64 {
+        self.fee
+    }
+
+    /// The lock height bound into the challenge
+    pub fn lock_height(&self) -> u64 {
+        self.lock_height
+    }
+
+    /// Signs the challenge with the requested key and the derived nonce
+    pub fn sign(&self) -> Result<RistrettoSchnorr, AppSW> {
+        match RistrettoSchnorr::sign_raw_uniform(&self.private_key, self.private_nonce.deref().clone(), &self.challenge)
+        {
+            Ok(sig) => Ok(sig),
+            Err(e) => {
+                SingleMessage::new(&format!("Signing error: {:?}", e.to_string())).show_and_wait();
+                Err(AppSW::SchnorrSignatureFail)
+            },
+        }
+    }
+}
+
+/// Signs this device's share of a kernel excess signature once the user has approved the fee, and the lock height if
+/// the kernel is time-locked.
+///
+/// The host does not send a challenge. The device builds the kernel message from the fee, lock height, features and
+/// burn commitment in the payload, so the fee and lock height shown to the user are the ones bound into the signature
+/// and a compromised host cannot get a higher fee or a time lock signed. The response holds the device's public nonce
+/// and its signature share.
+pub fn handler_get_kernel_signature(comm: &mut Comm) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() < 16 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let mut network_bytes = [0u8; 8];
+    network_bytes.clone_from_slice(&data[8..16]);
+    let network = u64::from_le_bytes(network_bytes);
+
+    let request = KernelSigningRequest::read(account, network, &data[16..])?;
+
+    SingleMessage::new(&format!("Fee: {} uT", request.fee())).show_and_wait();
+    confirm_lock_height(request.lock_height())?;
+    if !Validator::new("Sign kernel").ask() {
+        return Err(AppSW::UserCancelled);
+    }
+
+    let signature = request.sign()?;
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(&signature.get_public_nonce().to_vec());
+    comm.append(&signature.get_signature().to_vec());
+    comm.reply_ok();
+
+    Ok(())
+}
+
+/// Asks the user to confirm a time-locked spend. A lock height of zero is not time-locked and needs no confirmation.
+pub fn confirm_lock_height(lock_height: u64) -> Result<(), AppSW> {
+    if lock_height == 0 {
+        return Ok(());
+    }
+    SingleMessage::new(&format!("Lock height: {}", lock_height)).show_and_wait();
+    if !Validator::new("Time-locked spend").ask() {
+        return Err(AppSW::UserCancelled);
+    }
+    Ok(())
+}
+
+fn build_kernel_signature_message(
+    version: u8,
+    network: u64,
+    fee: u64,
+    lock_height: u64,
+    features: u8,
+    burn_commitment: &Option<PedersenCommitment>,
+) -> [u8; 32] {
+    DomainSeparatedConsensusHasher::<TransactionHashDomain, Blake2b<U32>>::new(KERNEL_MESSAGE_LABEL, network)
+        .chain(&version)
+        .chain(&fee)
+        .chain(&lock_height)
+        .chain(&features)
+        .chain(burn_commitment)
+        .finalize()
+        .into()
+}
+
+fn finalize_kernel_signature_challenge(
+    network: u64,
+    total_nonce: &RistrettoPublicKey,
+    total_excess: &RistrettoPublicKey,
+    message: &[u8; 32],
+) -> [u8; 64] {
+    DomainSeparatedConsensusHasher::<TransactionHashDomain, Blake2b<U64>>::new(KERNEL_SIGNATURE_LABEL, network)
+        .chain(total_nonce)
+        .chain(total_excess)
+        .chain(message)
+        .finalize()
+        .into()
+}
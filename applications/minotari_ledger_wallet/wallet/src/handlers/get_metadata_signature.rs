@@ -0,0 +1,266 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use alloc::{format, vec::Vec};
+
+use blake2::Blake2b;
+use digest::consts::{U32, U64};
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{MessageScroller, SingleMessage, Validator},
+};
+use tari_crypto::{
+    keys::PublicKey,
+    ristretto::{
+        pedersen::{extended_commitment_factory::ExtendedPedersenCommitmentFactory, PedersenCommitment},
+        RistrettoComAndPubSig,
+        RistrettoPublicKey,
+        RistrettoSecretKey,
+    },
+    tari_utilities::ByteArray,
+};
+use tari_hashing::TransactionHashDomain;
+
+use crate::{
+    alloc::string::ToString,
+    hashing::DomainSeparatedConsensusHasher,
+    utils::{bytes_to_hex_string, derive_from_bip32_key, get_key_from_canonical_bytes},
+    AppSW,
+    KeyType,
+    MAX_REASSEMBLY_LEN,
+    RESPONSE_VERSION,
+};
+
+/// Domain separation label of the metadata signature message
+pub const METADATA_MESSAGE_LABEL: &str = "metadata_message";
+/// Domain separation label of the metadata signature challenge
+pub const METADATA_SIGNATURE_LABEL: &str = "metadata_signature";
+/// Length of the chunk 0 payload
+const HEADER_LENGTH: usize = 136;
+/// The longest consensus encoded covenant the app signs for, including its length prefix
+const MAX_COVENANT_LENGTH: usize = 1024;
+/// The encoded minimum value promise that ends the message fields
+const MINIMUM_VALUE_PROMISE_LENGTH: usize = 8;
+/// The consensus encoding of an empty covenant
+const EMPTY_COVENANT: [u8; 1] = [0];
+/// The output versions the challenge can be built for
+const TXO_VERSIONS: [u64; 2] = [0, 1];
+
+struct MetadataSigningRequest {
+    account: u64,
+    network: u64,
+    version: u8,
+    sender_offset_index: u64,
+    nonce_index: u64,
+    nonce_key_type: KeyType,
+    commitment: PedersenCommitment,
+    ephemeral_commitment: PedersenCommitment,
+    script_length: usize,
+    features_length: usize,
+    covenant_length: usize,
+}
+
+/// Collects the consensus encoded fields of an output across the chunks of a sender metadata signature request
+pub struct MetadataSignatureCtx {
+    request: Option<MetadataSigningRequest>,
+    message_fields: Vec<u8>,
+    next_chunk: u8,
+}
+
+impl MetadataSignatureCtx {
+    pub fn new() -> Self {
+        Self {
+            request: None,
+            message_fields: Vec::new(),
+            next_chunk: 0,
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Signs the sender's part of an output's metadata signature with a sender offset key once the user has approved the
+/// output's covenant.
+///
+/// Chunk 0 holds the account, the network, the output version, the sender offset key index, the nonce index and
+/// branch, the commitment, the ephemeral commitment and the lengths of the encoded script, features and covenant.
+/// The following chunks hold the consensus encoded script, features, covenant, encrypted data and minimum value
+/// promise, in that order, split wherever the host likes. The device builds the metadata message and challenge from
+/// them, so the signature binds the covenant that was shown to the user.
+pub fn handler_get_metadata_signature(
+    comm: &mut Comm,
+    chunk: u8,
+    more: bool,
+    ctx: &mut MetadataSignatureCtx,
+) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+
+    if chunk == 0 {
+        ctx.reset();
+        ctx.request = Some(read_request(data)?);
+        ctx.next_chunk = 1;
+        return Ok(());
+    }
+
+    // The fields must arrive in order, and only after the request was started
+    if ctx.request.is_none() || chunk != ctx.next_chunk {
+        ctx.reset();
+        return Err(AppSW::WrongP1P2);
+    }
+    if ctx.message_fields.len() + data.len() > MAX_REASSEMBLY_LEN {
+        SingleMessage::new("Payload too large").show_and_wait();
+        ctx.reset();
+        return Err(AppSW::PayloadTooLarge);
+    }
+    ctx.message_fields.extend_from_slice(data);
+    ctx.next_chunk = ctx.next_chunk.saturating_add(1);
+
+    if more {
+        return Ok(());
+    }
+
+    let request = ctx.request.take().ok_or(AppSW::WrongP1P2)?;
+    let message_fields = core::mem::take(&mut ctx.message_fields);
+    ctx.reset();
+
+    let covenant_offset = request.script_length + request.features_length;
+    let covenant_end = covenant_offset + request.covenant_length;
+    if message_fields.len() < covenant_end + MINIMUM_VALUE_PROMISE_LENGTH {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let covenant = &message_fields[covenant_offset..covenant_end];
+    if covenant == EMPTY_COVENANT {
+        SingleMessage::new("No covenant").show_and_wait();
+    } else {
+        MessageScroller::new(&format!("Covenant: {}", bytes_to_hex_string(covenant))).event_loop();
+    }
+    if !Validator::new("Sign output metadata").ask() {
+        return Err(AppSW::UserCancelled);
+    }
+
+    let sender_offset_key = derive_from_bip32_key(
+        request.account,
+        request.sender_offset_index,
+        KeyType::OneSidedSenderOffset,
+    )?;
+    let ephemeral_nonce = derive_from_bip32_key(request.account, request.nonce_index, request.nonce_key_type)?;
+    let sender_offset_public_key = RistrettoPublicKey::from_secret_key(&sender_offset_key);
+    let ephemeral_pubkey = RistrettoPublicKey::from_secret_key(&ephemeral_nonce);
+
+    let message = build_metadata_signature_message(request.network, request.version, &message_fields);
+    let challenge = finalize_metadata_signature_challenge(
+        request.network,
+        &sender_offset_public_key,
+        &request.ephemeral_commitment,
+        &ephemeral_pubkey,
+        &request.commitment,
+        &message,
+    );
+
+    // The sender only knows the sender offset key, so the commitment part of the signature is left to the receiver
+    let zero = RistrettoSecretKey::default();
+    let factory = ExtendedPedersenCommitmentFactory::default();
+    let signature = match RistrettoComAndPubSig::sign(
+        &zero,
+        &zero,
+        &sender_offset_key,
+        &zero,
+        &zero,
+        &ephemeral_nonce,
+        &challenge,
+        &factory,
+    ) {
+        Ok(sig) => sig,
+        Err(e) => {
+            SingleMessage::new(&format!("Signing error: {:?}", e.to_string())).show_and_wait();
+            return Err(AppSW::ScriptSignatureFail);
+        },
+    };
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(&signature.to_vec());
+    comm.reply_ok();
+
+    Ok(())
+}
+
+fn read_request(data: &[u8]) -> Result<MetadataSigningRequest, AppSW> {
+    if data.len() != HEADER_LENGTH {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+    let read_u64 = |offset: usize| {
+        let mut bytes = [0u8; 8];
+        bytes.clone_from_slice(&data[offset..offset + 8]);
+        u64::from_le_bytes(bytes)
+    };
+
+    let version = read_u64(16);
+    if !TXO_VERSIONS.contains(&version) {
+        SingleMessage::new("Unsupported output version").show_and_wait();
+        return Err(AppSW::VersionMismatch);
+    }
+
+    let lengths = [read_u64(112), read_u64(120), read_u64(128)];
+    let [script_length, features_length, covenant_length] =
+        lengths.map(|length| usize::try_from(length).unwrap_or(usize::MAX));
+    if covenant_length > MAX_COVENANT_LENGTH {
+        SingleMessage::new("Covenant too long").show_and_wait();
+        return Err(AppSW::PayloadTooLarge);
+    }
+    if covenant_length == 0 ||
+        script_length
+            .saturating_add(features_length)
+            .saturating_add(covenant_length) >
+            MAX_REASSEMBLY_LEN - MINIMUM_VALUE_PROMISE_LENGTH
+    {
+        SingleMessage::new("Invalid field lengths").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    Ok(MetadataSigningRequest {
+        account: read_u64(0),
+        network: read_u64(8),
+        version: version as u8,
+        sender_offset_index: read_u64(24),
+        nonce_index: read_u64(32),
+        nonce_key_type: KeyType::from_branch_key(read_u64(40))?,
+        commitment: get_key_from_canonical_bytes::<PedersenCommitment>(&data[48..80])?,
+        ephemeral_commitment: get_key_from_canonical_bytes::<PedersenCommitment>(&data[80..112])?,
+        script_length,
+        features_length,
+        covenant_length,
+    })
+}
+
+/// The message hashes the output version followed by the fields as the host encoded them, which is the consensus
+/// encoding of the script, features, covenant, encrypted data and minimum value promise
+fn build_metadata_signature_message(network: u64, version: u8, message_fields: &[u8]) -> [u8; 32] {
+    DomainSeparatedConsensusHasher::<TransactionHashDomain, Blake2b<U32>>::new(METADATA_MESSAGE_LABEL, network)
+        .chain(&version)
+        .chain_encoded(message_fields)
+        .finalize()
+        .into()
+}
+
+fn finalize_metadata_signature_challenge(
+    network: u64,
+    sender_offset_public_key: &RistrettoPublicKey,
+    ephemeral_commitment: &PedersenCommitment,
+    ephemeral_pubkey: &RistrettoPublicKey,
+    commitment: &PedersenCommitment,
+    message: &[u8; 32],
+) -> [u8; 64] {
+    DomainSeparatedConsensusHasher::<TransactionHashDomain, Blake2b<U64>>::new(METADATA_SIGNATURE_LABEL, network)
+        .chain(ephemeral_pubkey)
+        .chain(ephemeral_commitment)
+        .chain(sender_offset_public_key)
+        .chain(commitment)
+        .chain(message)
+        .finalize()
+        .into()
+}
@@ -0,0 +1,21 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use ledger_device_sdk::io::Comm;
+
+use crate::{AppSW, RESPONSE_VERSION};
+
+/// The network this app is pinned to, if any. Key derivation does not depend on the network, so the same keys are
+/// used on every network and the app is not pinned.
+const PINNED_NETWORK: Option<u8> = None;
+
+pub fn handler_get_network_info(comm: &mut Comm) -> Result<(), AppSW> {
+    comm.append(&[RESPONSE_VERSION]); // version
+    match PINNED_NETWORK {
+        Some(network) => comm.append(&[1, network]),
+        None => comm.append(&[0, 0]),
+    }
+    comm.reply_ok();
+
+    Ok(())
+}
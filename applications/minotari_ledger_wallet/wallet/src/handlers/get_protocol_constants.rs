@@ -0,0 +1,44 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use ledger_device_sdk::io::Comm;
+
+use crate::{
+    handlers::{
+        get_kernel_signature::{KERNEL_MESSAGE_LABEL, KERNEL_SIGNATURE_LABEL},
+        get_metadata_signature::{METADATA_MESSAGE_LABEL, METADATA_SIGNATURE_LABEL},
+        get_script_signature::SCRIPT_CHALLENGE_LABEL,
+    },
+    AppSW,
+    RESPONSE_VERSION,
+    STATIC_SPEND_INDEX,
+    STATIC_VIEW_INDEX,
+};
+
+/// The domain separation labels of the challenges the app builds, in the order they are returned
+const CHALLENGE_LABELS: [&str; 5] = [
+    SCRIPT_CHALLENGE_LABEL,
+    KERNEL_MESSAGE_LABEL,
+    KERNEL_SIGNATURE_LABEL,
+    METADATA_MESSAGE_LABEL,
+    METADATA_SIGNATURE_LABEL,
+];
+
+/// Returns the constants the host must agree on with the app, so that protocol drift is caught before anything is
+/// signed.
+///
+/// The response holds the static spend and view key indexes, followed by the number of challenge labels and each
+/// label prefixed with its length. The response version that starts every response is one of the constants.
+pub fn handler_get_protocol_constants(comm: &mut Comm) -> Result<(), AppSW> {
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(&STATIC_SPEND_INDEX.to_le_bytes());
+    comm.append(&STATIC_VIEW_INDEX.to_le_bytes());
+    comm.append(&[CHALLENGE_LABELS.len() as u8]);
+    for label in CHALLENGE_LABELS {
+        comm.append(&[label.len() as u8]);
+        comm.append(label.as_bytes());
+    }
+    comm.reply_ok();
+
+    Ok(())
+}
@@ -25,6 +25,9 @@ hash_domain!(CheckSigHashDomain, "com.tari.script.check_sig", 1);
 /// The type used for `CheckSig`, `CheckMultiSig`, and related opcodes' signatures
 pub type CheckSigSchnorrSignature = SchnorrSignature<RistrettoPublicKey, RistrettoSecretKey, CheckSigHashDomain>;
 
+/// Signs a challenge built by the host. Spend branch keys sign kernels, so they are refused here: their challenges
+/// must be built on the device, by the kernel signature handler, which confirms the fee with the user, or by the script
+/// signature share handler.
 pub fn handler_get_raw_schnorr_signature(comm: &mut Comm) -> Result<(), AppSW> {
     let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
     if data.len() != 104 {
@@ -43,6 +46,10 @@ pub fn handler_get_raw_schnorr_signature(comm: &mut Comm) -> Result<(), AppSW> {
     let mut private_key_type_bytes = [0u8; 8];
     private_key_type_bytes.clone_from_slice(&data[16..24]);
     let private_key_type = KeyType::from_branch_key(u64::from_le_bytes(private_key_type_bytes))?;
+    if matches!(private_key_type, KeyType::Spend) {
+        SingleMessage::new("Use kernel signing").show_and_wait();
+        return Err(AppSW::Deny);
+    }
 
     let private_key = derive_from_bip32_key(account, private_key_index, private_key_type)?;
 
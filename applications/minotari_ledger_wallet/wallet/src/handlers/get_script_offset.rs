@@ -4,7 +4,7 @@
 use alloc::vec::Vec;
 use core::ops::Deref;
 
-use ledger_device_sdk::io::Comm;
+use ledger_device_sdk::{io::Comm, ui::gadgets::SingleMessage};
 use tari_crypto::{ristretto::RistrettoSecretKey, tari_utilities::ByteArray};
 use zeroize::Zeroizing;
 
@@ -12,11 +12,13 @@ use crate::{
     utils::{alpha_hasher, derive_from_bip32_key, get_key_from_canonical_bytes},
     AppSW,
     KeyType,
+    MAX_REASSEMBLY_LEN,
     RESPONSE_VERSION,
     STATIC_SPEND_INDEX,
 };
 
 const MIN_UNIQUE_KEYS: usize = 2;
+const PRIVATE_KEY_SIZE: usize = 32;
 
 pub struct ScriptOffsetCtx {
     total_sender_offset_private_key: Zeroizing<RistrettoSecretKey>,
@@ -25,6 +27,7 @@ pub struct ScriptOffsetCtx {
     total_offset_indexes: u64,
     total_commitment_keys: u64,
     unique_keys: Vec<Zeroizing<RistrettoSecretKey>>,
+    next_chunk: u8,
 }
 
 // Implement constructor for TxInfo with default values
@@ -37,23 +40,32 @@ impl ScriptOffsetCtx {
             total_offset_indexes: 0,
             total_commitment_keys: 0,
             unique_keys: Vec::new(),
+            next_chunk: 0,
         }
     }
 
     // Implement reset for TxInfo
-    fn reset(&mut self) {
+    pub fn reset(&mut self) {
         self.total_sender_offset_private_key = Zeroizing::new(RistrettoSecretKey::default());
         self.total_script_private_key = Zeroizing::new(RistrettoSecretKey::default());
         self.account = 0;
         self.total_offset_indexes = 0;
         self.total_commitment_keys = 0;
         self.unique_keys = Vec::new();
+        self.next_chunk = 0;
     }
 
-    fn add_unique_key(&mut self, secret_key: Zeroizing<RistrettoSecretKey>) {
-        if !self.unique_keys.contains(&secret_key) {
-            self.unique_keys.push(secret_key);
+    fn add_unique_key(&mut self, secret_key: Zeroizing<RistrettoSecretKey>) -> Result<(), AppSW> {
+        if self.unique_keys.contains(&secret_key) {
+            return Ok(());
         }
+        if (self.unique_keys.len() + 1) * PRIVATE_KEY_SIZE > MAX_REASSEMBLY_LEN {
+            SingleMessage::new("Payload too large").show_and_wait();
+            self.reset();
+            return Err(AppSW::PayloadTooLarge);
+        }
+        self.unique_keys.push(secret_key);
+        Ok(())
     }
 }
 
@@ -91,9 +103,17 @@ pub fn handler_get_script_offset(
         // Reset offset context
         offset_ctx.reset();
         read_instructions(offset_ctx, data);
+        offset_ctx.next_chunk = 1;
         return Ok(());
     }
 
+    // A chunk that is repeated or skipped, e.g. by a host retrying a failed transfer, would corrupt the sums
+    if chunk != offset_ctx.next_chunk {
+        offset_ctx.reset();
+        return Err(AppSW::WrongP1P2);
+    }
+    offset_ctx.next_chunk = offset_ctx.next_chunk.saturating_add(1);
+
     if chunk == 1 {
         // The sum of managed private keys
         let k: Zeroizing<RistrettoSecretKey> = get_key_from_canonical_bytes::<RistrettoSecretKey>(&data[0..32])?.into();
@@ -111,7 +131,7 @@ pub fn handler_get_script_offset(
         let index = u64::from_le_bytes(index_bytes);
 
         let offset = derive_from_bip32_key(offset_ctx.account, index, KeyType::OneSidedSenderOffset)?;
-        offset_ctx.add_unique_key(offset.clone());
+        offset_ctx.add_unique_key(offset.clone())?;
         offset_ctx.total_sender_offset_private_key =
             Zeroizing::new(offset_ctx.total_sender_offset_private_key.deref() + offset.deref());
     }
@@ -125,7 +145,7 @@ pub fn handler_get_script_offset(
 
         let k = alpha_hasher(alpha, blinding_factor)?;
 
-        offset_ctx.add_unique_key(k.clone());
+        offset_ctx.add_unique_key(k.clone())?;
         offset_ctx.total_script_private_key = Zeroizing::new(offset_ctx.total_script_private_key.deref() + k.deref());
     }
 
@@ -2,10 +2,14 @@
 // SPDX-License-Identifier: BSD-3-Clause
 
 use alloc::format;
+use core::ops::{Deref, Range};
 
 use blake2::Blake2b;
 use digest::consts::U64;
-use ledger_device_sdk::{io::Comm, ui::gadgets::SingleMessage};
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{SingleMessage, Validator},
+};
 use tari_crypto::{
     commitment::HomomorphicCommitmentFactory,
     keys::PublicKey,
@@ -13,6 +17,7 @@ use tari_crypto::{
         pedersen::{extended_commitment_factory::ExtendedPedersenCommitmentFactory, PedersenCommitment},
         RistrettoComAndPubSig,
         RistrettoPublicKey,
+        RistrettoSchnorr,
         RistrettoSecretKey,
     },
 };
@@ -22,47 +27,173 @@ use zeroize::Zeroizing;
 use crate::{
     alloc::string::ToString,
     hashing::DomainSeparatedConsensusHasher,
-    utils::{alpha_hasher, derive_from_bip32_key, get_key_from_canonical_bytes, get_random_nonce},
+    utils::{alpha_hasher, clear_apdu_buffer, derive_from_bip32_key, get_key_from_canonical_bytes, get_random_nonce},
     AppSW,
     KeyType,
     RESPONSE_VERSION,
     STATIC_SPEND_INDEX,
 };
 
+/// The payload format this handler understands, sent as the first byte of the payload
+const PAYLOAD_FORMAT_VERSION: u8 = 1;
+/// Payload length (after the format version byte) without the optional trailing spend key derivation index
+const PAYLOAD_LENGTH: usize = 184;
+/// Payload length when a spend key derivation index is appended
+const PAYLOAD_LENGTH_WITH_INDEX: usize = PAYLOAD_LENGTH + 8;
+
+// Field offsets of the payload, after the format version byte
+const ACCOUNT: Range<usize> = 0..8;
+const NETWORK: Range<usize> = 8..16;
+const TXI_VERSION: Range<usize> = 16..24;
+const BLINDING_FACTOR: Range<usize> = 24..56;
+const VALUE: Range<usize> = 56..88;
+const COMMITMENT_PRIVATE_KEY: Range<usize> = 88..120;
+const COMMITMENT: Range<usize> = 120..152;
+const SCRIPT_MESSAGE: Range<usize> = 152..184;
+const SPEND_INDEX: Range<usize> = 184..192;
+
+/// Returns true if the fields have the given lengths and follow each other without gaps or overlaps, from offset 0 up
+/// to `total_length`
+const fn fields_tile(fields: &[(Range<usize>, usize)], total_length: usize) -> bool {
+    let mut offset = 0;
+    let mut i = 0;
+    while i < fields.len() {
+        let (field, length) = &fields[i];
+        if field.start != offset || field.end != offset + *length {
+            return false;
+        }
+        offset = field.end;
+        i += 1;
+    }
+    offset == total_length
+}
+
+const _: () = assert!(fields_tile(
+    &[
+        (ACCOUNT, 8),
+        (NETWORK, 8),
+        (TXI_VERSION, 8),
+        (BLINDING_FACTOR, 32),
+        (VALUE, 32),
+        (COMMITMENT_PRIVATE_KEY, 32),
+        (COMMITMENT, 32),
+        (SCRIPT_MESSAGE, 32),
+    ],
+    PAYLOAD_LENGTH,
+));
+const _: () = assert!(SPEND_INDEX.start == PAYLOAD_LENGTH && SPEND_INDEX.end == PAYLOAD_LENGTH_WITH_INDEX);
+/// The longest payload this handler accepts, including the format version byte
+const MAX_PAYLOAD_LENGTH: usize = 1 + PAYLOAD_LENGTH_WITH_INDEX;
+/// Domain separation label of the script signature challenge
+pub const SCRIPT_CHALLENGE_LABEL: &str = "script_challenge";
+/// Derivation indexes are hardened, so they must fit in 31 bits
+const MAX_DERIVATION_INDEX: u64 = 0x7fff_ffff;
+
+// Field offsets of the script signature share payload
+const SHARE_ACCOUNT: Range<usize> = 0..8;
+const SHARE_NETWORK: Range<usize> = 8..16;
+const SHARE_TXI_VERSION: Range<usize> = 16..24;
+const SHARE_PRIVATE_KEY_INDEX: Range<usize> = 24..32;
+const SHARE_PRIVATE_KEY_BRANCH: Range<usize> = 32..40;
+const SHARE_NONCE_INDEX: Range<usize> = 40..48;
+const SHARE_NONCE_BRANCH: Range<usize> = 48..56;
+const SHARE_EPHEMERAL_COMMITMENT: Range<usize> = 56..88;
+const SHARE_EPHEMERAL_PUBKEY: Range<usize> = 88..120;
+const SHARE_SCRIPT_PUBLIC_KEY: Range<usize> = 120..152;
+const SHARE_COMMITMENT: Range<usize> = 152..184;
+const SHARE_SCRIPT_MESSAGE: Range<usize> = 184..216;
+/// Length of the script signature share payload
+const SHARE_PAYLOAD_LENGTH: usize = 216;
+
+const _: () = assert!(fields_tile(
+    &[
+        (SHARE_ACCOUNT, 8),
+        (SHARE_NETWORK, 8),
+        (SHARE_TXI_VERSION, 8),
+        (SHARE_PRIVATE_KEY_INDEX, 8),
+        (SHARE_PRIVATE_KEY_BRANCH, 8),
+        (SHARE_NONCE_INDEX, 8),
+        (SHARE_NONCE_BRANCH, 8),
+        (SHARE_EPHEMERAL_COMMITMENT, 32),
+        (SHARE_EPHEMERAL_PUBKEY, 32),
+        (SHARE_SCRIPT_PUBLIC_KEY, 32),
+        (SHARE_COMMITMENT, 32),
+        (SHARE_SCRIPT_MESSAGE, 32),
+    ],
+    SHARE_PAYLOAD_LENGTH,
+));
+
 pub fn handler_get_script_signature(comm: &mut Comm) -> Result<(), AppSW> {
+    // The payload carries the blinding factor, value and mask. It is copied into a buffer that is zeroized when the
+    // handler returns, and the APDU buffer is cleared straight away, so that they are wiped on every exit path.
     let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
-    if data.len() != 184 {
+    let payload_len = data.len();
+    let mut payload = Zeroizing::new([0u8; MAX_PAYLOAD_LENGTH]);
+    if payload_len <= MAX_PAYLOAD_LENGTH {
+        payload[..payload_len].copy_from_slice(data);
+    }
+    clear_apdu_buffer(comm);
+    if payload_len > MAX_PAYLOAD_LENGTH {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+    let data = &payload[..payload_len];
+
+    let (format_version, data) = match data.split_first() {
+        Some((format_version, data)) => (*format_version, data),
+        None => {
+            SingleMessage::new("Invalid data length").show_and_wait();
+            return Err(AppSW::WrongApduLength);
+        },
+    };
+    if format_version != PAYLOAD_FORMAT_VERSION {
+        SingleMessage::new("Unsupported payload version").show_and_wait();
+        return Err(AppSW::VersionMismatch);
+    }
+    if data.len() != PAYLOAD_LENGTH && data.len() != PAYLOAD_LENGTH_WITH_INDEX {
         SingleMessage::new("Invalid data length").show_and_wait();
         return Err(AppSW::WrongApduLength);
     }
 
     let mut account_bytes = [0u8; 8];
-    account_bytes.clone_from_slice(&data[0..8]);
+    account_bytes.clone_from_slice(&data[ACCOUNT]);
     let account = u64::from_le_bytes(account_bytes);
 
     let mut network_bytes = [0u8; 8];
-    network_bytes.clone_from_slice(&data[8..16]);
+    network_bytes.clone_from_slice(&data[NETWORK]);
     let network = u64::from_le_bytes(network_bytes);
 
     let mut txi_version_bytes = [0u8; 8];
-    txi_version_bytes.clone_from_slice(&data[16..24]);
+    txi_version_bytes.clone_from_slice(&data[TXI_VERSION]);
     let txi_version = u64::from_le_bytes(txi_version_bytes);
 
-    let alpha = derive_from_bip32_key(account, STATIC_SPEND_INDEX, KeyType::Spend)?;
+    let spend_index = if data.len() == PAYLOAD_LENGTH_WITH_INDEX {
+        let mut spend_index_bytes = [0u8; 8];
+        spend_index_bytes.clone_from_slice(&data[SPEND_INDEX]);
+        let spend_index = u64::from_le_bytes(spend_index_bytes);
+        if spend_index > MAX_DERIVATION_INDEX {
+            SingleMessage::new("Invalid derivation index").show_and_wait();
+            return Err(AppSW::KeyDeriveFail);
+        }
+        spend_index
+    } else {
+        STATIC_SPEND_INDEX
+    };
+
+    let alpha = derive_from_bip32_key(account, spend_index, KeyType::Spend)?;
     let blinding_factor: Zeroizing<RistrettoSecretKey> =
-        get_key_from_canonical_bytes::<RistrettoSecretKey>(&data[24..56])?.into();
+        get_key_from_canonical_bytes::<RistrettoSecretKey>(&data[BLINDING_FACTOR])?.into();
     let script_private_key = alpha_hasher(alpha, blinding_factor)?;
     let script_public_key = RistrettoPublicKey::from_secret_key(&script_private_key);
 
-    let value: Zeroizing<RistrettoSecretKey> =
-        get_key_from_canonical_bytes::<RistrettoSecretKey>(&data[56..88])?.into();
+    let value: Zeroizing<RistrettoSecretKey> = get_key_from_canonical_bytes::<RistrettoSecretKey>(&data[VALUE])?.into();
     let commitment_private_key: Zeroizing<RistrettoSecretKey> =
-        get_key_from_canonical_bytes::<RistrettoSecretKey>(&data[88..120])?.into();
+        get_key_from_canonical_bytes::<RistrettoSecretKey>(&data[COMMITMENT_PRIVATE_KEY])?.into();
 
-    let commitment: PedersenCommitment = get_key_from_canonical_bytes(&data[120..152])?;
+    let commitment: PedersenCommitment = get_key_from_canonical_bytes(&data[COMMITMENT])?;
 
     let mut script_message = [0u8; 32];
-    script_message.clone_from_slice(&data[152..184]);
+    script_message.clone_from_slice(&data[SCRIPT_MESSAGE]);
 
     let r_a = get_random_nonce()?;
     let r_x = get_random_nonce()?;
@@ -77,7 +208,7 @@ pub fn handler_get_script_signature(comm: &mut Comm) -> Result<(), AppSW> {
     let ephemeral_commitment = factory.commit(&r_x, &r_a);
     let ephemeral_pubkey = RistrettoPublicKey::from_secret_key(&r_y);
 
-    let challenge = finalize_script_signature_challenge(
+    let challenge = Zeroizing::new(finalize_script_signature_challenge(
         txi_version,
         network,
         &ephemeral_commitment,
@@ -85,7 +216,7 @@ pub fn handler_get_script_signature(comm: &mut Comm) -> Result<(), AppSW> {
         &script_public_key,
         &commitment,
         &script_message,
-    );
+    ));
 
     let script_signature = match RistrettoComAndPubSig::sign(
         &value,
@@ -94,7 +225,7 @@ pub fn handler_get_script_signature(comm: &mut Comm) -> Result<(), AppSW> {
         &r_a,
         &r_x,
         &r_y,
-        &challenge,
+        &*challenge,
         &factory,
     ) {
         Ok(sig) => sig,
@@ -111,6 +242,89 @@ pub fn handler_get_script_signature(comm: &mut Comm) -> Result<(), AppSW> {
     Ok(())
 }
 
+/// Signs a share of an aggregated script signature, as the parties of a multi-party input do. The host sends the
+/// aggregated ephemeral commitment and public key, the total script public key, the commitment and the script message,
+/// and the device builds the script signature challenge from them. The challenge is never taken from the host, so a
+/// share cannot be turned into a kernel or any other signature, and it may be signed with a spend branch key.
+///
+/// The user must approve the share before it is signed. The nonce key is derived from the given index, since its
+/// public key was shared with the other parties, so it must never be used for more than one share.
+pub fn handler_get_script_signature_share(comm: &mut Comm) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != SHARE_PAYLOAD_LENGTH {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[SHARE_ACCOUNT]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let mut network_bytes = [0u8; 8];
+    network_bytes.clone_from_slice(&data[SHARE_NETWORK]);
+    let network = u64::from_le_bytes(network_bytes);
+
+    let mut txi_version_bytes = [0u8; 8];
+    txi_version_bytes.clone_from_slice(&data[SHARE_TXI_VERSION]);
+    let txi_version = u64::from_le_bytes(txi_version_bytes);
+
+    let mut private_key_index_bytes = [0u8; 8];
+    private_key_index_bytes.clone_from_slice(&data[SHARE_PRIVATE_KEY_INDEX]);
+    let private_key_index = u64::from_le_bytes(private_key_index_bytes);
+
+    let mut private_key_branch_bytes = [0u8; 8];
+    private_key_branch_bytes.clone_from_slice(&data[SHARE_PRIVATE_KEY_BRANCH]);
+    let private_key_type = KeyType::from_branch_key(u64::from_le_bytes(private_key_branch_bytes))?;
+
+    let mut nonce_index_bytes = [0u8; 8];
+    nonce_index_bytes.clone_from_slice(&data[SHARE_NONCE_INDEX]);
+    let nonce_index = u64::from_le_bytes(nonce_index_bytes);
+
+    let mut nonce_branch_bytes = [0u8; 8];
+    nonce_branch_bytes.clone_from_slice(&data[SHARE_NONCE_BRANCH]);
+    let nonce_key_type = KeyType::from_branch_key(u64::from_le_bytes(nonce_branch_bytes))?;
+
+    let ephemeral_commitment: PedersenCommitment = get_key_from_canonical_bytes(&data[SHARE_EPHEMERAL_COMMITMENT])?;
+    let ephemeral_pubkey: RistrettoPublicKey = get_key_from_canonical_bytes(&data[SHARE_EPHEMERAL_PUBKEY])?;
+    let script_public_key: RistrettoPublicKey = get_key_from_canonical_bytes(&data[SHARE_SCRIPT_PUBLIC_KEY])?;
+    let commitment: PedersenCommitment = get_key_from_canonical_bytes(&data[SHARE_COMMITMENT])?;
+
+    let mut script_message = [0u8; 32];
+    script_message.clone_from_slice(&data[SHARE_SCRIPT_MESSAGE]);
+
+    if !Validator::new("Sign script share").ask() {
+        return Err(AppSW::UserCancelled);
+    }
+
+    let private_key = derive_from_bip32_key(account, private_key_index, private_key_type)?;
+    let private_nonce = derive_from_bip32_key(account, nonce_index, nonce_key_type)?;
+
+    let challenge = Zeroizing::new(finalize_script_signature_challenge(
+        txi_version,
+        network,
+        &ephemeral_commitment,
+        &ephemeral_pubkey,
+        &script_public_key,
+        &commitment,
+        &script_message,
+    ));
+
+    let signature = match RistrettoSchnorr::sign_raw_uniform(&private_key, private_nonce.deref().clone(), &*challenge) {
+        Ok(sig) => sig,
+        Err(e) => {
+            SingleMessage::new(&format!("Signing error: {:?}", e.to_string())).show_and_wait();
+            return Err(AppSW::RawSchnorrSignatureFail);
+        },
+    };
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(&signature.get_public_nonce().to_vec());
+    comm.append(&signature.get_signature().to_vec());
+    comm.reply_ok();
+
+    Ok(())
+}
+
 fn finalize_script_signature_challenge(
     _version: u64,
     network: u64,
@@ -120,7 +334,7 @@ fn finalize_script_signature_challenge(
     commitment: &PedersenCommitment,
     message: &[u8; 32],
 ) -> [u8; 64] {
-    DomainSeparatedConsensusHasher::<TransactionHashDomain, Blake2b<U64>>::new("script_challenge", network)
+    DomainSeparatedConsensusHasher::<TransactionHashDomain, Blake2b<U64>>::new(SCRIPT_CHALLENGE_LABEL, network)
         .chain(ephemeral_commitment)
         .chain(ephemeral_pubkey)
         .chain(script_public_key)
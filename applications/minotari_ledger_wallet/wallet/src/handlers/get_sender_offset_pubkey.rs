@@ -0,0 +1,35 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use ledger_device_sdk::{io::Comm, ui::gadgets::SingleMessage};
+use tari_crypto::{keys::PublicKey, ristretto::RistrettoPublicKey, tari_utilities::ByteArray};
+
+use crate::{utils::derive_from_bip32_key, AppSW, KeyType, RESPONSE_VERSION};
+
+/// Returns the public sender offset key for the given output index. The private key never leaves the device.
+pub fn handler_get_sender_offset_pubkey(comm: &mut Comm) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 16 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let mut index_bytes = [0u8; 8];
+    index_bytes.clone_from_slice(&data[8..16]);
+    let index = u64::from_le_bytes(index_bytes);
+
+    let sender_offset_pubkey = match derive_from_bip32_key(account, index, KeyType::OneSidedSenderOffset) {
+        Ok(k) => RistrettoPublicKey::from_secret_key(&k),
+        Err(e) => return Err(e),
+    };
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(sender_offset_pubkey.as_bytes());
+    comm.reply_ok();
+
+    Ok(())
+}
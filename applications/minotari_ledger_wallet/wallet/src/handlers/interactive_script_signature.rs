@@ -0,0 +1,228 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use alloc::format;
+use core::ops::Deref;
+
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{MessageScroller, SingleMessage, Validator},
+};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    hash_domain,
+    keys::PublicKey,
+    ristretto::{
+        pedersen::extended_commitment_factory::ExtendedPedersenCommitmentFactory,
+        RistrettoComAndPubSig,
+        RistrettoPublicKey,
+        RistrettoSecretKey,
+    },
+    signatures::SchnorrSignature,
+    tari_utilities::ByteArray,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    alloc::string::ToString,
+    utils::{
+        alpha_hasher,
+        derive_from_bip32_key,
+        get_key_from_canonical_bytes,
+        get_random_nonce,
+        tari_address_to_base58,
+        TARI_DUAL_ADDRESS_SIZE,
+    },
+    AppSW,
+    KeyType,
+    RESPONSE_VERSION,
+    STATIC_SPEND_INDEX,
+};
+
+// This label must never be shared with script, kernel, message, ownership proof or payment id signing, otherwise a
+// transfer approval could be replayed as one of those signatures.
+hash_domain!(TransferApprovalHashDomain, "com.tari.ledger.transfer_approval", 1);
+
+/// A signature by the account spend key over an approved script signature challenge, amount and recipient address
+pub type TransferApprovalSignature =
+    SchnorrSignature<RistrettoPublicKey, RistrettoSecretKey, TransferApprovalHashDomain>;
+
+struct ScriptSignatureNonces {
+    account: u64,
+    r_a: Zeroizing<RistrettoSecretKey>,
+    r_x: Zeroizing<RistrettoSecretKey>,
+    r_y: Zeroizing<RistrettoSecretKey>,
+}
+
+/// Holds the nonces of an interactive script signature between the commit and finalize rounds. The nonces are
+/// consumed by the finalize round, so they can never be used for more than one signature.
+///
+/// Commands that do not sign may be sent between the rounds. A new commit round replaces the nonces, and any other
+/// signing command or `ResetSigningSession` discards them.
+pub struct ScriptSignatureCtx {
+    nonces: Option<ScriptSignatureNonces>,
+}
+
+impl ScriptSignatureCtx {
+    pub fn new() -> Self {
+        Self { nonces: None }
+    }
+
+    /// Discards the stored nonces, which are zeroized when dropped
+    pub fn reset(&mut self) {
+        self.nonces = None;
+    }
+}
+
+/// Round 1: generates and stores fresh nonces and returns the ephemeral commitment and ephemeral public key
+pub fn handler_script_sig_commit(comm: &mut Comm, ctx: &mut ScriptSignatureCtx) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 8 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let r_a = get_random_nonce()?;
+    let r_x = get_random_nonce()?;
+    let r_y = get_random_nonce()?;
+    if r_a == r_x || r_a == r_y || r_x == r_y {
+        SingleMessage::new("Nonces not unique!").show_and_wait();
+        return Err(AppSW::ScriptSignatureFail);
+    }
+
+    let factory = ExtendedPedersenCommitmentFactory::default();
+    let ephemeral_commitment = factory.commit(&r_x, &r_a);
+    let ephemeral_pubkey = RistrettoPublicKey::from_secret_key(&r_y);
+
+    ctx.nonces = Some(ScriptSignatureNonces { account, r_a, r_x, r_y });
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(ephemeral_commitment.as_bytes());
+    comm.append(ephemeral_pubkey.as_bytes());
+    comm.reply_ok();
+
+    Ok(())
+}
+
+/// Length of the round 2 payload: account, blinding factor, value, commitment private key and challenge
+const FINALIZE_PAYLOAD_LENGTH: usize = 168;
+/// Length of a Tari single address: network, features, spend key and checksum
+const TARI_SINGLE_ADDRESS_SIZE: usize = 35;
+
+/// Round 2: the round 2 payload is followed by the transfer amount and the recipient address, a Tari single or dual
+/// address. The amount must match the value being signed for. Both are shown on the device and the user must approve
+/// them before the challenge is signed. There is no round 2 without approval.
+///
+/// The response holds the script signature followed by a [`TransferApprovalSignature`] over the challenge, the amount
+/// and the recipient address, which verifies against the account's public spend key. The challenge alone does not
+/// commit to the recipient, so the approval signature is what binds the recipient the user saw to the script
+/// signature.
+pub fn handler_script_sig_finalize_confirmed(comm: &mut Comm, ctx: &mut ScriptSignatureCtx) -> Result<(), AppSW> {
+    // Take the nonces out of the context so that they are discarded whatever the outcome
+    let nonces = ctx.nonces.take();
+
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    let address_offset = FINALIZE_PAYLOAD_LENGTH + 8;
+    let recipient_length = data.len().saturating_sub(address_offset);
+    if recipient_length != TARI_SINGLE_ADDRESS_SIZE && recipient_length != TARI_DUAL_ADDRESS_SIZE {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut amount_bytes = [0u8; 8];
+    amount_bytes.clone_from_slice(&data[FINALIZE_PAYLOAD_LENGTH..address_offset]);
+    let amount = u64::from_le_bytes(amount_bytes);
+
+    // The amount shown to the user must be the value that is signed for, not whatever the host claims
+    let value: Zeroizing<RistrettoSecretKey> =
+        get_key_from_canonical_bytes::<RistrettoSecretKey>(&data[40..72])?.into();
+    if *value != RistrettoSecretKey::from(amount) {
+        SingleMessage::new("Amount does not match value").show_and_wait();
+        return Err(AppSW::AmountMismatch);
+    }
+
+    MessageScroller::new(&format!(
+        "Send {}.{:06} XTM to {}",
+        amount / 1_000_000,
+        amount % 1_000_000,
+        tari_address_to_base58(&data[address_offset..])
+    ))
+    .event_loop();
+    if !Validator::new("Approve transfer").ask() {
+        return Err(AppSW::UserCancelled);
+    }
+
+    let script_signature = sign_challenge(&data[..FINALIZE_PAYLOAD_LENGTH], nonces)?;
+
+    // The signed message is the challenge followed by the amount and the recipient address
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+    let spend_key = derive_from_bip32_key(account, STATIC_SPEND_INDEX, KeyType::Spend)?;
+    let random_nonce = get_random_nonce()?.deref().clone();
+    let approval = match TransferApprovalSignature::sign_with_nonce_and_message(&spend_key, random_nonce, &data[104..])
+    {
+        Ok(sig) => sig,
+        Err(e) => {
+            SingleMessage::new(&format!("Signing error: {:?}", e.to_string())).show_and_wait();
+            return Err(AppSW::ScriptSignatureFail);
+        },
+    };
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(&script_signature.to_vec());
+    comm.append(&approval.get_public_nonce().to_vec());
+    comm.append(&approval.get_signature().to_vec());
+    comm.reply_ok();
+
+    Ok(())
+}
+
+fn sign_challenge(data: &[u8], nonces: Option<ScriptSignatureNonces>) -> Result<RistrettoComAndPubSig, AppSW> {
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let nonces = match nonces {
+        Some(nonces) if nonces.account == account => nonces,
+        _ => {
+            SingleMessage::new("No signing round started").show_and_wait();
+            return Err(AppSW::ScriptSignatureFail);
+        },
+    };
+
+    let alpha = derive_from_bip32_key(account, STATIC_SPEND_INDEX, KeyType::Spend)?;
+    let blinding_factor: Zeroizing<RistrettoSecretKey> =
+        get_key_from_canonical_bytes::<RistrettoSecretKey>(&data[8..40])?.into();
+    let script_private_key = alpha_hasher(alpha, blinding_factor)?;
+
+    let value: Zeroizing<RistrettoSecretKey> =
+        get_key_from_canonical_bytes::<RistrettoSecretKey>(&data[40..72])?.into();
+    let commitment_private_key: Zeroizing<RistrettoSecretKey> =
+        get_key_from_canonical_bytes::<RistrettoSecretKey>(&data[72..104])?.into();
+
+    let mut challenge = [0u8; 64];
+    challenge.clone_from_slice(&data[104..168]);
+
+    let factory = ExtendedPedersenCommitmentFactory::default();
+    match RistrettoComAndPubSig::sign(
+        &value,
+        &commitment_private_key,
+        &script_private_key,
+        &nonces.r_a,
+        &nonces.r_x,
+        &nonces.r_y,
+        &challenge,
+        &factory,
+    ) {
+        Ok(sig) => Ok(sig),
+        Err(e) => {
+            SingleMessage::new(&format!("Signing error: {:?}", e.to_string())).show_and_wait();
+            Err(AppSW::ScriptSignatureFail)
+        },
+    }
+}
@@ -0,0 +1,179 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use alloc::{format, vec::Vec};
+use core::mem::size_of;
+
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{SingleMessage, Validator},
+};
+use tari_crypto::{ristretto::RistrettoSchnorr, tari_utilities::ByteArray};
+
+use crate::{
+    handlers::get_kernel_signature::{confirm_lock_height, KernelSigningRequest},
+    AppSW,
+    MAX_REASSEMBLY_LEN,
+    RESPONSE_VERSION,
+};
+
+/// Collects the kernels of a batch across its chunks, and keeps their signatures once the user has approved the batch
+pub struct KernelBatchCtx {
+    account: u64,
+    network: u64,
+    num_kernels: u64,
+    total_fee: u64,
+    requests: Vec<KernelSigningRequest>,
+    signatures: Vec<RistrettoSchnorr>,
+}
+
+impl KernelBatchCtx {
+    pub fn new() -> Self {
+        Self {
+            account: 0,
+            network: 0,
+            num_kernels: 0,
+            total_fee: 0,
+            requests: Vec::new(),
+            signatures: Vec::new(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        *self = Self::new();
+    }
+}
+
+/// Signs several kernels of a transaction with a single confirmation.
+///
+/// Chunk 0 holds the account, the network and the number of kernels, and every following chunk holds the fields of
+/// one kernel in the same format as the kernel signature handler. Every challenge is built on the device, so the
+/// total fee shown on the last chunk is the sum of the fees bound into the signatures. If any kernel is time-locked,
+/// the highest lock height must be confirmed as well. Once approved, the signatures are read one at a time with
+/// [handler_kernel_batch_signature].
+pub fn handler_kernel_batch(comm: &mut Comm, chunk: u8, more: bool, ctx: &mut KernelBatchCtx) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+
+    if chunk == 0 {
+        ctx.reset();
+        if data.len() != 24 {
+            SingleMessage::new("Invalid data length").show_and_wait();
+            return Err(AppSW::WrongApduLength);
+        }
+
+        let mut account_bytes = [0u8; 8];
+        account_bytes.clone_from_slice(&data[0..8]);
+        ctx.account = u64::from_le_bytes(account_bytes);
+
+        let mut network_bytes = [0u8; 8];
+        network_bytes.clone_from_slice(&data[8..16]);
+        ctx.network = u64::from_le_bytes(network_bytes);
+
+        let mut num_kernels_bytes = [0u8; 8];
+        num_kernels_bytes.clone_from_slice(&data[16..24]);
+        ctx.num_kernels = u64::from_le_bytes(num_kernels_bytes);
+
+        let max_kernels = (MAX_REASSEMBLY_LEN / size_of::<KernelSigningRequest>()) as u64;
+        if ctx.num_kernels == 0 || ctx.num_kernels > max_kernels {
+            SingleMessage::new("Payload too large").show_and_wait();
+            ctx.reset();
+            return Err(AppSW::PayloadTooLarge);
+        }
+        return Ok(());
+    }
+
+    // Kernels must arrive in order, one per chunk, and only after the batch was started
+    if ctx.num_kernels == 0 || u64::from(chunk) != ctx.requests.len() as u64 + 1 || u64::from(chunk) > ctx.num_kernels {
+        ctx.reset();
+        return Err(AppSW::WrongP1P2);
+    }
+
+    let request = match KernelSigningRequest::read(ctx.account, ctx.network, data) {
+        Ok(request) => request,
+        Err(e) => {
+            ctx.reset();
+            return Err(e);
+        },
+    };
+    ctx.total_fee = match ctx.total_fee.checked_add(request.fee()) {
+        Some(total_fee) => total_fee,
+        None => {
+            SingleMessage::new("Invalid total fee").show_and_wait();
+            ctx.reset();
+            return Err(AppSW::AmountMismatch);
+        },
+    };
+    ctx.requests.push(request);
+
+    if more {
+        return Ok(());
+    }
+
+    if ctx.requests.len() as u64 != ctx.num_kernels {
+        SingleMessage::new("Incomplete kernel batch").show_and_wait();
+        ctx.reset();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    SingleMessage::new(&format!("Sign {} kernels", ctx.num_kernels)).show_and_wait();
+    SingleMessage::new(&format!("Total fee: {} uT", ctx.total_fee)).show_and_wait();
+    let max_lock_height = ctx.requests.iter().map(|r| r.lock_height()).max().unwrap_or(0);
+    if let Err(e) = confirm_lock_height(max_lock_height) {
+        ctx.reset();
+        return Err(e);
+    }
+    if !Validator::new("Sign kernels").ask() {
+        ctx.reset();
+        return Err(AppSW::UserCancelled);
+    }
+
+    let mut signatures = Vec::with_capacity(ctx.requests.len());
+    for request in &ctx.requests {
+        match request.sign() {
+            Ok(signature) => signatures.push(signature),
+            Err(e) => {
+                ctx.reset();
+                return Err(e);
+            },
+        }
+    }
+    ctx.requests = Vec::new();
+    ctx.signatures = signatures;
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.reply_ok();
+
+    Ok(())
+}
+
+/// Returns the signature of the kernel at the given index of the most recently approved batch
+pub fn handler_kernel_batch_signature(comm: &mut Comm, ctx: &mut KernelBatchCtx) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 16 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let mut index_bytes = [0u8; 8];
+    index_bytes.clone_from_slice(&data[8..16]);
+    let index = u64::from_le_bytes(index_bytes);
+
+    let signature = match usize::try_from(index).ok().and_then(|index| ctx.signatures.get(index)) {
+        Some(signature) if account == ctx.account => signature,
+        _ => {
+            SingleMessage::new("No approved kernel").show_and_wait();
+            return Err(AppSW::Deny);
+        },
+    };
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(&signature.get_public_nonce().to_vec());
+    comm.append(&signature.get_signature().to_vec());
+    comm.reply_ok();
+
+    Ok(())
+}
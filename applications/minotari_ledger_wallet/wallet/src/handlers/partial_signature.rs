@@ -0,0 +1,163 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use alloc::format;
+use core::ops::Deref;
+
+use blake2::Blake2b;
+use digest::consts::U64;
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{MessageScroller, SingleMessage, Validator},
+};
+use tari_crypto::{
+    hash_domain,
+    keys::PublicKey,
+    ristretto::{RistrettoPublicKey, RistrettoSecretKey},
+    signatures::SchnorrSignature,
+    tari_utilities::ByteArray,
+};
+use zeroize::Zeroizing;
+
+use crate::{
+    alloc::string::ToString,
+    utils::{bytes_to_hex_string, derive_from_bip32_key, get_key_from_canonical_bytes, get_random_nonce},
+    AppSW,
+    KeyType,
+    RESPONSE_VERSION,
+};
+
+// This label must never be shared with any other signature the device makes, so that a signature share can never be
+// combined into a kernel, script or any other signature.
+hash_domain!(PartialSignatureHashDomain, "com.tari.ledger.partial_signature", 1);
+
+/// A multi-party signature made from the devices' signature shares, which verifies against the aggregate public key
+pub type PartialSignature = SchnorrSignature<RistrettoPublicKey, RistrettoSecretKey, PartialSignatureHashDomain>;
+
+struct PartialSignatureNonce {
+    account: u64,
+    r: Zeroizing<RistrettoSecretKey>,
+}
+
+/// Holds this device's nonce between the commit and sign rounds of a multi-party signature. The nonce is consumed by
+/// the sign round, so it can never be used for more than one signature share.
+///
+/// Between the rounds the host may send commands that do not sign, such as key queries, while it agrees with the other
+/// parties on the aggregate nonce and public key. A new commit round replaces the nonce, and any other signing
+/// command or `ResetSigningSession` discards it.
+pub struct PartialSignatureCtx {
+    nonce: Option<PartialSignatureNonce>,
+}
+
+impl PartialSignatureCtx {
+    pub fn new() -> Self {
+        Self { nonce: None }
+    }
+
+    /// Discards the stored nonce, which is zeroized when dropped
+    pub fn reset(&mut self) {
+        self.nonce = None;
+    }
+}
+
+/// Round 1: generates and stores a fresh nonce and returns its public nonce for the host to aggregate
+pub fn handler_partial_sig_commit(comm: &mut Comm, ctx: &mut PartialSignatureCtx) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 8 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let r = get_random_nonce()?;
+    let public_nonce = RistrettoPublicKey::from_secret_key(&r);
+    ctx.nonce = Some(PartialSignatureNonce { account, r });
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(public_nonce.as_bytes());
+    comm.reply_ok();
+
+    Ok(())
+}
+
+/// Round 2: once the user approves, signs the message with the key at the given index and the nonce from round 1, and
+/// returns only this device's signature share. Spend branch keys are refused, since they sign kernels.
+///
+/// Every party contributes a public nonce `R_i` in round 1, and the host agrees with the other parties on the aggregate
+/// nonce `R = sum(R_i)` and the aggregate public key `P = sum(P_i)`. The device builds the challenge `e` from `R`, `P`
+/// and the 32-byte message under the [`PartialSignature`] domain, and returns `s_i = r_i + e·x_i`. The host sums the
+/// shares of all parties into `s = sum(s_i)`, and `(R, s)` is a [`PartialSignature`] over the message that verifies
+/// against `P`. A share on its own is not a valid signature.
+pub fn handler_partial_sig_sign(comm: &mut Comm, ctx: &mut PartialSignatureCtx) -> Result<(), AppSW> {
+    // Take the nonce out of the context so that it is discarded whatever the outcome
+    let nonce = ctx.nonce.take();
+
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 120 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let nonce = match nonce {
+        Some(nonce) if nonce.account == account => nonce,
+        _ => {
+            SingleMessage::new("No signing round started").show_and_wait();
+            return Err(AppSW::SchnorrSignatureFail);
+        },
+    };
+
+    let mut private_key_index_bytes = [0u8; 8];
+    private_key_index_bytes.clone_from_slice(&data[8..16]);
+    let private_key_index = u64::from_le_bytes(private_key_index_bytes);
+
+    let mut private_key_type_bytes = [0u8; 8];
+    private_key_type_bytes.clone_from_slice(&data[16..24]);
+    let private_key_type = KeyType::from_branch_key(u64::from_le_bytes(private_key_type_bytes))?;
+    if matches!(private_key_type, KeyType::Spend) {
+        SingleMessage::new("Use kernel signing").show_and_wait();
+        return Err(AppSW::Deny);
+    }
+
+    let aggregate_nonce = get_key_from_canonical_bytes::<RistrettoPublicKey>(&data[24..56])?;
+    let aggregate_public_key = get_key_from_canonical_bytes::<RistrettoPublicKey>(&data[56..88])?;
+
+    let mut message = [0u8; 32];
+    message.clone_from_slice(&data[88..120]);
+
+    MessageScroller::new(&format!(
+        "Co-sign message {} for key {}",
+        bytes_to_hex_string(&message),
+        bytes_to_hex_string(aggregate_public_key.as_bytes())
+    ))
+    .event_loop();
+    if !Validator::new("Approve signature share").ask() {
+        return Err(AppSW::UserCancelled);
+    }
+
+    let private_key = derive_from_bip32_key(account, private_key_index, private_key_type)?;
+    let challenge = PartialSignature::construct_domain_separated_challenge::<_, Blake2b<U64>>(
+        &aggregate_nonce,
+        &aggregate_public_key,
+        message,
+    );
+    let share = match PartialSignature::sign_raw_uniform(&private_key, nonce.r.deref().clone(), challenge.as_ref()) {
+        Ok(sig) => sig,
+        Err(e) => {
+            SingleMessage::new(&format!("Signing error: {:?}", e.to_string())).show_and_wait();
+            return Err(AppSW::SchnorrSignatureFail);
+        },
+    };
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(&share.get_signature().to_vec());
+    comm.reply_ok();
+
+    Ok(())
+}
@@ -0,0 +1,69 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use alloc::format;
+use core::ops::Deref;
+
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{MessageScroller, SingleMessage, Validator},
+};
+use tari_crypto::{
+    hash_domain,
+    ristretto::{RistrettoPublicKey, RistrettoSecretKey},
+    signatures::SchnorrSignature,
+    tari_utilities::ByteArray,
+};
+
+use crate::{
+    alloc::string::ToString,
+    utils::{bytes_to_hex_string, derive_from_bip32_key, get_random_nonce},
+    AppSW,
+    KeyType,
+    RESPONSE_VERSION,
+    STATIC_SPEND_INDEX,
+};
+
+// This label must never be shared with script, kernel or message signing, otherwise a verifier could hand the device
+// a challenge that is really one of those messages and reuse the proof as a valid signature elsewhere.
+hash_domain!(OwnershipProofHashDomain, "com.tari.ledger.ownership_proof", 1);
+
+/// A signature proving knowledge of the account spend key over a verifier-supplied challenge
+pub type OwnershipProofSignature = SchnorrSignature<RistrettoPublicKey, RistrettoSecretKey, OwnershipProofHashDomain>;
+
+pub fn handler_prove_ownership(comm: &mut Comm) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 40 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let mut challenge = [0u8; 32];
+    challenge.clone_from_slice(&data[8..40]);
+
+    MessageScroller::new(&format!("Challenge: {}", bytes_to_hex_string(&challenge))).event_loop();
+    if !Validator::new("Prove ownership").ask() {
+        return Err(AppSW::UserCancelled);
+    }
+
+    let spend_key = derive_from_bip32_key(account, STATIC_SPEND_INDEX, KeyType::Spend)?;
+    let random_nonce = get_random_nonce()?.deref().clone();
+    let signature = match OwnershipProofSignature::sign_with_nonce_and_message(&spend_key, random_nonce, &challenge) {
+        Ok(sig) => sig,
+        Err(e) => {
+            SingleMessage::new(&format!("Signing error: {:?}", e.to_string())).show_and_wait();
+            return Err(AppSW::SchnorrSignatureFail);
+        },
+    };
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(&signature.get_public_nonce().to_vec());
+    comm.append(&signature.get_signature().to_vec());
+    comm.reply_ok();
+
+    Ok(())
+}
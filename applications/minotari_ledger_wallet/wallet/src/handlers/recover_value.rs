@@ -0,0 +1,62 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use blake2::Blake2b;
+use digest::consts::U32;
+use ledger_device_sdk::{io::Comm, ui::gadgets::SingleMessage};
+use tari_crypto::{
+    hashing::DomainSeparatedHasher,
+    ristretto::{pedersen::PedersenCommitment, RistrettoSecretKey},
+    tari_utilities::ByteArray,
+};
+use tari_hashing::TransactionSecureNonceKdfDomain;
+use zeroize::Zeroizing;
+
+use crate::{
+    utils::{derive_from_bip32_key, get_key_from_canonical_bytes},
+    AppSW,
+    KeyType,
+    RESPONSE_VERSION,
+    STATIC_VIEW_INDEX,
+};
+
+/// Returns the key that decrypts the encrypted value and mask of the output with the given commitment. This is the
+/// same key derivation as `kdf_aead` in the base layer's `encrypted_data`, using the private view key as the
+/// encryption key.
+pub fn handler_recover_value(comm: &mut Comm) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 40 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let commitment: PedersenCommitment = get_key_from_canonical_bytes(&data[8..40])?;
+
+    let view_key = derive_from_bip32_key(account, STATIC_VIEW_INDEX, KeyType::ViewKey)?;
+
+    let recovery_key = value_encryption_key(&view_key, &commitment);
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(recovery_key.as_ref());
+    comm.reply_ok();
+
+    Ok(())
+}
+
+/// The key that encrypts the value and mask of the output with the given commitment, as `kdf_aead` in the base layer's
+/// `encrypted_data` derives it
+pub fn value_encryption_key(
+    encryption_key: &RistrettoSecretKey,
+    commitment: &PedersenCommitment,
+) -> Zeroizing<[u8; 32]> {
+    let mut key = Zeroizing::new([0u8; 32]);
+    DomainSeparatedHasher::<Blake2b<U32>, TransactionSecureNonceKdfDomain>::new_with_label("encrypted_value_and_mask")
+        .chain(encryption_key.as_bytes())
+        .chain(commitment.as_bytes())
+        .finalize_into(key.as_mut().into());
+    key
+}
@@ -0,0 +1,36 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use ledger_device_sdk::io::Comm;
+
+use crate::{
+    handlers::{
+        finalize_offsets::FinalizeOffsetsCtx,
+        get_metadata_signature::MetadataSignatureCtx,
+        get_script_offset::ScriptOffsetCtx,
+        kernel_batch::KernelBatchCtx,
+    },
+    AppSW,
+    RESPONSE_VERSION,
+};
+
+/// Discards the chunks of any chunked command that was started but not finished, so the host can resend a command
+/// from its first chunk after a transfer failed part way. It can be sent at any time, and succeeds whether or not a
+/// chunked command was in progress.
+pub fn handler_reset_reassembly(
+    comm: &mut Comm,
+    offset_ctx: &mut ScriptOffsetCtx,
+    kernel_batch_ctx: &mut KernelBatchCtx,
+    metadata_sig_ctx: &mut MetadataSignatureCtx,
+    finalize_offsets_ctx: &mut FinalizeOffsetsCtx,
+) -> Result<(), AppSW> {
+    offset_ctx.reset();
+    kernel_batch_ctx.reset();
+    metadata_sig_ctx.reset();
+    finalize_offsets_ctx.reset();
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.reply_ok();
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use ledger_device_sdk::io::Comm;
+
+use crate::{
+    handlers::{interactive_script_signature::ScriptSignatureCtx, partial_signature::PartialSignatureCtx},
+    AppSW,
+    RESPONSE_VERSION,
+};
+
+/// Discards the nonces of any interactive script signature or multi-party signature that was started but not
+/// finished. It can be sent at any time, and succeeds whether or not a signing round was in progress.
+pub fn handler_reset_signing_session(
+    comm: &mut Comm,
+    script_sig_ctx: &mut ScriptSignatureCtx,
+    partial_sig_ctx: &mut PartialSignatureCtx,
+) -> Result<(), AppSW> {
+    script_sig_ctx.reset();
+    partial_sig_ctx.reset();
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.reply_ok();
+
+    Ok(())
+}
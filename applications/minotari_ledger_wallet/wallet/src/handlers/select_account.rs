@@ -0,0 +1,111 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use alloc::format;
+
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{SingleMessage, Validator},
+};
+use tari_crypto::{keys::PublicKey, ristretto::RistrettoPublicKey, tari_utilities::ByteArray};
+
+use crate::{
+    utils::{bytes_to_hex_string, derive_from_bip32_key},
+    AppSW,
+    Instruction,
+    KeyType,
+    RESPONSE_VERSION,
+    STATIC_SPEND_INDEX,
+};
+
+/// Number of bytes of the public spend key shown to the user to identify an account
+const FINGERPRINT_LENGTH: usize = 8;
+
+/// The account the user confirmed with [handler_select_account]. Until an account is confirmed, signing commands may
+/// be for any account.
+pub struct AccountCtx {
+    selected_account: Option<u64>,
+}
+
+impl AccountCtx {
+    pub fn new() -> Self {
+        Self { selected_account: None }
+    }
+}
+
+/// Shows the fingerprint of an account's public spend key and asks the user to switch to that account. Once
+/// confirmed, signing commands for any other account are rejected until the user confirms another account.
+///
+/// The payload holds the account. The response holds the account's public spend key.
+pub fn handler_select_account(comm: &mut Comm, ctx: &mut AccountCtx) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() != 8 {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let spend_key = derive_from_bip32_key(account, STATIC_SPEND_INDEX, KeyType::Spend)?;
+    let public_spend_key = RistrettoPublicKey::from_secret_key(&spend_key);
+
+    SingleMessage::new(&format!("Account: {}", account)).show_and_wait();
+    SingleMessage::new(&format!(
+        "Key: {}",
+        bytes_to_hex_string(&public_spend_key.as_bytes()[..FINGERPRINT_LENGTH])
+    ))
+    .show_and_wait();
+    if !Validator::new("Switch account").ask() {
+        return Err(AppSW::UserCancelled);
+    }
+    ctx.selected_account = Some(account);
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(public_spend_key.as_bytes());
+    comm.reply_ok();
+
+    Ok(())
+}
+
+/// Rejects a signing command for another account than the one the user confirmed. Only the first chunk of a chunked
+/// command holds the account, so the other chunks are not checked.
+pub fn check_selected_account(comm: &Comm, ins: &Instruction, ctx: &AccountCtx) -> Result<(), AppSW> {
+    let selected_account = match ctx.selected_account {
+        Some(account) => account,
+        None => return Ok(()),
+    };
+    let account_offset = match ins {
+        // The script signature payload starts with its format version
+        Instruction::GetScriptSignature => 1,
+        Instruction::GetScriptOffset { chunk: 0, .. } |
+        Instruction::KernelBatch { chunk: 0, .. } |
+        Instruction::GetMetadataSignature { chunk: 0, .. } |
+        Instruction::FinalizeOffsets { chunk: 0, .. } |
+        Instruction::GetRawSchnorrSignature |
+        Instruction::GetScriptSchnorrSignature |
+        Instruction::ScriptSigCommit |
+        Instruction::ScriptSigFinalizeConfirmed |
+        Instruction::ProveOwnership |
+        Instruction::PartialSigCommit |
+        Instruction::PartialSigSign |
+        Instruction::GetKernelSignature |
+        Instruction::KernelBatchSignature |
+        Instruction::SignPaymentId => 0,
+        _ => return Ok(()),
+    };
+
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    let account_bytes = data
+        .get(account_offset..account_offset + 8)
+        .ok_or(AppSW::WrongApduLength)?;
+    let mut account = [0u8; 8];
+    account.clone_from_slice(account_bytes);
+    if u64::from_le_bytes(account) != selected_account {
+        SingleMessage::new("Account not selected").show_and_wait();
+        return Err(AppSW::AccountMismatch);
+    }
+
+    Ok(())
+}
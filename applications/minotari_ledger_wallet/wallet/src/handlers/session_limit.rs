@@ -0,0 +1,75 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use alloc::format;
+
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{SingleMessage, Validator},
+};
+
+use crate::{AppSW, Instruction, MAX_SIGS_PER_SESSION, RESPONSE_VERSION};
+
+/// Counts the signing commands of the session, so that a host cannot get an unbounded number of signatures without
+/// the user noticing
+pub struct SessionLimitCtx {
+    signatures: u64,
+}
+
+impl SessionLimitCtx {
+    pub fn new() -> Self {
+        Self { signatures: 0 }
+    }
+}
+
+/// Counts a signing command against the session limit, and rejects it once [MAX_SIGS_PER_SESSION] signing commands
+/// were sent since the app started or the user last confirmed [handler_reset_session_limit]. A chunked command counts
+/// once, on its last chunk. Commands are counted whether or not they succeed, since a failed attempt is as useful to a
+/// host grinding for a signature.
+pub fn check_session_limit(ins: &Instruction, ctx: &mut SessionLimitCtx) -> Result<(), AppSW> {
+    if !is_signing_instruction(ins) {
+        return Ok(());
+    }
+    if ctx.signatures >= MAX_SIGS_PER_SESSION {
+        SingleMessage::new("Signature limit reached").show_and_wait();
+        return Err(AppSW::SessionLimitExceeded);
+    }
+    ctx.signatures += 1;
+
+    Ok(())
+}
+
+/// Whether the command makes a signature, or returns a scalar derived from private keys. A chunked command only makes
+/// it on its last chunk.
+pub fn is_signing_instruction(ins: &Instruction) -> bool {
+    matches!(
+        ins,
+        Instruction::GetScriptSignature |
+            Instruction::GetRawSchnorrSignature |
+            Instruction::GetScriptSchnorrSignature |
+            Instruction::GetScriptSignatureShare |
+            Instruction::ScriptSigFinalizeConfirmed |
+            Instruction::ProveOwnership |
+            Instruction::PartialSigSign |
+            Instruction::GetKernelSignature |
+            Instruction::KernelBatchSignature |
+            Instruction::SignPaymentId |
+            Instruction::GetMetadataSignature { more: false, .. } |
+            Instruction::GetScriptOffset { more: false, .. } |
+            Instruction::FinalizeOffsets { more: false, .. }
+    )
+}
+
+/// Asks the user to allow another [MAX_SIGS_PER_SESSION] signing commands, and restarts the count once confirmed
+pub fn handler_reset_session_limit(comm: &mut Comm, ctx: &mut SessionLimitCtx) -> Result<(), AppSW> {
+    SingleMessage::new(&format!("{} signatures made", ctx.signatures)).show_and_wait();
+    if !Validator::new("Allow more signatures").ask() {
+        return Err(AppSW::UserCancelled);
+    }
+    ctx.signatures = 0;
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.reply_ok();
+
+    Ok(())
+}
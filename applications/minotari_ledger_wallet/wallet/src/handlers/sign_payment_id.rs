@@ -0,0 +1,77 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use alloc::format;
+use core::ops::Deref;
+
+use ledger_device_sdk::{
+    io::Comm,
+    ui::gadgets::{MessageScroller, SingleMessage, Validator},
+};
+use tari_crypto::{
+    hash_domain,
+    ristretto::{RistrettoPublicKey, RistrettoSecretKey},
+    signatures::SchnorrSignature,
+    tari_utilities::ByteArray,
+};
+
+use crate::{
+    alloc::string::ToString,
+    utils::{bytes_to_hex_string, derive_from_bip32_key, get_random_nonce},
+    AppSW,
+    KeyType,
+    RESPONSE_VERSION,
+    STATIC_SPEND_INDEX,
+};
+
+// This label must never be shared with script, kernel, message or ownership proof signing, otherwise a payment id
+// signature could be replayed as one of those signatures.
+hash_domain!(PaymentIdHashDomain, "com.tari.ledger.payment_id", 1);
+
+/// A signature by the account spend key binding a payment id to a transaction
+pub type PaymentIdSignature = SchnorrSignature<RistrettoPublicKey, RistrettoSecretKey, PaymentIdHashDomain>;
+
+/// Length of the transaction context the payment id is bound to
+const CONTEXT_LENGTH: usize = 32;
+
+/// Signs a payment id together with the transaction context it belongs to once the user has confirmed the payment id.
+///
+/// The payload holds the account, the 32 byte transaction context and the payment id. The signed message is the
+/// context followed by the payment id, and verifies against the account's public spend key, so the recipient can
+/// attribute the payment id to the sender's address.
+pub fn handler_sign_payment_id(comm: &mut Comm) -> Result<(), AppSW> {
+    let data = comm.get_data().map_err(|_| AppSW::WrongApduLength)?;
+    if data.len() <= 8 + CONTEXT_LENGTH {
+        SingleMessage::new("Invalid data length").show_and_wait();
+        return Err(AppSW::WrongApduLength);
+    }
+
+    let mut account_bytes = [0u8; 8];
+    account_bytes.clone_from_slice(&data[0..8]);
+    let account = u64::from_le_bytes(account_bytes);
+
+    let message = &data[8..];
+    let payment_id = &message[CONTEXT_LENGTH..];
+
+    MessageScroller::new(&format!("Payment id: {}", bytes_to_hex_string(payment_id))).event_loop();
+    if !Validator::new("Sign payment id").ask() {
+        return Err(AppSW::UserCancelled);
+    }
+
+    let spend_key = derive_from_bip32_key(account, STATIC_SPEND_INDEX, KeyType::Spend)?;
+    let random_nonce = get_random_nonce()?.deref().clone();
+    let signature = match PaymentIdSignature::sign_with_nonce_and_message(&spend_key, random_nonce, message) {
+        Ok(sig) => sig,
+        Err(e) => {
+            SingleMessage::new(&format!("Signing error: {:?}", e.to_string())).show_and_wait();
+            return Err(AppSW::SchnorrSignatureFail);
+        },
+    };
+
+    comm.append(&[RESPONSE_VERSION]); // version
+    comm.append(&signature.get_public_nonce().to_vec());
+    comm.append(&signature.get_signature().to_vec());
+    comm.reply_ok();
+
+    Ok(())
+}
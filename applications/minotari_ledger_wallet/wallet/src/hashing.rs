@@ -32,6 +32,12 @@ where D: Default
         self.update_consensus_encode(data);
         self
     }
+
+    /// Adds data that the host has already consensus encoded, e.g. a type the app does not decode itself
+    pub fn chain_encoded(mut self, encoded: &[u8]) -> Self {
+        self.hasher.update_encoded(encoded);
+        self
+    }
 }
 
 /// Domain separated borsh-encoding hasher.
@@ -59,6 +65,10 @@ impl<D: Digest + Default, M: DomainSeparation> DomainSeparatedBorshHasher<M, D>
         BorshSerialize::serialize(data, &mut self.writer)
             .expect("Incorrect implementation of BorshSerialize encountered. Implementations MUST be infallible.");
     }
+
+    pub fn update_encoded(&mut self, encoded: &[u8]) {
+        self.writer.0.update(encoded);
+    }
 }
 
 /// This private struct wraps a Digest and implements the Write trait to satisfy the consensus encoding trait.
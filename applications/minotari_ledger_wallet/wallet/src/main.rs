@@ -14,14 +14,38 @@ mod app_ui {
     pub mod menu;
 }
 mod handlers {
+    pub mod compute_view_tag;
+    pub mod confirm_address;
+    pub mod encrypt_value;
+    pub mod finalize_offsets;
+    pub mod get_address_checksum;
+    pub mod get_commitment;
+    pub mod get_derivation_info;
     pub mod get_dh_shared_secret;
+    pub mod get_encoding_info;
+    pub mod get_ephemeral_key;
+    pub mod get_kernel_signature;
+    pub mod get_metadata_signature;
+    pub mod get_network_info;
+    pub mod get_protocol_constants;
     pub mod get_public_key;
     pub mod get_public_spend_key;
     pub mod get_schnorr_signature;
     pub mod get_script_offset;
     pub mod get_script_signature;
+    pub mod get_sender_offset_pubkey;
     pub mod get_version;
     pub mod get_view_key;
+    pub mod interactive_script_signature;
+    pub mod kernel_batch;
+    pub mod partial_signature;
+    pub mod prove_ownership;
+    pub mod recover_value;
+    pub mod reset_reassembly;
+    pub mod reset_signing_session;
+    pub mod select_account;
+    pub mod session_limit;
+    pub mod sign_payment_id;
 }
 
 use core::mem::MaybeUninit;
@@ -29,14 +53,42 @@ use core::mem::MaybeUninit;
 use app_ui::menu::ui_menu_main;
 use critical_section::RawRestoreState;
 use handlers::{
+    compute_view_tag::handler_compute_view_tag,
+    confirm_address::handler_confirm_address,
+    encrypt_value::handler_encrypt_value,
+    finalize_offsets::{handler_finalize_offsets, FinalizeOffsetsCtx},
+    get_address_checksum::handler_get_address_checksum,
+    get_commitment::handler_get_commitment,
+    get_derivation_info::handler_get_derivation_info,
     get_dh_shared_secret::handler_get_dh_shared_secret,
+    get_encoding_info::handler_get_encoding_info,
+    get_ephemeral_key::handler_get_ephemeral_key,
+    get_kernel_signature::handler_get_kernel_signature,
+    get_metadata_signature::{handler_get_metadata_signature, MetadataSignatureCtx},
+    get_network_info::handler_get_network_info,
+    get_protocol_constants::handler_get_protocol_constants,
     get_public_key::handler_get_public_key,
     get_public_spend_key::handler_get_public_spend_key,
     get_schnorr_signature::{handler_get_raw_schnorr_signature, handler_get_script_schnorr_signature},
     get_script_offset::{handler_get_script_offset, ScriptOffsetCtx},
-    get_script_signature::handler_get_script_signature,
+    get_script_signature::{handler_get_script_signature, handler_get_script_signature_share},
+    get_sender_offset_pubkey::handler_get_sender_offset_pubkey,
     get_version::handler_get_version,
     get_view_key::handler_get_view_key,
+    interactive_script_signature::{
+        handler_script_sig_commit,
+        handler_script_sig_finalize_confirmed,
+        ScriptSignatureCtx,
+    },
+    kernel_batch::{handler_kernel_batch, handler_kernel_batch_signature, KernelBatchCtx},
+    partial_signature::{handler_partial_sig_commit, handler_partial_sig_sign, PartialSignatureCtx},
+    prove_ownership::handler_prove_ownership,
+    recover_value::handler_recover_value,
+    reset_reassembly::handler_reset_reassembly,
+    reset_signing_session::handler_reset_signing_session,
+    select_account::{check_selected_account, handler_select_account, AccountCtx},
+    session_limit::{check_session_limit, handler_reset_session_limit, is_signing_instruction, SessionLimitCtx},
+    sign_payment_id::handler_sign_payment_id,
 };
 #[cfg(feature = "pending_review_screen")]
 use ledger_device_sdk::ui::gadgets::display_pending_review;
@@ -106,6 +158,13 @@ pub enum AppSW {
     KeyDeriveFromUniform = AppSWMapping::KeyDeriveFromUniform as u16,
     RandomNonceFail = AppSWMapping::RandomNonceFail as u16,
     BadBranchKey = AppSWMapping::BadBranchKey as u16,
+    VersionMismatch = AppSWMapping::VersionMismatch as u16,
+    PayloadTooLarge = AppSWMapping::PayloadTooLarge as u16,
+    AmountMismatch = AppSWMapping::AmountMismatch as u16,
+    EncryptionFail = AppSWMapping::EncryptionFail as u16,
+    AccountMismatch = AppSWMapping::AccountMismatch as u16,
+    SessionLimitExceeded = AppSWMapping::SessionLimitExceeded as u16,
+    OffsetNotUnique = AppSWMapping::OffsetNotUnique as u16,
     WrongApduLength = StatusWords::BadLen as u16, // See ledger-device-rust-sdk/ledger_device_sdk/src/io.rs:16
     UserCancelled = StatusWords::UserCancelled as u16, // See ledger-device-rust-sdk/ledger_device_sdk/src/io.rs:16
 }
@@ -128,21 +187,55 @@ pub enum Instruction {
     GetDHSharedSecret,
     GetRawSchnorrSignature,
     GetScriptSchnorrSignature,
+    ConfirmAddress,
+    GetNetworkInfo,
+    RecoverValue,
+    ScriptSigCommit,
+    ScriptSigFinalizeConfirmed,
+    GetSenderOffsetPublicKey,
+    ProveOwnership,
+    PartialSigCommit,
+    PartialSigSign,
+    GetDerivationInfo,
+    EncryptValue,
+    GetKernelSignature,
+    KernelBatch { chunk: u8, more: bool },
+    KernelBatchSignature,
+    ComputeViewTag,
+    GetProtocolConstants,
+    SignPaymentId,
+    ResetSigningSession,
+    GetEncodingInfo,
+    GetMetadataSignature { chunk: u8, more: bool },
+    ResetReassembly,
+    SelectAccount,
+    GetEphemeralKey,
+    ResetSessionLimit,
+    GetAddressChecksum,
+    FinalizeOffsets { chunk: u8, more: bool },
+    GetCommitment,
+    GetScriptSignatureShare,
 }
 
 const P2_MORE: u8 = 0x01;
 const STATIC_SPEND_INDEX: u64 = 42;
 const STATIC_VIEW_INDEX: u64 = 57311; // No significance, just a random number by large dice roll
 const MAX_PAYLOADS: u8 = 250;
+/// The number of signing commands the app accepts before the user must allow more, see
+/// [handler_reset_session_limit]
+const MAX_SIGS_PER_SESSION: u64 = 1000;
+/// The maximum number of bytes a chunked instruction may accumulate in app state across its APDUs
+const MAX_REASSEMBLY_LEN: usize = 4096;
 
 #[repr(u8)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum KeyType {
     Spend = 0x01,
     Nonce = 0x02,
     ViewKey = 0x03,
     OneSidedSenderOffset = 0x04,
     Random = 0x06,
+    OneSidedEphemeral = 0x07,
 }
 
 impl KeyType {
@@ -159,6 +252,7 @@ impl KeyType {
                 BranchMapping::SenderOffsetLedger => Ok(Self::OneSidedSenderOffset),
                 BranchMapping::Spend => Ok(Self::Spend),
                 BranchMapping::RandomKey => Ok(Self::Random),
+                BranchMapping::OneSidedEphemeralLedger => Ok(Self::OneSidedEphemeral),
                 _ => Err(AppSW::BadBranchKey),
             }
         } else {
@@ -198,6 +292,45 @@ impl TryFrom<ApduHeader> for Instruction {
             (InstructionMapping::GetRawSchnorrSignature, 0, 0) => Ok(Instruction::GetRawSchnorrSignature),
             (InstructionMapping::GetScriptSchnorrSignature, 0, 0) => Ok(Instruction::GetScriptSchnorrSignature),
             (InstructionMapping::GetScriptSchnorrSignature, _, _) => Err(AppSW::WrongP1P2),
+            (InstructionMapping::ConfirmAddress, 0, 0) => Ok(Instruction::ConfirmAddress),
+            (InstructionMapping::GetNetworkInfo, 0, 0) => Ok(Instruction::GetNetworkInfo),
+            (InstructionMapping::RecoverValue, 0, 0) => Ok(Instruction::RecoverValue),
+            (InstructionMapping::ScriptSigCommit, 0, 0) => Ok(Instruction::ScriptSigCommit),
+            (InstructionMapping::ScriptSigFinalizeConfirmed, 0, 0) => Ok(Instruction::ScriptSigFinalizeConfirmed),
+            (InstructionMapping::GetSenderOffsetPublicKey, 0, 0) => Ok(Instruction::GetSenderOffsetPublicKey),
+            (InstructionMapping::ProveOwnership, 0, 0) => Ok(Instruction::ProveOwnership),
+            (InstructionMapping::PartialSigCommit, 0, 0) => Ok(Instruction::PartialSigCommit),
+            (InstructionMapping::PartialSigSign, 0, 0) => Ok(Instruction::PartialSigSign),
+            (InstructionMapping::GetDerivationInfo, 0, 0) => Ok(Instruction::GetDerivationInfo),
+            (InstructionMapping::EncryptValue, 0, 0) => Ok(Instruction::EncryptValue),
+            (InstructionMapping::GetKernelSignature, 0, 0) => Ok(Instruction::GetKernelSignature),
+            (InstructionMapping::KernelBatch, 0..=MAX_PAYLOADS, 0 | P2_MORE) => Ok(Instruction::KernelBatch {
+                chunk: value.p1,
+                more: value.p2 == P2_MORE,
+            }),
+            (InstructionMapping::KernelBatchSignature, 0, 0) => Ok(Instruction::KernelBatchSignature),
+            (InstructionMapping::ComputeViewTag, 0, 0) => Ok(Instruction::ComputeViewTag),
+            (InstructionMapping::GetProtocolConstants, 0, 0) => Ok(Instruction::GetProtocolConstants),
+            (InstructionMapping::SignPaymentId, 0, 0) => Ok(Instruction::SignPaymentId),
+            (InstructionMapping::ResetSigningSession, 0, 0) => Ok(Instruction::ResetSigningSession),
+            (InstructionMapping::GetEncodingInfo, 0, 0) => Ok(Instruction::GetEncodingInfo),
+            (InstructionMapping::GetMetadataSignature, 0..=MAX_PAYLOADS, 0 | P2_MORE) => {
+                Ok(Instruction::GetMetadataSignature {
+                    chunk: value.p1,
+                    more: value.p2 == P2_MORE,
+                })
+            },
+            (InstructionMapping::ResetReassembly, 0, 0) => Ok(Instruction::ResetReassembly),
+            (InstructionMapping::SelectAccount, 0, 0) => Ok(Instruction::SelectAccount),
+            (InstructionMapping::GetEphemeralKey, 0, 0) => Ok(Instruction::GetEphemeralKey),
+            (InstructionMapping::ResetSessionLimit, 0, 0) => Ok(Instruction::ResetSessionLimit),
+            (InstructionMapping::GetAddressChecksum, 0, 0) => Ok(Instruction::GetAddressChecksum),
+            (InstructionMapping::FinalizeOffsets, 0..=MAX_PAYLOADS, 0 | P2_MORE) => Ok(Instruction::FinalizeOffsets {
+                chunk: value.p1,
+                more: value.p2 == P2_MORE,
+            }),
+            (InstructionMapping::GetCommitment, 0, 0) => Ok(Instruction::GetCommitment),
+            (InstructionMapping::GetScriptSignatureShare, 0, 0) => Ok(Instruction::GetScriptSignatureShare),
             (_, _, _) => Err(AppSW::InsNotSupported),
         }
     }
@@ -218,12 +351,30 @@ extern "C" fn sample_main() {
 
     // This is long-lived over the span the ledger app is open, across multiple interactions
     let mut offset_ctx = ScriptOffsetCtx::new();
+    let mut script_sig_ctx = ScriptSignatureCtx::new();
+    let mut partial_sig_ctx = PartialSignatureCtx::new();
+    let mut kernel_batch_ctx = KernelBatchCtx::new();
+    let mut metadata_sig_ctx = MetadataSignatureCtx::new();
+    let mut account_ctx = AccountCtx::new();
+    let mut session_limit_ctx = SessionLimitCtx::new();
+    let mut finalize_offsets_ctx = FinalizeOffsetsCtx::new();
 
     loop {
         // Wait for either a specific button push to exit the app
         // or an APDU command
         if let Event::Command(ins) = ui_menu_main(&mut comm) {
-            match handle_apdu(&mut comm, ins, &mut offset_ctx) {
+            match handle_apdu(
+                &mut comm,
+                ins,
+                &mut offset_ctx,
+                &mut script_sig_ctx,
+                &mut partial_sig_ctx,
+                &mut kernel_batch_ctx,
+                &mut metadata_sig_ctx,
+                &mut account_ctx,
+                &mut session_limit_ctx,
+                &mut finalize_offsets_ctx,
+            ) {
                 Ok(()) => comm.reply_ok(),
                 Err(sw) => comm.reply(sw),
             }
@@ -231,7 +382,30 @@ extern "C" fn sample_main() {
     }
 }
 
-fn handle_apdu(comm: &mut Comm, ins: Instruction, offset_ctx: &mut ScriptOffsetCtx) -> Result<(), AppSW> {
+#[allow(clippy::too_many_arguments)]
+fn handle_apdu(
+    comm: &mut Comm,
+    ins: Instruction,
+    offset_ctx: &mut ScriptOffsetCtx,
+    script_sig_ctx: &mut ScriptSignatureCtx,
+    partial_sig_ctx: &mut PartialSignatureCtx,
+    kernel_batch_ctx: &mut KernelBatchCtx,
+    metadata_sig_ctx: &mut MetadataSignatureCtx,
+    account_ctx: &mut AccountCtx,
+    session_limit_ctx: &mut SessionLimitCtx,
+    finalize_offsets_ctx: &mut FinalizeOffsetsCtx,
+) -> Result<(), AppSW> {
+    // A signing session survives the queries the host makes between its rounds, but any other signing command
+    // discards its stored nonces
+    if is_signing_instruction(&ins) && !matches!(ins, Instruction::ScriptSigFinalizeConfirmed) {
+        script_sig_ctx.reset();
+    }
+    if is_signing_instruction(&ins) && !matches!(ins, Instruction::PartialSigSign) {
+        partial_sig_ctx.reset();
+    }
+    check_selected_account(comm, &ins, account_ctx)?;
+    check_session_limit(&ins, session_limit_ctx)?;
+
     match ins {
         Instruction::GetVersion => handler_get_version(comm),
         Instruction::GetAppName => {
@@ -246,5 +420,43 @@ fn handle_apdu(comm: &mut Comm, ins: Instruction, offset_ctx: &mut ScriptOffsetC
         Instruction::GetDHSharedSecret => handler_get_dh_shared_secret(comm),
         Instruction::GetRawSchnorrSignature => handler_get_raw_schnorr_signature(comm),
         Instruction::GetScriptSchnorrSignature => handler_get_script_schnorr_signature(comm),
+        Instruction::ConfirmAddress => handler_confirm_address(comm),
+        Instruction::GetNetworkInfo => handler_get_network_info(comm),
+        Instruction::RecoverValue => handler_recover_value(comm),
+        Instruction::ScriptSigCommit => handler_script_sig_commit(comm, script_sig_ctx),
+        Instruction::ScriptSigFinalizeConfirmed => handler_script_sig_finalize_confirmed(comm, script_sig_ctx),
+        Instruction::GetSenderOffsetPublicKey => handler_get_sender_offset_pubkey(comm),
+        Instruction::ProveOwnership => handler_prove_ownership(comm),
+        Instruction::PartialSigCommit => handler_partial_sig_commit(comm, partial_sig_ctx),
+        Instruction::PartialSigSign => handler_partial_sig_sign(comm, partial_sig_ctx),
+        Instruction::GetDerivationInfo => handler_get_derivation_info(comm),
+        Instruction::EncryptValue => handler_encrypt_value(comm),
+        Instruction::GetKernelSignature => handler_get_kernel_signature(comm),
+        Instruction::KernelBatch { chunk, more } => handler_kernel_batch(comm, chunk, more, kernel_batch_ctx),
+        Instruction::KernelBatchSignature => handler_kernel_batch_signature(comm, kernel_batch_ctx),
+        Instruction::ComputeViewTag => handler_compute_view_tag(comm),
+        Instruction::GetProtocolConstants => handler_get_protocol_constants(comm),
+        Instruction::SignPaymentId => handler_sign_payment_id(comm),
+        Instruction::ResetSigningSession => handler_reset_signing_session(comm, script_sig_ctx, partial_sig_ctx),
+        Instruction::GetEncodingInfo => handler_get_encoding_info(comm),
+        Instruction::GetMetadataSignature { chunk, more } => {
+            handler_get_metadata_signature(comm, chunk, more, metadata_sig_ctx)
+        },
+        Instruction::ResetReassembly => handler_reset_reassembly(
+            comm,
+            offset_ctx,
+            kernel_batch_ctx,
+            metadata_sig_ctx,
+            finalize_offsets_ctx,
+        ),
+        Instruction::SelectAccount => handler_select_account(comm, account_ctx),
+        Instruction::GetEphemeralKey => handler_get_ephemeral_key(comm),
+        Instruction::ResetSessionLimit => handler_reset_session_limit(comm, session_limit_ctx),
+        Instruction::GetAddressChecksum => handler_get_address_checksum(comm),
+        Instruction::FinalizeOffsets { chunk, more } => {
+            handler_finalize_offsets(comm, chunk, more, finalize_offsets_ctx)
+        },
+        Instruction::GetCommitment => handler_get_commitment(comm),
+        Instruction::GetScriptSignatureShare => handler_get_script_signature_share(comm),
     }
 }
@@ -8,7 +8,7 @@ use blake2::Blake2b;
 use digest::{consts::U64, Digest};
 use ledger_device_sdk::{
     ecc::{bip32_derive, make_bip32_path, CurvesId, CxError},
-    io::SyscallError,
+    io::{Comm, SyscallError},
     random::LedgerRng,
     ui::gadgets::SingleMessage,
 };
@@ -16,14 +16,17 @@ use rand_core::RngCore;
 use tari_crypto::{
     hashing::DomainSeparatedHasher,
     keys::SecretKey,
-    ristretto::RistrettoSecretKey,
+    ristretto::{RistrettoPublicKey, RistrettoSecretKey},
     tari_utilities::ByteArray,
 };
 use tari_hashing::{KeyManagerTransactionsHashDomain, LedgerHashDomain};
-use zeroize::Zeroizing;
+use zeroize::{Zeroize, Zeroizing};
 
 use crate::{
-    alloc::string::{String, ToString},
+    alloc::{
+        string::{String, ToString},
+        vec::Vec,
+    },
     AppSW,
     KeyType,
     BIP32_COIN_TYPE,
@@ -119,6 +122,96 @@ pub fn u64_to_string(number: u64) -> String {
     String::from_utf8_lossy(&buffer[..pos]).to_string()
 }
 
+/// Convert a byte slice to a lowercase hex string without using the standard library
+pub fn bytes_to_hex_string(bytes: &[u8]) -> String {
+    const HEX_CHARS: &[u8; 16] = b"0123456789abcdef";
+    let mut hex = String::with_capacity(bytes.len() * 2);
+    for byte in bytes {
+        hex.push(HEX_CHARS[(byte >> 4) as usize] as char);
+        hex.push(HEX_CHARS[(byte & 0x0f) as usize] as char);
+    }
+    hex
+}
+
+/// The base58 alphabet of the `bs58` crate, which the base layer uses to encode addresses
+const BASE58_ALPHABET: &[u8; 58] = b"123456789ABCDEFGHJKLMNPQRSTUVWXYZabcdefghijkmnopqrstuvwxyz";
+
+/// Convert a byte slice to a base58 string without using the standard library. This must match `bs58::encode`.
+pub fn bytes_to_base58_string(bytes: &[u8]) -> String {
+    // The base58 digits of the number the bytes represent, least significant first
+    let mut digits = Vec::with_capacity(bytes.len() * 138 / 100 + 1);
+    for byte in bytes {
+        let mut carry = u32::from(*byte);
+        for digit in digits.iter_mut() {
+            carry += u32::from(*digit) << 8;
+            *digit = (carry % 58) as u8;
+            carry /= 58;
+        }
+        while carry > 0 {
+            digits.push((carry % 58) as u8);
+            carry /= 58;
+        }
+    }
+
+    let mut base58 = String::with_capacity(bytes.len() + digits.len());
+    // Every leading zero byte is encoded as the first character of the alphabet
+    for _ in bytes.iter().take_while(|byte| **byte == 0) {
+        base58.push(BASE58_ALPHABET[0] as char);
+    }
+    for digit in digits.iter().rev() {
+        base58.push(BASE58_ALPHABET[usize::from(*digit)] as char);
+    }
+    base58
+}
+
+/// Convert the bytes of a Tari single or dual address to the base58 string of
+/// `tari_common_types::tari_address::TariAddress::to_base58`, which encodes the network, the features and the rest of
+/// the address separately. The address must hold at least the network and features bytes.
+pub fn tari_address_to_base58(address: &[u8]) -> String {
+    let mut base58 = bytes_to_base58_string(&address[0..1]);
+    base58.push_str(&bytes_to_base58_string(&address[1..2]));
+    base58.push_str(&bytes_to_base58_string(&address[2..]));
+    base58
+}
+
+/// The DammSum mask for a dictionary size of `2^8`, i.e. `1 + 2^4 + 2^3 + 2^1` for the coefficients `[4, 3, 1]`
+const DAMMSUM_MASK: u8 = 0b0001_1011;
+
+/// Compute the DammSum checksum for a byte slice. This must match `tari_common_types::dammsum::compute_checksum`.
+pub fn compute_checksum(data: &[u8]) -> u8 {
+    let mut result = 0u8;
+    for digit in data {
+        result ^= *digit;
+        let overflow = (result & (1 << 7)) != 0;
+        result <<= 1;
+        if overflow {
+            result ^= DAMMSUM_MASK;
+        }
+    }
+    result
+}
+
+/// The byte size of a Tari dual address: network, features, public view key, public spend key and checksum
+pub const TARI_DUAL_ADDRESS_SIZE: usize = 67;
+/// The default Tari address features, i.e. interactive and one-sided payments
+const TARI_ADDRESS_DEFAULT_FEATURES: u8 = 0x03;
+
+/// Assemble a Tari dual address with default features in the same byte layout as
+/// `tari_common_types::tari_address::DualAddress::to_bytes`
+pub fn tari_dual_address(
+    network: u64,
+    public_view_key: &RistrettoPublicKey,
+    public_spend_key: &RistrettoPublicKey,
+) -> [u8; TARI_DUAL_ADDRESS_SIZE] {
+    let mut buf = [0u8; TARI_DUAL_ADDRESS_SIZE];
+    buf[0] = network as u8;
+    buf[1] = TARI_ADDRESS_DEFAULT_FEATURES;
+    buf[2..34].copy_from_slice(public_view_key.as_bytes());
+    buf[34..66].copy_from_slice(public_spend_key.as_bytes());
+    buf[66] = compute_checksum(&buf[0..66]);
+    buf
+}
+
 // Convert CxError to a string for display
 fn cx_error_to_string(e: CxError) -> String {
     let err = match e {
@@ -236,11 +329,8 @@ pub fn alpha_hasher(
     Ok(Zeroizing::new(private_key.deref() + alpha.deref()))
 }
 
-pub fn derive_from_bip32_key(
-    u64_account: u64,
-    u64_index: u64,
-    u64_key_type: KeyType,
-) -> Result<Zeroizing<RistrettoSecretKey>, AppSW> {
+/// The BIP32 path of the key of the given type at the given index: `m/44'/535348'/account'/0/index'/key_type`
+pub fn bip32_key_path(u64_account: u64, u64_index: u64, u64_key_type: KeyType) -> [u32; 6] {
     let account = u64_to_string(u64_account);
     let index = u64_to_string(u64_index);
     let key_type = u64_to_string(u64_key_type.as_byte() as u64);
@@ -253,7 +343,15 @@ pub fn derive_from_bip32_key(
     bip32_path.push_str(&index);
     bip32_path.push_str(&"'/");
     bip32_path.push_str(&key_type);
-    let path: [u32; 6] = make_bip32_path(bip32_path.as_bytes());
+    make_bip32_path(bip32_path.as_bytes())
+}
+
+pub fn derive_from_bip32_key(
+    u64_account: u64,
+    u64_index: u64,
+    u64_key_type: KeyType,
+) -> Result<Zeroizing<RistrettoSecretKey>, AppSW> {
+    let path = bip32_key_path(u64_account, u64_index, u64_key_type);
 
     match get_raw_key(&path) {
         Ok(val) => get_key_from_uniform_bytes(&val.as_ref()),
@@ -265,12 +363,17 @@ pub fn derive_from_bip32_key(
 }
 
 pub fn get_random_nonce() -> Result<Zeroizing<RistrettoSecretKey>, AppSW> {
-    let mut raw_bytes = [0u8; 64];
-    LedgerRng.fill_bytes(&mut raw_bytes);
-    if raw_bytes == [0u8; 64] {
+    let mut raw_bytes = Zeroizing::new([0u8; 64]);
+    LedgerRng.fill_bytes(&mut *raw_bytes);
+    if *raw_bytes == [0u8; 64] {
         return Err(AppSW::RandomNonceFail);
     }
     Ok(Zeroizing::new(
-        RistrettoSecretKey::from_uniform_bytes(&raw_bytes).expect("will not fail"),
+        RistrettoSecretKey::from_uniform_bytes(&*raw_bytes).expect("will not fail"),
     ))
 }
+
+/// Overwrites the APDU buffer, so that secrets sent by the host do not stay in it after they have been read
+pub fn clear_apdu_buffer(comm: &mut Comm) {
+    comm.apdu_buffer.zeroize();
+}
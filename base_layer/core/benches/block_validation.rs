@@ -0,0 +1,121 @@
+//  Copyright 2024. The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+#[cfg(not(feature = "benches"))]
+mod benches {
+    pub fn main() {
+        println!("Enable the `benches` feature to run benches");
+    }
+}
+
+#[cfg(feature = "benches")]
+mod benches {
+    use criterion::{criterion_group, Criterion};
+    use tari_common_types::types::Commitment;
+    use tari_core::{
+        test_helpers::validation_bench::{build_test_block, run_validation_bench},
+        transactions::transaction_components::transaction_output::batch_verify_metadata_signatures,
+        validation::block_body::BlockBodyFullValidator,
+    };
+    use tokio::runtime::Runtime;
+
+    pub fn block_validation_perf_test(c: &mut Criterion) {
+        let runtime = Runtime::new().unwrap();
+        // (inputs, outputs, kernels)
+        for (num_inputs, num_outputs, num_kernels) in [(10, 10, 1), (100, 100, 10), (250, 400, 50)] {
+            let (blockchain, block) = runtime.block_on(build_test_block(num_inputs, num_outputs, num_kernels));
+            for bypass_range_proof_verification in [false, true] {
+                let validator =
+                    BlockBodyFullValidator::new(blockchain.rules().clone(), bypass_range_proof_verification);
+                let name = format!(
+                    "Block validation ({} inputs, {} outputs, {} kernels, bypass range proofs: {})",
+                    num_inputs, num_outputs, num_kernels, bypass_range_proof_verification
+                );
+                c.bench_function(&name, |b| {
+                    b.iter(|| run_validation_bench(&blockchain, &block, &validator, 1).unwrap());
+                });
+            }
+        }
+    }
+
+    /// The commitment sum is a single pass over the inputs and outputs. This measures it on its own, to compare with
+    /// the cost of validating the whole block.
+    pub fn commitment_sum_perf_test(c: &mut Criterion) {
+        let runtime = Runtime::new().unwrap();
+        for (num_inputs, num_outputs, num_kernels) in [(10, 10, 1), (100, 100, 10), (250, 400, 50)] {
+            let (_, block) = runtime.block_on(build_test_block(num_inputs, num_outputs, num_kernels));
+            let name = format!("Commitment sum ({} inputs, {} outputs)", num_inputs, num_outputs);
+            c.bench_function(&name, |b| {
+                b.iter(|| {
+                    let sum_inputs = block
+                        .body
+                        .inputs()
+                        .iter()
+                        .map(|i| i.commitment().unwrap())
+                        .sum::<Commitment>();
+                    let sum_outputs = block.body.outputs().iter().map(|o| &o.commitment).sum::<Commitment>();
+                    &sum_outputs - &sum_inputs
+                });
+            });
+        }
+    }
+
+    /// Compares verifying the metadata signatures of a block one output at a time with verifying them in a batch
+    pub fn metadata_signature_perf_test(c: &mut Criterion) {
+        let runtime = Runtime::new().unwrap();
+        for num_outputs in [10, 100, 400] {
+            let (_, block) = runtime.block_on(build_test_block(num_outputs / 10, num_outputs, num_outputs / 10));
+            let outputs = block.body.outputs().iter().collect::<Vec<_>>();
+            c.bench_function(
+                &format!("Metadata signatures one at a time ({} outputs)", outputs.len()),
+                |b| {
+                    b.iter(|| {
+                        for output in &outputs {
+                            output.verify_metadata_signature().unwrap();
+                        }
+                    });
+                },
+            );
+            c.bench_function(
+                &format!("Metadata signatures in a batch ({} outputs)", outputs.len()),
+                |b| {
+                    b.iter(|| batch_verify_metadata_signatures(&outputs).unwrap());
+                },
+            );
+        }
+    }
+
+    criterion_group!(
+        name = block_validation_perf;
+        config = Criterion::default().sample_size(10);
+        targets = block_validation_perf_test, commitment_sum_perf_test, metadata_signature_perf_test
+    );
+
+    pub fn main() {
+        block_validation_perf();
+        criterion::Criterion::default().configure_from_args().final_summary();
+    }
+}
+
+fn main() {
+    benches::main();
+}
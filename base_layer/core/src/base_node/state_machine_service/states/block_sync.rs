@@ -57,7 +57,9 @@ impl BlockSync {
             shared.connectivity.clone(),
             &mut self.sync_peers,
             shared.sync_validators.block_body.clone(),
-        );
+        )
+        .with_validation_runtime(shared.sync_validators.validation_runtime.clone())
+        .with_validation_task_gauge(shared.sync_validators.validation_tasks.clone());
 
         let status_event_sender = shared.status_event_sender.clone();
         let bootstrapped = shared.is_bootstrapped();
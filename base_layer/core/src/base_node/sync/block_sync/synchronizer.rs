@@ -22,6 +22,8 @@
 
 use std::{
     convert::{TryFrom, TryInto},
+    fs,
+    path::Path,
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -30,12 +32,12 @@ use futures::StreamExt;
 use log::*;
 use tari_comms::{connectivity::ConnectivityRequester, peer_manager::NodeId, protocol::rpc::RpcClient, PeerConnection};
 use tari_utilities::hex::Hex;
-use tokio::task;
+use tokio::{runtime::Handle, sync::mpsc};
 
 use super::error::BlockSyncError;
 use crate::{
     base_node::{
-        sync::{ban::PeerBanManager, hooks::Hooks, rpc, SyncPeer},
+        sync::{ban::PeerBanManager, hooks::Hooks, rpc, spawn_validation_worker, SyncPeer, ValidationTaskGauge},
         BlockchainSyncConfig,
     },
     blocks::{Block, ChainBlock},
@@ -43,7 +45,7 @@ use crate::{
     common::{rolling_avg::RollingAverageTime, BanPeriod},
     proto::base_node::SyncBlocksRequest,
     transactions::aggregated_body::AggregateBody,
-    validation::{BlockBodyValidator, ValidationError},
+    validation::{header::ValidatedHeader, BlockBodyValidator, ValidationError},
 };
 
 const LOG_TARGET: &str = "c::bn::block_sync";
@@ -58,6 +60,8 @@ pub struct BlockSynchronizer<'a, B> {
     block_validator: Arc<dyn BlockBodyValidator<B>>,
     hooks: Hooks,
     peer_ban_manager: PeerBanManager,
+    validation_runtime: Option<Handle>,
+    validation_tasks: ValidationTaskGauge,
 }
 
 impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
@@ -77,9 +81,23 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
             block_validator,
             hooks: Default::default(),
             peer_ban_manager,
+            validation_runtime: None,
+            validation_tasks: ValidationTaskGauge::new(),
         }
     }
 
+    /// Validate block bodies on the blocking pool of the given runtime instead of the ambient one
+    pub fn with_validation_runtime(mut self, validation_runtime: Option<Handle>) -> Self {
+        self.validation_runtime = validation_runtime;
+        self
+    }
+
+    /// Count the block body validation tasks on the given gauge while they run
+    pub fn with_validation_task_gauge(mut self, validation_tasks: ValidationTaskGauge) -> Self {
+        self.validation_tasks = validation_tasks;
+        self
+    }
+
     pub fn on_starting<H>(&mut self, hook: H)
     where for<'r> H: FnOnce(&SyncPeer) + Send + Sync + 'static {
         self.hooks.add_on_starting_hook(hook);
@@ -263,6 +281,18 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
         };
 
         let mut block_stream = client.sync_blocks(request).await?;
+        // A body may spend the outputs of the block before it, so the next body is only sent to the worker once the
+        // previous block was stored. The worker stops when the sender is dropped at the end of the sync.
+        let (body_tx, body_rx) = mpsc::channel(1);
+        let (result_tx, mut result_rx) = mpsc::channel(1);
+        spawn_validation_worker(
+            self.db.inner().clone(),
+            self.block_validator.clone(),
+            self.validation_runtime.clone(),
+            self.validation_tasks.clone(),
+            body_rx,
+            result_tx,
+        );
         let mut prev_hash = best_full_block_hash;
         let mut current_block = None;
         let mut last_sync_timer = Instant::now();
@@ -285,6 +315,8 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
 
             let current_height = header.height();
             let header_hash = *header.hash();
+            // Competing blocks can share a height during a reorg, so log the hash alongside it
+            let block_id = format!("#{} ({})", current_height, header_hash.to_hex());
             let timestamp = header.timestamp();
 
             if header.header().prev_hash != prev_hash {
@@ -304,34 +336,38 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
 
             debug!(
                 target: LOG_TARGET,
-                "Validating block body #{} (PoW = {}, {}, latency: {:.2?})",
-                current_height,
+                "Validating block body {} (PoW = {}, {}, latency: {:.2?})",
+                block_id,
                 header.header().pow_algo(),
                 body.to_counts_string(),
                 latency
             );
 
             let timer = Instant::now();
+            // The header was validated before it was added to the chain during header sync
+            let validated_header = ValidatedHeader::from_chain_header(header.clone());
             let (header, header_accum_data) = header.into_parts();
             let block = Block::new(header, body);
 
-            // Validate the block inside a tokio task
-            let task_block = block.clone();
-            let db = self.db.inner().clone();
-            let validator = self.block_validator.clone();
-            let res = task::spawn_blocking(move || {
-                let txn = db.db_read_access()?;
-                let smt = db.smt().clone();
-                validator.validate_body(&*txn, &task_block, smt)
-            })
-            .await?;
+            let worker_stopped = || ValidationError::Internal("the validation worker stopped".to_string());
+            body_tx
+                .send((validated_header, block.body.clone()))
+                .await
+                .map_err(|_| worker_stopped())?;
+            let res = result_rx.recv().await.ok_or_else(worker_stopped)?;
 
             let block = match res {
                 Ok(block) => block,
-                Err(err @ ValidationError::BadBlockFound { .. }) | Err(err @ ValidationError::FatalStorageError(_)) => {
+                // An internal error, e.g. a failed validation task, says nothing about the block
+                Err(err @ ValidationError::BadBlockFound { .. }) |
+                Err(err @ ValidationError::FatalStorageError(_)) |
+                Err(err @ ValidationError::Internal(_)) => {
                     return Err(err.into());
                 },
                 Err(err) => {
+                    if let Some(dir) = &self.config.rejected_block_dump_dir {
+                        dump_rejected_block(dir, &block, &err);
+                    }
                     // Add to bad blocks
                     if let Err(err) = self
                         .db
@@ -353,9 +389,9 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
 
             debug!(
                 target: LOG_TARGET,
-                "Validated in {:.0?}. Storing block body #{} (PoW = {}, {})",
+                "Validated in {:.0?}. Storing block body {} (PoW = {}, {})",
                 timer.elapsed(),
-                block.header().height,
+                block_id,
                 block.header().pow_algo(),
                 block.block().body.to_counts_string(),
             );
@@ -391,8 +427,8 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
 
             debug!(
                 target: LOG_TARGET,
-                "Block body #{} added in {:.0?}, Tot_acc_diff {}, Monero {}, SHA3 {}, latency: {:.2?}",
-                block.height(),
+                "Block body {} added in {:.0?}, Tot_acc_diff {}, Monero {}, SHA3 {}, latency: {:.2?}",
+                block_id,
                 timer.elapsed(),
                 block
                     .accumulated_data()
@@ -445,3 +481,30 @@ impl<'a, B: BlockchainBackend + 'static> BlockSynchronizer<'a, B> {
         self.sync_peers.iter().position(|p| p.node_id() == node_id)
     }
 }
+
+/// Writes a block that failed validation to `dir` so that it can be re-validated offline. Failing to write the block
+/// is logged but does not affect the sync.
+fn dump_rejected_block(dir: &Path, block: &Block, err: &ValidationError) {
+    let path = dir.join(format!("{}_{}.bin", block.header.height, block.hash().to_hex()));
+    let result = fs::create_dir_all(dir)
+        .and_then(|_| borsh::to_vec(block))
+        .and_then(|bytes| fs::write(&path, bytes));
+    match result {
+        Ok(_) => warn!(
+            target: LOG_TARGET,
+            "Block #{} ({}) failed validation ({}) and was written to {}",
+            block.header.height,
+            block.hash().to_hex(),
+            err,
+            path.display()
+        ),
+        Err(e) => warn!(
+            target: LOG_TARGET,
+            "Could not write rejected block #{} ({}) to {}: {}",
+            block.header.height,
+            block.hash().to_hex(),
+            path.display(),
+            e
+        ),
+    }
+}
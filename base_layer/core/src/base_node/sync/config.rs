@@ -20,7 +20,7 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::time::Duration;
+use std::{path::PathBuf, time::Duration};
 
 use serde::{Deserialize, Serialize};
 use tari_common::configuration::serializers;
@@ -52,6 +52,9 @@ pub struct BlockchainSyncConfig {
     /// sync.
     #[serde(with = "serializers::seconds")]
     pub rpc_deadline: Duration,
+    /// If set, blocks that fail body validation during sync are written to this directory (borsh encoded) so that
+    /// they can be analysed offline
+    pub rejected_block_dump_dir: Option<PathBuf>,
 }
 
 impl Default for BlockchainSyncConfig {
@@ -64,6 +67,7 @@ impl Default for BlockchainSyncConfig {
             forced_sync_peers: Default::default(),
             validation_concurrency: 6,
             rpc_deadline: Duration::from_secs(240), // Syncing many full blocks over tor require this
+            rejected_block_dump_dir: None,
         }
     }
 }
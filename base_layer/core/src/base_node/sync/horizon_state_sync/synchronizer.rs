@@ -60,7 +60,6 @@ use crate::{
     },
     validation::{helpers, FinalHorizonStateValidation},
     OutputSmt,
-    PrunedKernelMmr,
 };
 
 const LOG_TARGET: &str = "c::bn::state_machine_service::states::horizon_state_sync";
@@ -478,7 +477,7 @@ impl<'a, B: BlockchainBackend + 'static> HorizonStateSynchronization<'a, B> {
                     .fetch_block_accumulated_data(current_header.header().prev_hash)
                     .await?;
                 let kernel_pruned_set = block_data.dissolve();
-                let mut kernel_mmr = PrunedKernelMmr::new(kernel_pruned_set);
+                let mut kernel_mmr = self.rules.mmr_hashing().kernel_mmr(kernel_pruned_set);
 
                 for hash in kernel_hashes.drain(..) {
                     kernel_mmr.push(hash.to_vec())?;
@@ -58,3 +58,13 @@ pub use sync_peer::SyncPeer;
 mod validators;
 #[cfg(feature = "base_node")]
 pub use validators::SyncValidators;
+
+#[cfg(feature = "base_node")]
+mod validation_task_gauge;
+#[cfg(feature = "base_node")]
+pub use validation_task_gauge::{ValidationTaskGauge, ValidationTaskGuard};
+
+#[cfg(feature = "base_node")]
+mod validation_worker;
+#[cfg(feature = "base_node")]
+pub use validation_worker::spawn_validation_worker;
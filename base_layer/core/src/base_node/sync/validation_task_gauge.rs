@@ -0,0 +1,97 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+/// Counts the blocking tasks that are currently validating block bodies, so that validation can be correlated with
+/// the pressure on the blocking pool. Clones share the same count.
+#[derive(Debug, Clone, Default)]
+pub struct ValidationTaskGauge {
+    active: Arc<AtomicUsize>,
+}
+
+impl ValidationTaskGauge {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The number of validation tasks that are currently running
+    pub fn active(&self) -> usize {
+        self.active.load(Ordering::Relaxed)
+    }
+
+    /// Counts a running task until the returned guard is dropped. The guard is also dropped when the task panics, so
+    /// the count does not leak.
+    pub fn start(&self) -> ValidationTaskGuard {
+        self.active.fetch_add(1, Ordering::Relaxed);
+        ValidationTaskGuard {
+            active: self.active.clone(),
+        }
+    }
+}
+
+/// Decrements its [ValidationTaskGauge] when dropped
+#[must_use = "the task is only counted while the guard is held"]
+#[derive(Debug)]
+pub struct ValidationTaskGuard {
+    active: Arc<AtomicUsize>,
+}
+
+impl Drop for ValidationTaskGuard {
+    fn drop(&mut self) {
+        self.active.fetch_sub(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::panic;
+
+    use super::*;
+
+    #[test]
+    fn it_counts_the_running_tasks() {
+        let gauge = ValidationTaskGauge::new();
+        let first = gauge.start();
+        let second = gauge.clone().start();
+        assert_eq!(gauge.active(), 2);
+        drop(first);
+        assert_eq!(gauge.active(), 1);
+        drop(second);
+        assert_eq!(gauge.active(), 0);
+    }
+
+    #[test]
+    fn it_stops_counting_a_task_that_panics() {
+        let gauge = ValidationTaskGauge::new();
+        let task_gauge = gauge.clone();
+        let result = panic::catch_unwind(move || {
+            let _task = task_gauge.start();
+            panic!("validation failed");
+        });
+        assert!(result.is_err());
+        assert_eq!(gauge.active(), 0);
+    }
+}
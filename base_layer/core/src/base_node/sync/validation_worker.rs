@@ -0,0 +1,139 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::sync::Arc;
+
+use log::*;
+use tokio::{
+    runtime::Handle,
+    sync::mpsc,
+    task::{self, JoinHandle},
+};
+
+use super::ValidationTaskGauge;
+use crate::{
+    blocks::Block,
+    chain_storage::{BlockchainBackend, BlockchainDatabase},
+    transactions::aggregated_body::AggregateBody,
+    validation::{header::ValidatedHeader, BlockBodyValidator, ValidationError},
+};
+
+const LOG_TARGET: &str = "c::bn::sync::validation_worker";
+
+/// Spawns a pipeline stage that validates the block bodies received on `rx` against their validated headers and sends
+/// each result on `tx`, in the order the bodies were received. Validation runs on the blocking pool of
+/// `validation_runtime`, or of the ambient runtime if `None`, and is counted on `validation_tasks` while it runs.
+///
+/// A body is only taken from `rx` once the result of the previous body was sent, so with bounded channels a slow
+/// receiver of the results holds back validation instead of letting results queue up. A body is validated against the
+/// chain as it is when the body is taken, so a sender whose bodies build on each other must only send the next body
+/// once the previous block was added to the chain. The worker stops once `rx` is closed and drained, or once the
+/// receiver of the results is dropped.
+pub fn spawn_validation_worker<B: BlockchainBackend + 'static>(
+    db: BlockchainDatabase<B>,
+    validator: Arc<dyn BlockBodyValidator<B>>,
+    validation_runtime: Option<Handle>,
+    validation_tasks: ValidationTaskGauge,
+    mut rx: mpsc::Receiver<(ValidatedHeader, AggregateBody)>,
+    tx: mpsc::Sender<Result<Block, ValidationError>>,
+) -> JoinHandle<()> {
+    task::spawn(async move {
+        while let Some((header, body)) = rx.recv().await {
+            let db = db.clone();
+            let validator = validator.clone();
+            let validation_tasks = validation_tasks.clone();
+            let validate = move || {
+                let _task = validation_tasks.start();
+                let txn = db.db_read_access()?;
+                validator.validate_body(&*txn, header, body, db.smt())
+            };
+            let result = match &validation_runtime {
+                Some(runtime) => runtime.spawn_blocking(validate),
+                None => task::spawn_blocking(validate),
+            }
+            .await
+            .unwrap_or_else(|e| {
+                error!(target: LOG_TARGET, "Block validation task failed: {}", e);
+                Err(ValidationError::Internal(format!(
+                    "block validation task failed: {}",
+                    e
+                )))
+            });
+            if tx.send(result).await.is_err() {
+                debug!(target: LOG_TARGET, "Validation results are no longer received, stopping");
+                break;
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{
+        blocks::BlockHeader,
+        test_helpers::blockchain::create_new_blockchain,
+        validation::mocks::MockValidator,
+    };
+
+    fn block_at(height: u64) -> (ValidatedHeader, AggregateBody) {
+        let mut header = BlockHeader::new(0);
+        header.height = height;
+        (ValidatedHeader::new_unchecked(header), AggregateBody::empty())
+    }
+
+    #[tokio::test]
+    async fn it_sends_the_results_in_the_order_the_blocks_were_received() {
+        let validator = MockValidator::new(true);
+        let is_valid = validator.shared_flag();
+        let (block_tx, block_rx) = mpsc::channel(4);
+        let (result_tx, mut result_rx) = mpsc::channel(1);
+        let validation_tasks = ValidationTaskGauge::new();
+        let worker = spawn_validation_worker(
+            create_new_blockchain(),
+            Arc::new(validator),
+            Some(Handle::current()),
+            validation_tasks.clone(),
+            block_rx,
+            result_tx,
+        );
+
+        for height in 1..=3 {
+            block_tx.send(block_at(height)).await.unwrap();
+        }
+        for height in 1..=3 {
+            let block = result_rx.recv().await.unwrap().unwrap();
+            assert_eq!(block.header.height, height);
+        }
+
+        is_valid.set(false);
+        block_tx.send(block_at(4)).await.unwrap();
+        let err = result_rx.recv().await.unwrap().unwrap_err();
+        assert!(matches!(err, ValidationError::ConsensusError(_)));
+
+        // The worker stops once no more blocks can be received
+        drop(block_tx);
+        worker.await.unwrap();
+        assert!(result_rx.recv().await.is_none());
+        assert_eq!(validation_tasks.active(), 0);
+    }
+}
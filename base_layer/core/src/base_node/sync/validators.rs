@@ -22,6 +22,9 @@
 
 use std::{fmt, sync::Arc};
 
+use tokio::runtime::Handle;
+
+use super::ValidationTaskGauge;
 use crate::{
     chain_storage::BlockchainBackend,
     consensus::ConsensusManager,
@@ -38,6 +41,10 @@ use crate::{
 pub struct SyncValidators<B> {
     pub block_body: Arc<dyn BlockBodyValidator<B>>,
     pub final_horizon_state: Arc<dyn FinalHorizonStateValidation<B>>,
+    /// The runtime whose blocking pool runs block body validation during sync. If `None`, the ambient runtime is used.
+    pub validation_runtime: Option<Handle>,
+    /// Counts the block body validation tasks that are running on the blocking pool during sync
+    pub validation_tasks: ValidationTaskGauge,
 }
 
 impl<B: BlockchainBackend + 'static> SyncValidators<B> {
@@ -49,16 +56,25 @@ impl<B: BlockchainBackend + 'static> SyncValidators<B> {
         Self {
             block_body: Arc::new(block_body),
             final_horizon_state: Arc::new(final_state),
+            validation_runtime: None,
+            validation_tasks: ValidationTaskGauge::new(),
         }
     }
 
+    /// Run block body validation on the blocking pool of the given runtime, e.g. a dedicated runtime so that
+    /// validation does not compete with the rest of the node's blocking work
+    pub fn with_validation_runtime(mut self, validation_runtime: Handle) -> Self {
+        self.validation_runtime = Some(validation_runtime);
+        self
+    }
+
     pub fn full_consensus(
         rules: ConsensusManager,
         factories: CryptoFactories,
         bypass_range_proof_verification: bool,
     ) -> Self {
         Self::new(
-            BlockBodyFullValidator::new(rules.clone(), bypass_range_proof_verification),
+            BlockBodyFullValidator::with_factories(rules.clone(), bypass_range_proof_verification, factories.clone()),
             ChainBalanceValidator::<B>::new(rules, factories),
         )
     }
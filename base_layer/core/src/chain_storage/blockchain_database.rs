@@ -41,10 +41,7 @@ use tari_common_types::{
     types::{BlockHash, Commitment, FixedHash, HashOutput, PublicKey, Signature},
 };
 use tari_hashing::TransactionHashDomain;
-use tari_mmr::{
-    pruned_hashset::PrunedHashSet,
-    sparse_merkle_tree::{DeleteResult, NodeKey, ValueHash},
-};
+use tari_mmr::sparse_merkle_tree::{DeleteResult, NodeKey, ValueHash};
 use tari_utilities::{epoch_time::EpochTime, hex::Hex, ByteArray};
 
 use super::TemplateRegistrationEntry;
@@ -100,8 +97,7 @@ use crate::{
         ValidationError,
     },
     OutputSmt,
-    PrunedInputMmr,
-    PrunedKernelMmr,
+    OutputSmtInclusionProof,
     ValidatorNodeBMT,
 };
 
@@ -897,13 +893,7 @@ where B: BlockchainBackend
                 return Err(e);
             },
         };
-        block.header.kernel_mr = roots.kernel_mr;
-        block.header.kernel_mmr_size = roots.kernel_mmr_size;
-        block.header.input_mr = roots.input_mr;
-        block.header.output_mr = roots.output_mr;
-        block.header.output_smt_size = roots.output_smt_size;
-        block.header.validator_node_mr = roots.validator_node_mr;
-        block.header.validator_node_size = roots.validator_node_size;
+        roots.apply_to_header(&mut block.header);
         Ok(block)
     }
 
@@ -964,6 +954,17 @@ where B: BlockchainBackend
     ///
     /// If an error does occur while writing the new block parts, all changes are reverted before returning.
     pub fn add_block(&self, candidate_block: Arc<Block>) -> Result<BlockAddResult, ChainStorageError> {
+        self.add_block_with_validator(candidate_block, &*self.validators.block)
+    }
+
+    /// Adds a block in the same way as [add_block](Self::add_block), but validates the block body with the given
+    /// validator instead of the configured one. The body is validated while the write lock is held, so the UTXO set
+    /// it was validated against is the one it is committed to.
+    pub fn add_block_with_validator(
+        &self,
+        candidate_block: Arc<Block>,
+        block_validator: &dyn CandidateBlockValidator<B>,
+    ) -> Result<BlockAddResult, ChainStorageError> {
         let timer = Instant::now();
 
         let block_hash = candidate_block.hash();
@@ -1026,7 +1027,7 @@ where B: BlockchainBackend
             &mut *db,
             &self.config,
             &self.consensus_manager,
-            &*self.validators.block,
+            block_validator,
             &*self.validators.header,
             self.consensus_manager.chain_strength_comparer(),
             candidate_block,
@@ -1315,6 +1316,20 @@ pub struct MmrRoots {
     pub validator_node_size: u64,
 }
 
+impl MmrRoots {
+    /// Sets the roots and sizes of the header to these, so that the header matches what validation calculates for
+    /// the block
+    pub fn apply_to_header(&self, header: &mut BlockHeader) {
+        header.kernel_mr = self.kernel_mr;
+        header.kernel_mmr_size = self.kernel_mmr_size;
+        header.input_mr = self.input_mr;
+        header.output_mr = self.output_mr;
+        header.output_smt_size = self.output_smt_size;
+        header.validator_node_mr = self.validator_node_mr;
+        header.validator_node_size = self.validator_node_size;
+    }
+}
+
 impl std::fmt::Display for MmrRoots {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "MMR Roots")?;
@@ -1340,16 +1355,7 @@ pub fn calculate_mmr_roots<T: BlockchainBackend>(
     let header = &block.header;
     let body = &block.body;
 
-    let metadata = db.fetch_chain_metadata()?;
-    if header.prev_hash != *metadata.best_block_hash() {
-        return Err(ChainStorageError::CannotCalculateNonTipMmr(format!(
-            "Block (#{}) is not building on tip, previous hash is {} but the current tip is #{} {}",
-            header.height,
-            header.prev_hash,
-            metadata.best_block_height(),
-            metadata.best_block_hash(),
-        )));
-    }
+    check_builds_on_tip(db, header)?;
 
     let BlockAccumulatedData { kernels, .. } =
         db.fetch_block_accumulated_data(&header.prev_hash)?
@@ -1359,47 +1365,19 @@ pub fn calculate_mmr_roots<T: BlockchainBackend>(
                 value: header.prev_hash.to_hex(),
             })?;
 
-    let mut kernel_mmr = PrunedKernelMmr::new(kernels);
-    let mut input_mmr = PrunedInputMmr::new(PrunedHashSet::default());
+    let mut kernel_mmr = rules.mmr_hashing().kernel_mmr(kernels);
+    let mut input_mmr = rules.mmr_hashing().input_mmr();
 
     for kernel in body.kernels() {
         kernel_mmr.push(kernel.hash().to_vec())?;
     }
 
-    let mut outputs_to_remove = Vec::new();
-    for output in body.outputs() {
-        if !output.is_burned() {
-            let smt_key = NodeKey::try_from(output.commitment.as_bytes())?;
-            let smt_node = ValueHash::try_from(output.smt_hash(header.height).as_slice())?;
-            outputs_to_remove.push(smt_key.clone());
-            if let Err(e) = output_smt.insert(smt_key, smt_node) {
-                error!(
-                    target: LOG_TARGET,
-                    "Output commitment({}) already in SMT",
-                    output.commitment.to_hex(),
-                );
-                return Err(e.into());
-            }
-        }
-    }
-
-    let mut outputs_to_add = Vec::new();
     for input in body.inputs() {
         input_mmr.push(input.canonical_hash().to_vec())?;
-        let smt_key = NodeKey::try_from(input.commitment()?.as_bytes())?;
-        match output_smt.delete(&smt_key)? {
-            DeleteResult::Deleted(value_hash) => outputs_to_add.push((smt_key, value_hash)),
-            DeleteResult::KeyNotFound => {
-                error!(
-                    target: LOG_TARGET,
-                    "Could not find input({}) in SMT",
-                    input.commitment()?.to_hex(),
-                );
-                return Err(ChainStorageError::UnspendableInput);
-            },
-        };
     }
 
+    let changes = apply_block_to_output_smt(block, output_smt)?;
+
     let block_height = block.header.height;
     let epoch_len = rules.consensus_constants(block_height).epoch_length();
     let (validator_node_mr, validator_node_size) = if block_height % epoch_len == 0 {
@@ -1426,7 +1404,113 @@ pub fn calculate_mmr_roots<T: BlockchainBackend>(
     };
     // We have made changes to the SMT that we dont want, sp lets rewind the SMT back to tip again as we want to have
     // the SMT at tip.
-    for output in outputs_to_add {
+    rewind_output_smt(output_smt, changes)?;
+    Ok(mmr_roots)
+}
+
+/// Builds the proof that the output at `output_index` of the block is included in the output SMT once the block is
+/// added to the tip. The proof verifies against the `output_mr` of the block header with the output commitment as the
+/// key and the output's SMT hash at the block height as the value. Burned outputs are not added to the SMT, so they
+/// have no inclusion proof.
+pub fn calculate_output_inclusion_proof<T: BlockchainBackend>(
+    db: &T,
+    block: &Block,
+    output_index: usize,
+    // we dont want to clone the SMT, so we rather change it and change it back after we are done.
+    output_smt: &mut OutputSmt,
+) -> Result<OutputSmtInclusionProof, ChainStorageError> {
+    let output = block
+        .body
+        .outputs()
+        .get(output_index)
+        .ok_or_else(|| ChainStorageError::InvalidArguments {
+            func: "calculate_output_inclusion_proof",
+            arg: "output_index",
+            message: format!(
+                "Block has {} outputs, index {} is out of range",
+                block.body.outputs().len(),
+                output_index
+            ),
+        })?;
+    if output.is_burned() {
+        return Err(ChainStorageError::InvalidArguments {
+            func: "calculate_output_inclusion_proof",
+            arg: "output_index",
+            message: format!("Output {} is burned and not in the output SMT", output_index),
+        });
+    }
+    let smt_key = NodeKey::try_from(output.commitment.as_bytes())?;
+    let smt_node = ValueHash::try_from(output.smt_hash(block.header.height).as_slice())?;
+
+    check_builds_on_tip(db, &block.header)?;
+    let changes = apply_block_to_output_smt(block, output_smt)?;
+    let proof = OutputSmtInclusionProof::from_tree(output_smt, &smt_key, &smt_node);
+    // Rewind the SMT to the tip whether or not the proof could be built
+    rewind_output_smt(output_smt, changes)?;
+    Ok(proof?)
+}
+
+fn check_builds_on_tip<T: BlockchainBackend>(db: &T, header: &BlockHeader) -> Result<(), ChainStorageError> {
+    let metadata = db.fetch_chain_metadata()?;
+    if header.prev_hash != *metadata.best_block_hash() {
+        return Err(ChainStorageError::CannotCalculateNonTipMmr(format!(
+            "Block (#{}) is not building on tip, previous hash is {} but the current tip is #{} {}",
+            header.height,
+            header.prev_hash,
+            metadata.best_block_height(),
+            metadata.best_block_hash(),
+        )));
+    }
+    Ok(())
+}
+
+/// The changes made to the output SMT by a block, so that they can be undone with [rewind_output_smt]
+struct OutputSmtChanges {
+    outputs_to_remove: Vec<NodeKey>,
+    outputs_to_add: Vec<(NodeKey, ValueHash)>,
+}
+
+fn apply_block_to_output_smt(block: &Block, output_smt: &mut OutputSmt) -> Result<OutputSmtChanges, ChainStorageError> {
+    let mut outputs_to_remove = Vec::new();
+    for output in block.body.outputs() {
+        if !output.is_burned() {
+            let smt_key = NodeKey::try_from(output.commitment.as_bytes())?;
+            let smt_node = ValueHash::try_from(output.smt_hash(block.header.height).as_slice())?;
+            outputs_to_remove.push(smt_key.clone());
+            if let Err(e) = output_smt.insert(smt_key, smt_node) {
+                error!(
+                    target: LOG_TARGET,
+                    "Output commitment({}) already in SMT",
+                    output.commitment.to_hex(),
+                );
+                return Err(e.into());
+            }
+        }
+    }
+
+    let mut outputs_to_add = Vec::new();
+    for input in block.body.inputs() {
+        let smt_key = NodeKey::try_from(input.commitment()?.as_bytes())?;
+        match output_smt.delete(&smt_key)? {
+            DeleteResult::Deleted(value_hash) => outputs_to_add.push((smt_key, value_hash)),
+            DeleteResult::KeyNotFound => {
+                error!(
+                    target: LOG_TARGET,
+                    "Could not find input({}) in SMT",
+                    input.commitment()?.to_hex(),
+                );
+                return Err(ChainStorageError::UnspendableInput);
+            },
+        };
+    }
+    Ok(OutputSmtChanges {
+        outputs_to_remove,
+        outputs_to_add,
+    })
+}
+
+fn rewind_output_smt(output_smt: &mut OutputSmt, changes: OutputSmtChanges) -> Result<(), ChainStorageError> {
+    for output in changes.outputs_to_add {
         if output_smt.insert(output.0.clone(), output.1).is_err() {
             error!(
                 target: LOG_TARGET,
@@ -1439,7 +1523,7 @@ pub fn calculate_mmr_roots<T: BlockchainBackend>(
             )));
         }
     }
-    for output in outputs_to_remove {
+    for output in changes.outputs_to_remove {
         match output_smt.delete(&output)? {
             DeleteResult::Deleted(_value_hash) => {},
             DeleteResult::KeyNotFound => {
@@ -1455,7 +1539,7 @@ pub fn calculate_mmr_roots<T: BlockchainBackend>(
             },
         };
     }
-    Ok(mmr_roots)
+    Ok(())
 }
 
 pub fn calculate_validator_node_mr(validator_nodes: &[(PublicKey, [u8; 32])]) -> tari_mmr::Hash {
@@ -1990,30 +2074,44 @@ fn reorganize_chain<T: BlockchainBackend>(
         let block_hash = *block.hash();
         txn.delete_orphan(block_hash);
         let chain_metadata = backend.fetch_chain_metadata()?;
-        if let Err(e) = block_validator.validate_body_with_metadata(backend, block, &chain_metadata, smt.clone()) {
-            warn!(
-                target: LOG_TARGET,
-                "Orphan block {} ({}) failed validation during chain reorg: {:?}",
-                block.header().height,
-                block_hash,
-                e
-            );
-            if e.get_ban_reason().is_some() && e.get_ban_reason().unwrap().ban_duration != BanPeriod::Short {
-                txn.insert_bad_block(block.header().hash(), block.header().height, e.to_string());
-            }
-            // We removed a block from the orphan chain, so the chain is now "broken", so we remove the rest of the
-            // remaining blocks as well.
-            for block in new_chain_from_fork.iter().skip(i + 1) {
-                txn.delete_orphan(*block.hash());
-            }
-            backend.write(txn)?;
+        let validated_block =
+            match block_validator.validate_body_with_metadata(backend, block, &chain_metadata, smt.clone()) {
+                Ok(validated_block) => validated_block,
+                Err(e) => {
+                    warn!(
+                        target: LOG_TARGET,
+                        "Orphan block {} ({}) failed validation during chain reorg: {:?}",
+                        block.header().height,
+                        block_hash,
+                        e
+                    );
+                    if e.get_ban_reason().is_some() && e.get_ban_reason().unwrap().ban_duration != BanPeriod::Short {
+                        txn.insert_bad_block(block.header().hash(), block.header().height, e.to_string());
+                    }
+                    // We removed a block from the orphan chain, so the chain is now "broken", so we remove the rest of
+                    // the remaining blocks as well.
+                    for block in new_chain_from_fork.iter().skip(i + 1) {
+                        txn.delete_orphan(*block.hash());
+                    }
+                    backend.write(txn)?;
 
-            info!(target: LOG_TARGET, "Restoring previous chain after failed reorg.");
-            restore_reorged_chain(backend, fork_hash, removed_blocks, consensus, smt.clone())?;
-            return Err(e.into());
-        }
+                    info!(target: LOG_TARGET, "Restoring previous chain after failed reorg.");
+                    restore_reorged_chain(backend, fork_hash, removed_blocks, consensus, smt.clone())?;
+                    return Err(e.into());
+                },
+            };
+        // Commit the block as the validator returned it, so that the body committed is the body that was validated
+        let validated_block = ChainBlock::try_construct(Arc::new(validated_block), block.accumulated_data().clone())
+            .ok_or_else(|| ChainStorageError::DataInconsistencyDetected {
+                function: "reorganize_chain",
+                details: format!(
+                    "The validator returned a block with a different hash for block {} ({})",
+                    block_hash,
+                    block.height()
+                ),
+            })?;
 
-        if let Err(e) = insert_best_block(&mut txn, block.clone(), consensus, smt.clone()) {
+        if let Err(e) = insert_best_block(&mut txn, Arc::new(validated_block), consensus, smt.clone()) {
             let mut write_smt = smt.write().map_err(|e| {
                 error!(
                     target: LOG_TARGET,
@@ -116,7 +116,6 @@ use crate::{
         },
     },
     OutputSmt,
-    PrunedKernelMmr,
 };
 
 type DatabaseRef = Arc<Database<'static>>;
@@ -1231,7 +1230,7 @@ impl LMDBDatabase {
             ..
         } = data;
 
-        let mut kernel_mmr = PrunedKernelMmr::new(pruned_kernel_set);
+        let mut kernel_mmr = self.consensus_manager.mmr_hashing().kernel_mmr(pruned_kernel_set);
 
         for kernel in kernels {
             total_kernel_sum = &total_kernel_sum + &kernel.excess;
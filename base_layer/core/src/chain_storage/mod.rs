@@ -40,6 +40,7 @@ use serde::{Deserialize, Serialize};
 mod blockchain_database;
 pub use blockchain_database::{
     calculate_mmr_roots,
+    calculate_output_inclusion_proof,
     calculate_validator_node_mr,
     fetch_header,
     fetch_headers,
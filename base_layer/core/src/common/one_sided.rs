@@ -21,7 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use blake2::Blake2b;
-use digest::consts::U64;
+use digest::consts::{U32, U64};
 use tari_common_types::types::{PrivateKey, PublicKey, WalletHasher};
 use tari_comms::types::CommsDHKE;
 use tari_crypto::{
@@ -44,8 +44,15 @@ hash_domain!(
     1
 );
 
+hash_domain!(
+    WalletOutputViewTagDomain,
+    "com.tari.base_layer.wallet.output_view_tag",
+    1
+);
+
 type WalletOutputEncryptionKeysDomainHasher = DomainSeparatedHasher<Blake2b<U64>, WalletOutputEncryptionKeysDomain>;
 type WalletOutputSpendingKeysDomainHasher = DomainSeparatedHasher<Blake2b<U64>, WalletOutputSpendingKeysDomain>;
+type WalletOutputViewTagDomainHasher = DomainSeparatedHasher<Blake2b<U32>, WalletOutputViewTagDomain>;
 
 /// Generate an output encryption key from a Diffie-Hellman shared secret
 pub fn shared_secret_to_output_encryption_key(shared_secret: &CommsDHKE) -> Result<PrivateKey, ByteArrayError> {
@@ -87,6 +94,16 @@ pub fn shared_secret_to_output_spending_key(shared_secret: &CommsDHKE) -> Result
     )
 }
 
+/// Generate the one byte view tag of an output from a Diffie-Hellman shared secret. The sender derives the secret from
+/// its key and the receiver's view public key, and the receiver, e.g. a ledger device, from its view key and the
+/// sender's public key, so a scanner can skip outputs whose tag does not match before trying to decrypt them.
+pub fn shared_secret_to_view_tag(shared_secret: &CommsDHKE) -> u8 {
+    WalletOutputViewTagDomainHasher::new_with_label("view_tag")
+        .chain(shared_secret.as_bytes())
+        .finalize()
+        .as_ref()[0]
+}
+
 /// Stealth address domain separated hasher using Diffie-Hellman shared secret
 pub fn diffie_hellman_stealth_domain_hasher(diffie_hellman: CommsDHKE) -> DomainSeparatedHash<Blake2b<U64>> {
     WalletHasher::new_with_label("stealth_address")
@@ -104,3 +121,24 @@ pub fn stealth_address_script_spending_key(
             .expect("'DomainSeparatedHash<Blake2b<U64>>' has correct size"),
     ) + spend_key
 }
+
+#[cfg(test)]
+mod test {
+    use rand::rngs::OsRng;
+
+    use super::*;
+
+    #[test]
+    fn it_derives_the_same_view_tag_on_both_sides() {
+        let (sender_secret, sender_public) = PublicKey::random_keypair(&mut OsRng);
+        let (view_secret, view_public) = PublicKey::random_keypair(&mut OsRng);
+
+        let sender_tag = shared_secret_to_view_tag(&CommsDHKE::new(&sender_secret, &view_public));
+        let receiver_tag = shared_secret_to_view_tag(&CommsDHKE::new(&view_secret, &sender_public));
+        assert_eq!(sender_tag, receiver_tag);
+
+        // The ledger application hashes the shared secret as a public key
+        let device_secret = CommsDHKE::from_canonical_bytes((&view_secret * &sender_public).as_bytes()).unwrap();
+        assert_eq!(shared_secret_to_view_tag(&device_secret), sender_tag);
+    }
+}
@@ -123,6 +123,14 @@ pub struct ConsensusConstants {
     vn_registration_lock_height: u64,
     /// The period after which the VNs will be reshuffled.
     vn_registration_shuffle_interval: VnEpoch,
+    /// The minimum value of an output with a revealed value. Zero disables the check.
+    min_output_value: MicroMinotari,
+    /// The largest number of blocks the maturity of an output may lie beyond the height of its block
+    max_output_maturity_offset: u64,
+    /// The largest size of a serialized block in bytes
+    max_block_serialized_size: usize,
+    /// Whether outputs other than coinbase outputs are rejected if their sender offset public key is the identity
+    reject_identity_sender_offset_keys: bool,
 }
 
 #[derive(Debug, Clone)]
@@ -355,6 +363,29 @@ impl ConsensusConstants {
         self.vn_registration_lock_height
     }
 
+    /// The minimum value of an output with a revealed value. Outputs with hidden values cannot be checked against it.
+    pub fn min_output_value(&self) -> MicroMinotari {
+        self.min_output_value
+    }
+
+    /// The largest number of blocks the maturity of an output may lie beyond the height of its block. It must not be
+    /// below the coinbase maturity, or coinbase outputs are rejected.
+    pub fn max_output_maturity_offset(&self) -> u64 {
+        self.max_output_maturity_offset
+    }
+
+    /// The largest size of a serialized block in bytes. It bounds the resources spent on a block independently of its
+    /// weight.
+    pub fn max_block_serialized_size(&self) -> usize {
+        self.max_block_serialized_size
+    }
+
+    /// Whether outputs other than coinbase outputs are rejected if their sender offset public key is the identity. Such
+    /// a key adds nothing to the script offset. Disabled on every network until it is scheduled for activation.
+    pub fn reject_identity_sender_offset_keys(&self) -> bool {
+        self.reject_identity_sender_offset_keys
+    }
+
     /// Returns the current epoch from the given height
     pub fn block_height_to_epoch(&self, height: u64) -> VnEpoch {
         VnEpoch(height / self.vn_epoch_length)
@@ -415,6 +446,10 @@ impl ConsensusConstants {
             vn_registration_lock_height: 0,
             vn_registration_shuffle_interval: VnEpoch(100),
             coinbase_output_features_extra_max_length: 64,
+            min_output_value: MicroMinotari(0),
+            max_output_maturity_offset: u64::MAX,
+            max_block_serialized_size: usize::MAX,
+            reject_identity_sender_offset_keys: false,
         }];
         #[cfg(any(test, debug_assertions))]
         assert_hybrid_pow_constants(&consensus_constants, &[120], &[50], &[50]);
@@ -481,6 +516,10 @@ impl ConsensusConstants {
             vn_registration_lock_height: 0,
             vn_registration_shuffle_interval: VnEpoch(100),
             coinbase_output_features_extra_max_length: 64,
+            min_output_value: MicroMinotari(0),
+            max_output_maturity_offset: u64::MAX,
+            max_block_serialized_size: usize::MAX,
+            reject_identity_sender_offset_keys: false,
         }];
         #[cfg(any(test, debug_assertions))]
         assert_hybrid_pow_constants(&consensus_constants, &[target_time], &[randomx_split], &[sha3x_split]);
@@ -538,6 +577,10 @@ impl ConsensusConstants {
             vn_registration_lock_height: 0,
             vn_registration_shuffle_interval: VnEpoch(100),
             coinbase_output_features_extra_max_length: 64,
+            min_output_value: MicroMinotari(0),
+            max_output_maturity_offset: u64::MAX,
+            max_block_serialized_size: usize::MAX,
+            reject_identity_sender_offset_keys: false,
         }];
         #[cfg(any(test, debug_assertions))]
         assert_hybrid_pow_constants(&consensus_constants, &[120], &[50], &[50]);
@@ -595,6 +638,10 @@ impl ConsensusConstants {
             vn_registration_lock_height: 0,
             vn_registration_shuffle_interval: VnEpoch(100),
             coinbase_output_features_extra_max_length: 64,
+            min_output_value: MicroMinotari(0),
+            max_output_maturity_offset: u64::MAX,
+            max_block_serialized_size: usize::MAX,
+            reject_identity_sender_offset_keys: false,
         }];
         #[cfg(any(test, debug_assertions))]
         assert_hybrid_pow_constants(&consensus_constants, &[120], &[50], &[50]);
@@ -646,6 +693,10 @@ impl ConsensusConstants {
             vn_registration_lock_height: 0,
             vn_registration_shuffle_interval: VnEpoch(100),
             coinbase_output_features_extra_max_length: 64,
+            min_output_value: MicroMinotari(0),
+            max_output_maturity_offset: u64::MAX,
+            max_block_serialized_size: usize::MAX,
+            reject_identity_sender_offset_keys: false,
         }];
         #[cfg(any(test, debug_assertions))]
         assert_hybrid_pow_constants(&consensus_constants, &[120], &[50], &[50]);
@@ -699,6 +750,10 @@ impl ConsensusConstants {
             vn_registration_lock_height: 0,
             vn_registration_shuffle_interval: VnEpoch(100),
             coinbase_output_features_extra_max_length: 64,
+            min_output_value: MicroMinotari(0),
+            max_output_maturity_offset: u64::MAX,
+            max_block_serialized_size: usize::MAX,
+            reject_identity_sender_offset_keys: false,
         }];
         #[cfg(any(test, debug_assertions))]
         assert_hybrid_pow_constants(&consensus_constants, &[120], &[50], &[50]);
@@ -837,6 +892,11 @@ impl ConsensusConstantsBuilder {
         self
     }
 
+    pub fn with_max_covenant_length(mut self, length: u32) -> Self {
+        self.consensus.max_covenant_length = length;
+        self
+    }
+
     pub fn with_max_block_transaction_weight(mut self, weight: u64) -> Self {
         self.consensus.max_block_transaction_weight = weight;
         self
@@ -889,6 +949,26 @@ impl ConsensusConstantsBuilder {
         self
     }
 
+    pub fn with_min_output_value(mut self, value: MicroMinotari) -> Self {
+        self.consensus.min_output_value = value;
+        self
+    }
+
+    pub fn with_max_output_maturity_offset(mut self, offset: u64) -> Self {
+        self.consensus.max_output_maturity_offset = offset;
+        self
+    }
+
+    pub fn with_max_block_serialized_size(mut self, size: usize) -> Self {
+        self.consensus.max_block_serialized_size = size;
+        self
+    }
+
+    pub fn with_reject_identity_sender_offset_keys(mut self, reject: bool) -> Self {
+        self.consensus.reject_identity_sender_offset_keys = reject;
+        self
+    }
+
     pub fn build(self) -> ConsensusConstants {
         self.consensus
     }
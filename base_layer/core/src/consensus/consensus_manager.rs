@@ -28,7 +28,11 @@ use thiserror::Error;
 #[cfg(feature = "base_node")]
 use crate::{
     blocks::ChainBlock,
-    consensus::chain_strength_comparer::{strongest_chain, ChainStrengthComparer},
+    consensus::{
+        chain_strength_comparer::{strongest_chain, ChainStrengthComparer},
+        DefaultMmrHashing,
+        MmrHashing,
+    },
     proof_of_work::PowAlgorithm,
     proof_of_work::TargetDifficultyWindow,
 };
@@ -157,6 +161,12 @@ impl ConsensusManager {
         self.inner.chain_strength_comparer.as_ref()
     }
 
+    /// Returns the hashing of the kernel and input MMRs whose roots the block headers commit to
+    #[cfg(feature = "base_node")]
+    pub fn mmr_hashing(&self) -> &dyn MmrHashing {
+        self.inner.mmr_hashing.as_ref()
+    }
+
     /// This is the currently configured chain network.
     pub fn network(&self) -> NetworkConsensus {
         self.inner.network
@@ -178,6 +188,9 @@ struct ConsensusManagerInner {
     #[cfg(feature = "base_node")]
     /// The comparer used to determine which chain is stronger for reorgs.
     pub chain_strength_comparer: Box<dyn ChainStrengthComparer + Send + Sync>,
+    #[cfg(feature = "base_node")]
+    /// The hashing of the kernel and input MMRs
+    pub mmr_hashing: Box<dyn MmrHashing + Send + Sync>,
 }
 
 /// Constructor for the consensus manager struct
@@ -189,6 +202,8 @@ pub struct ConsensusManagerBuilder {
     gen_block: Option<ChainBlock>,
     #[cfg(feature = "base_node")]
     chain_strength_comparer: Option<Box<dyn ChainStrengthComparer + Send + Sync>>,
+    #[cfg(feature = "base_node")]
+    mmr_hashing: Option<Box<dyn MmrHashing + Send + Sync>>,
 }
 
 impl ConsensusManagerBuilder {
@@ -201,6 +216,8 @@ impl ConsensusManagerBuilder {
             gen_block: None,
             #[cfg(feature = "base_node")]
             chain_strength_comparer: None,
+            #[cfg(feature = "base_node")]
+            mmr_hashing: None,
         }
     }
 
@@ -223,6 +240,14 @@ impl ConsensusManagerBuilder {
         self
     }
 
+    /// Hashes the kernel and input MMRs with `mmr_hashing` instead of [DefaultMmrHashing]. Only meant for test
+    /// networks, since the genesis blocks of the built-in networks commit to MMRs hashed with the default.
+    #[cfg(feature = "base_node")]
+    pub fn with_mmr_hashing(mut self, mmr_hashing: Box<dyn MmrHashing + Send + Sync>) -> Self {
+        self.mmr_hashing = Some(mmr_hashing);
+        self
+    }
+
     /// Builds a consensus manager
     pub fn build(mut self) -> Result<ConsensusManager, ConsensusBuilderError> {
         // should not be allowed to set the gen block and have the network type anything else than LocalNet
@@ -262,6 +287,8 @@ impl ConsensusManagerBuilder {
                     .by_sha3x_difficulty()
                     .build()
             }),
+            #[cfg(feature = "base_node")]
+            mmr_hashing: self.mmr_hashing.unwrap_or_else(|| Box::new(DefaultMmrHashing::new())),
         };
         Ok(ConsensusManager { inner: Arc::new(inner) })
     }
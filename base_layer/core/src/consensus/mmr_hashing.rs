@@ -0,0 +1,93 @@
+// Copyright 2024 The Tari Project
+// SPDX-License-Identifier: BSD-3-Clause
+
+use std::{
+    fmt::{self, Debug},
+    marker::PhantomData,
+};
+
+use tari_common::DomainDigest;
+use tari_mmr::{error::MerkleMountainRangeError, pruned_hashset::PrunedHashSet, Hash, MerkleMountainRange};
+
+use crate::{InputMmrHasherBlake256, KernelMmrHasherBlake256};
+
+/// The kernel and input MMRs of a block, with only the peaks of the MMR they were started from
+pub trait PrunedMmr {
+    fn push(&mut self, hash: Hash) -> Result<usize, MerkleMountainRangeError>;
+    fn get_merkle_root(&self) -> Result<Hash, MerkleMountainRangeError>;
+    fn get_leaf_count(&self) -> Result<usize, MerkleMountainRangeError>;
+    fn get_pruned_hash_set(&self) -> Result<PrunedHashSet, MerkleMountainRangeError>;
+}
+
+impl<D: DomainDigest> PrunedMmr for MerkleMountainRange<D, PrunedHashSet> {
+    fn push(&mut self, hash: Hash) -> Result<usize, MerkleMountainRangeError> {
+        MerkleMountainRange::push(self, hash)
+    }
+
+    fn get_merkle_root(&self) -> Result<Hash, MerkleMountainRangeError> {
+        MerkleMountainRange::get_merkle_root(self)
+    }
+
+    fn get_leaf_count(&self) -> Result<usize, MerkleMountainRangeError> {
+        MerkleMountainRange::get_leaf_count(self)
+    }
+
+    fn get_pruned_hash_set(&self) -> Result<PrunedHashSet, MerkleMountainRangeError> {
+        MerkleMountainRange::get_pruned_hash_set(self)
+    }
+}
+
+/// Creates the kernel and input MMRs whose roots a block header commits to. Test networks that hash their MMRs in
+/// another domain can set their own with [ConsensusManagerBuilder::with_mmr_hashing]. The output SMT is kept by the
+/// database and always uses [OutputSmt](crate::OutputSmt).
+///
+/// [ConsensusManagerBuilder::with_mmr_hashing]: crate::consensus::ConsensusManagerBuilder::with_mmr_hashing
+pub trait MmrHashing: Debug {
+    /// Returns the kernel MMR with the given peaks
+    fn kernel_mmr(&self, kernels: PrunedHashSet) -> Box<dyn PrunedMmr>;
+    /// Returns an empty input MMR
+    fn input_mmr(&self) -> Box<dyn PrunedMmr>;
+}
+
+/// Hashes the kernel MMR with `K` and the input MMR with `I`
+pub struct DomainMmrHashing<K, I> {
+    _hashers: PhantomData<fn() -> (K, I)>,
+}
+
+impl<K, I> DomainMmrHashing<K, I> {
+    pub fn new() -> Self {
+        Self { _hashers: PhantomData }
+    }
+}
+
+impl<K, I> Default for DomainMmrHashing<K, I> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K, I> Debug for DomainMmrHashing<K, I> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DomainMmrHashing")
+            .field("kernel_hasher", &std::any::type_name::<K>())
+            .field("input_hasher", &std::any::type_name::<I>())
+            .finish()
+    }
+}
+
+impl<K, I> MmrHashing for DomainMmrHashing<K, I>
+where
+    K: DomainDigest + 'static,
+    I: DomainDigest + 'static,
+{
+    fn kernel_mmr(&self, kernels: PrunedHashSet) -> Box<dyn PrunedMmr> {
+        Box::new(MerkleMountainRange::<K, _>::new(kernels))
+    }
+
+    fn input_mmr(&self) -> Box<dyn PrunedMmr> {
+        Box::new(MerkleMountainRange::<I, _>::new(PrunedHashSet::default()))
+    }
+}
+
+/// The MMR hashing of every network, unless the consensus rules set another
+pub type DefaultMmrHashing = DomainMmrHashing<KernelMmrHasherBlake256, InputMmrHasherBlake256>;
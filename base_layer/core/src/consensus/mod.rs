@@ -23,6 +23,11 @@
 #[cfg(feature = "base_node")]
 pub(crate) mod chain_strength_comparer;
 
+#[cfg(feature = "base_node")]
+mod mmr_hashing;
+#[cfg(feature = "base_node")]
+pub use mmr_hashing::{DefaultMmrHashing, DomainMmrHashing, MmrHashing, PrunedMmr};
+
 pub mod consensus_constants;
 pub use consensus_constants::{ConsensusConstants, ConsensusConstantsBuilder};
 
@@ -60,7 +60,7 @@ mod domain_hashing {
     use tari_hashing::ValidatorNodeBmtHashDomain;
     use tari_mmr::{
         pruned_hashset::PrunedHashSet,
-        sparse_merkle_tree::SparseMerkleTree,
+        sparse_merkle_tree::{InclusionProof, SparseMerkleTree},
         BalancedBinaryMerkleTree,
         Hash,
         MerkleMountainRange,
@@ -80,6 +80,7 @@ mod domain_hashing {
     pub type PrunedInputMmr = MerkleMountainRange<InputMmrHasherBlake256, PrunedHashSet>;
 
     pub type OutputSmt = SparseMerkleTree<OutputSmtHasherBlake256>;
+    pub type OutputSmtInclusionProof = InclusionProof<OutputSmtHasherBlake256>;
 
     pub type ValidatorNodeBmtHasherBlake256 = DomainSeparatedHasher<Blake2b<U32>, ValidatorNodeBmtHashDomain>;
     pub type ValidatorNodeBMT = BalancedBinaryMerkleTree<ValidatorNodeBmtHasherBlake256>;
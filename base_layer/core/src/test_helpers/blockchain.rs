@@ -111,6 +111,26 @@ pub fn create_custom_blockchain(rules: ConsensusManager) -> BlockchainDatabase<T
     create_store_with_consensus_and_validators(rules, validators, smt)
 }
 
+/// Create a new custom blockchain database containing no blocks, with a backend that uses the same rules. This is
+/// needed for rules that change what the backend stores, e.g. the MMR hashing.
+pub fn create_custom_blockchain_and_backend(rules: ConsensusManager) -> BlockchainDatabase<TempDatabase> {
+    let validators = Validators::new(
+        MockValidator::new(true),
+        MockValidator::new(true),
+        MockValidator::new(true),
+    );
+    let smt = Arc::new(RwLock::new(OutputSmt::new()));
+    BlockchainDatabase::new(
+        TempDatabase::with_rules(rules.clone()),
+        rules.clone(),
+        validators,
+        BlockchainDatabaseConfig::default(),
+        DifficultyCalculator::new(rules, Default::default()),
+        smt,
+    )
+    .unwrap()
+}
+
 pub fn create_store_with_consensus_and_validators(
     rules: ConsensusManager,
     validators: Validators<TempDatabase>,
@@ -164,8 +184,12 @@ pub struct TempDatabase {
 
 impl TempDatabase {
     pub fn new() -> Self {
+        Self::with_rules(create_consensus_rules())
+    }
+
+    /// Creates a temporary database whose backend uses `rules`
+    pub fn with_rules(rules: ConsensusManager) -> Self {
         let temp_path = create_temporary_data_path();
-        let rules = create_consensus_rules();
 
         Self {
             db: Some(create_lmdb_database(&temp_path, LMDBConfig::default(), rules).unwrap()),
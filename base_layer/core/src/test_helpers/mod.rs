@@ -55,6 +55,8 @@ use crate::{
 #[macro_use]
 mod block_spec;
 pub mod blockchain;
+#[cfg(feature = "benches")]
+pub mod validation_bench;
 
 pub fn create_consensus_rules() -> ConsensusManager {
     ConsensusManager::builder(Network::LocalNet).build().unwrap()
@@ -0,0 +1,132 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+//! Helpers for benchmarking block body validation on synthetic blocks.
+
+use std::{
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use tari_common::configuration::Network;
+
+use crate::{
+    block_spec,
+    blocks::Block,
+    consensus::{ConsensusConstantsBuilder, ConsensusManager},
+    test_helpers::blockchain::TestBlockchain,
+    transactions::{
+        tari_amount::MicroMinotari,
+        test_helpers::schema_to_transaction,
+        transaction_components::MAX_TRANSACTION_OUTPUTS,
+    },
+    txn_schema,
+    validation::{block_body::BlockBodyFullValidator, ValidationError},
+};
+
+/// Builds a test blockchain and an unmined block on its tip with `num_kernels` transactions that together spend
+/// `num_inputs` inputs and create `num_outputs` outputs. Each transaction also adds a change output, and the block
+/// includes a coinbase.
+///
+/// Panics if `num_kernels` is zero, if there are fewer inputs or outputs than kernels, or if `num_inputs` is not
+/// less than `MAX_TRANSACTION_OUTPUTS`.
+pub async fn build_test_block(num_inputs: usize, num_outputs: usize, num_kernels: usize) -> (TestBlockchain, Block) {
+    assert!(num_kernels > 0, "at least one kernel is required");
+    assert!(num_inputs >= num_kernels, "every kernel needs at least one input");
+    assert!(num_outputs >= num_kernels, "every kernel needs at least one output");
+    assert!(num_inputs < MAX_TRANSACTION_OUTPUTS, "too many inputs");
+
+    let rules = ConsensusManager::builder(Network::LocalNet)
+        .add_consensus_constants(
+            ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_coinbase_lockheight(0)
+                .with_max_block_transaction_weight(u64::from(u32::MAX))
+                .build(),
+        )
+        .build()
+        .unwrap();
+    let mut blockchain = TestBlockchain::create(rules).await;
+    let (_, coinbase_a) = blockchain.add_next_tip(block_spec!("A")).await.unwrap();
+
+    // Split the coinbase into the outputs that the benchmark block will spend
+    let split_value = MicroMinotari::from(coinbase_a.value.as_u64() / (num_inputs as u64 + 1));
+    let schema = txn_schema!(from: vec![coinbase_a], to: vec![split_value; num_inputs]);
+    let (txs, outputs) = schema_to_transaction(&[schema], &blockchain.km).await;
+    let txs = txs.into_iter().map(|t| Arc::try_unwrap(t).unwrap()).collect::<Vec<_>>();
+    blockchain
+        .append(block_spec!("B", parent: "A", transactions: txs))
+        .await
+        .unwrap();
+
+    let mut inputs = vec![Vec::new(); num_kernels];
+    for (i, output) in outputs
+        .into_iter()
+        .filter(|o| o.value == split_value)
+        .take(num_inputs)
+        .enumerate()
+    {
+        inputs[i % num_kernels].push(output);
+    }
+    let mut schemas = Vec::with_capacity(num_kernels);
+    for (i, from) in inputs.into_iter().enumerate() {
+        let num_tx_outputs = num_outputs / num_kernels + usize::from(i < num_outputs % num_kernels);
+        let input_value = from.iter().map(|o| o.value.as_u64()).sum::<u64>();
+        let output_value = MicroMinotari::from(input_value / (num_tx_outputs as u64 + 1));
+        schemas.push(txn_schema!(from: from, to: vec![output_value; num_tx_outputs]));
+    }
+    let (txs, _) = schema_to_transaction(&schemas, &blockchain.km).await;
+    let txs = txs.into_iter().map(|t| Arc::try_unwrap(t).unwrap()).collect::<Vec<_>>();
+    let (chain_block, _) = blockchain
+        .create_next_tip(block_spec!("C", parent: "B", transactions: txs))
+        .await;
+
+    let (mut block, mmr_roots) = blockchain
+        .db()
+        .calculate_mmr_roots(chain_block.block().clone())
+        .unwrap();
+    block.header.input_mr = mmr_roots.input_mr;
+    block.header.output_mr = mmr_roots.output_mr;
+    block.header.output_smt_size = mmr_roots.output_smt_size;
+    block.header.kernel_mr = mmr_roots.kernel_mr;
+    block.header.kernel_mmr_size = mmr_roots.kernel_mmr_size;
+    block.header.validator_node_mr = mmr_roots.validator_node_mr;
+    block.header.validator_node_size = mmr_roots.validator_node_size;
+
+    (blockchain, block)
+}
+
+/// Validates `block` against the tip of `blockchain` `iterations` times and returns the average time taken per
+/// validation.
+pub fn run_validation_bench(
+    blockchain: &TestBlockchain,
+    block: &Block,
+    validator: &BlockBodyFullValidator,
+    iterations: u32,
+) -> Result<Duration, ValidationError> {
+    let txn = blockchain.db().db_read_access()?;
+    let smt = blockchain.db().smt();
+    let start = Instant::now();
+    for _ in 0..iterations {
+        validator.validate(&*txn, block, None, smt.clone())?;
+    }
+    Ok(start.elapsed() / iterations.max(1))
+}
@@ -32,6 +32,7 @@ use minotari_ledger_wallet_comms::accessor_methods::{
     ledger_get_script_offset,
     ledger_get_script_schnorr_signature,
     ledger_get_script_signature,
+    ledger_get_script_signature_share,
 };
 use rand::rngs::OsRng;
 #[cfg(feature = "ledger")]
@@ -1220,6 +1221,78 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
         }
     }
 
+    pub async fn get_script_signature_share(
+        &self,
+        private_key_id: &TariKeyId,
+        nonce_key_id: &TariKeyId,
+        txi_version: &TransactionInputVersion,
+        ephemeral_commitment: &Commitment,
+        ephemeral_pubkey: &PublicKey,
+        script_public_key: &PublicKey,
+        commitment: &Commitment,
+        script_message: &[u8; 32],
+    ) -> Result<Signature, TransactionError> {
+        match &self.wallet_type {
+            WalletType::Ledger(ledger) => {
+                #[cfg(not(feature = "ledger"))]
+                {
+                    Err(TransactionError::LedgerNotSupported(format!(
+                        "Ledger {} is not supported",
+                        ledger
+                    )))
+                }
+
+                #[cfg(feature = "ledger")]
+                {
+                    match (private_key_id, nonce_key_id) {
+                        (
+                            KeyId::Managed {
+                                branch: private_key_branch,
+                                index: private_key_index,
+                            },
+                            KeyId::Managed {
+                                branch: nonce_branch,
+                                index: nonce_index,
+                            },
+                        ) => {
+                            let signature = ledger_get_script_signature_share(
+                                ledger.account,
+                                ledger.network,
+                                txi_version.as_u8(),
+                                *private_key_index,
+                                TransactionKeyManagerBranch::from_key(private_key_branch),
+                                *nonce_index,
+                                TransactionKeyManagerBranch::from_key(nonce_branch),
+                                ephemeral_commitment,
+                                ephemeral_pubkey,
+                                script_public_key,
+                                commitment,
+                                *script_message,
+                            )
+                            .map_err(|e| KeyManagerServiceError::LedgerError(e.to_string()))?;
+                            Ok(signature)
+                        },
+                        (KeyId::Managed { .. }, key_id) | (key_id, _) => Err(TransactionError::UnsupportedTariKeyId(
+                            format!("Expected 'KeyId::Managed', got {}", key_id),
+                        )),
+                    }
+                }
+            },
+            _ => {
+                let challenge = TransactionInput::finalize_script_signature_challenge(
+                    txi_version,
+                    ephemeral_commitment,
+                    ephemeral_pubkey,
+                    script_public_key,
+                    commitment,
+                    script_message,
+                );
+                self.sign_with_nonce_and_challenge(private_key_id, nonce_key_id, &challenge)
+                    .await
+            },
+        }
+    }
+
     pub async fn get_metadata_signature(
         &self,
         spending_key_id: &TariKeyId,
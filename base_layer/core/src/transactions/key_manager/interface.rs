@@ -233,6 +233,19 @@ pub trait TransactionKeyManagerInterface: KeyManagerInterface<PublicKey> {
         challenge: &[u8; 64],
     ) -> Result<Signature, TransactionError>;
 
+    /// Signs a share of an aggregated script signature with the script challenge built from the given parts
+    async fn get_script_signature_share(
+        &self,
+        private_key_id: &TariKeyId,
+        nonce_key_id: &TariKeyId,
+        txi_version: &TransactionInputVersion,
+        ephemeral_commitment: &Commitment,
+        ephemeral_pubkey: &PublicKey,
+        script_public_key: &PublicKey,
+        commitment: &Commitment,
+        script_message: &[u8; 32],
+    ) -> Result<Signature, TransactionError>;
+
     async fn get_receiver_partial_metadata_signature(
         &self,
         commitment_mask_key_id: &TariKeyId,
@@ -486,6 +486,33 @@ where TBackend: KeyManagerBackend<PublicKey> + 'static
             .await
     }
 
+    async fn get_script_signature_share(
+        &self,
+        private_key_id: &TariKeyId,
+        nonce_key_id: &TariKeyId,
+        txi_version: &TransactionInputVersion,
+        ephemeral_commitment: &Commitment,
+        ephemeral_pubkey: &PublicKey,
+        script_public_key: &PublicKey,
+        commitment: &Commitment,
+        script_message: &[u8; 32],
+    ) -> Result<Signature, TransactionError> {
+        self.transaction_key_manager_inner
+            .read()
+            .await
+            .get_script_signature_share(
+                private_key_id,
+                nonce_key_id,
+                txi_version,
+                ephemeral_commitment,
+                ephemeral_pubkey,
+                script_public_key,
+                commitment,
+                script_message,
+            )
+            .await
+    }
+
     async fn get_receiver_partial_metadata_signature(
         &self,
         commitment_mask_key_id: &TariKeyId,
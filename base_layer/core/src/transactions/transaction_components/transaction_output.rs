@@ -30,6 +30,12 @@ use std::{
 
 use blake2::Blake2b;
 use borsh::{BorshDeserialize, BorshSerialize};
+use curve25519_dalek::{
+    constants::RISTRETTO_BASEPOINT_POINT,
+    ristretto::{CompressedRistretto, RistrettoPoint},
+    scalar::Scalar,
+    traits::{IsIdentity, VartimeMultiscalarMul},
+};
 use digest::consts::{U32, U64};
 use rand::rngs::OsRng;
 use serde::{Deserialize, Serialize};
@@ -49,7 +55,7 @@ use tari_crypto::{
     extended_range_proof::{ExtendedRangeProofService, Statement},
     keys::SecretKey,
     ristretto::bulletproofs_plus::RistrettoAggregatedPublicStatement,
-    tari_utilities::hex::Hex,
+    tari_utilities::{hex::Hex, ByteArray},
 };
 use tari_hashing::TransactionHashDomain;
 use tari_script::TariScript;
@@ -268,7 +274,7 @@ impl TransactionOutput {
     // As an alternate range proof check, the value of the commitment with a deterministic ephemeral_commitment nonce
     // `r_a` of zero can optionally be bound into the metadata signature. This is a much faster check than the full
     // range proof verification.
-    fn revealed_value_range_proof_check(&self) -> Result<(), RangeProofError> {
+    pub fn revealed_value_range_proof_check(&self) -> Result<(), RangeProofError> {
         if self.features.range_proof_type != RangeProofType::RevealedValue {
             return Err(RangeProofError::InvalidRangeProof {
                 reason: format!(
@@ -306,18 +312,7 @@ impl TransactionOutput {
     }
 
     fn verify_metadata_signature_internal(&self) -> Result<[u8; 64], TransactionError> {
-        let challenge = TransactionOutput::build_metadata_signature_challenge(
-            &self.version,
-            &self.script,
-            &self.features,
-            &self.sender_offset_public_key,
-            self.metadata_signature.ephemeral_commitment(),
-            self.metadata_signature.ephemeral_pubkey(),
-            &self.commitment,
-            &self.covenant,
-            &self.encrypted_data,
-            self.minimum_value_promise,
-        );
+        let challenge = self.metadata_signature_challenge();
 
         if !self.metadata_signature.verify_challenge(
             &self.commitment,
@@ -333,6 +328,21 @@ impl TransactionOutput {
         Ok(challenge)
     }
 
+    fn metadata_signature_challenge(&self) -> [u8; 64] {
+        TransactionOutput::build_metadata_signature_challenge(
+            &self.version,
+            &self.script,
+            &self.features,
+            &self.sender_offset_public_key,
+            self.metadata_signature.ephemeral_commitment(),
+            self.metadata_signature.ephemeral_pubkey(),
+            &self.commitment,
+            &self.covenant,
+            &self.encrypted_data,
+            self.minimum_value_promise,
+        )
+    }
+
     /// Verify that the metadata signature is valid
     pub fn verify_metadata_signature(&self) -> Result<(), TransactionError> {
         let _challenge = self.verify_metadata_signature_internal()?;
@@ -567,11 +577,81 @@ pub fn batch_verify_range_proofs(
     Ok(())
 }
 
+/// Verifies the metadata signatures of an arbitrary number of outputs at once. Each signature must satisfy
+/// `u_x.G + u_a.H = R_a + e.C` and `u_y.G = R_y + e.K_O`. Every equation is scaled by its own random weight and all of
+/// them are summed into a single multiscalar multiplication, which only comes to the identity if every signature is
+/// valid (except with negligible probability).
+///
+/// The error does not say which signature is invalid. Use [TransactionOutput::verify_metadata_signature] on each
+/// output to find it.
+pub fn batch_verify_metadata_signatures(outputs: &[&TransactionOutput]) -> Result<(), TransactionError> {
+    // An empty batch is valid
+    if outputs.is_empty() {
+        return Ok(());
+    }
+    let invalid = || TransactionError::InvalidSignatureError("Metadata signature batch not valid!".to_string());
+
+    let generator_h = CommitmentFactory::default().commit_value(&PrivateKey::default(), 1);
+    let mut g_scalar = Scalar::ZERO;
+    let mut h_scalar = Scalar::ZERO;
+    let mut scalars = Vec::with_capacity(outputs.len() * 4 + 2);
+    let mut points = Vec::with_capacity(outputs.len() * 4 + 2);
+    for output in outputs {
+        // The same signatures are rejected as by verify_challenge
+        if output.commitment.as_public_key() == &PublicKey::default() ||
+            output.sender_offset_public_key == PublicKey::default()
+        {
+            return Err(invalid());
+        }
+        let e = Scalar::from_bytes_mod_order_wide(&output.metadata_signature_challenge());
+        if e == Scalar::ZERO {
+            return Err(invalid());
+        }
+
+        let signature = &output.metadata_signature;
+        let w = to_scalar(&PrivateKey::random(&mut OsRng));
+        let v = to_scalar(&PrivateKey::random(&mut OsRng));
+        g_scalar += w * to_scalar(signature.u_x()) + v * to_scalar(signature.u_y());
+        h_scalar += w * to_scalar(signature.u_a());
+
+        scalars.push(-w);
+        points.push(to_point(signature.ephemeral_commitment().as_public_key()).ok_or_else(invalid)?);
+        scalars.push(-(w * e));
+        points.push(to_point(output.commitment.as_public_key()).ok_or_else(invalid)?);
+        scalars.push(-v);
+        points.push(to_point(signature.ephemeral_pubkey()).ok_or_else(invalid)?);
+        scalars.push(-(v * e));
+        points.push(to_point(&output.sender_offset_public_key).ok_or_else(invalid)?);
+    }
+    scalars.push(g_scalar);
+    points.push(RISTRETTO_BASEPOINT_POINT);
+    scalars.push(h_scalar);
+    points.push(to_point(generator_h.as_public_key()).ok_or_else(invalid)?);
+
+    if RistrettoPoint::vartime_multiscalar_mul(scalars, points).is_identity() {
+        Ok(())
+    } else {
+        Err(invalid())
+    }
+}
+
+fn to_scalar(key: &PrivateKey) -> Scalar {
+    let mut bytes = [0u8; 32];
+    bytes.copy_from_slice(key.as_bytes());
+    Scalar::from_bytes_mod_order(bytes)
+}
+
+fn to_point(key: &PublicKey) -> Option<RistrettoPoint> {
+    CompressedRistretto::from_slice(key.as_bytes()).ok()?.decompress()
+}
+
 #[cfg(test)]
 mod test {
-    use tari_crypto::errors::RangeProofError;
+    use rand::rngs::OsRng;
+    use tari_common_types::types::{ComAndPubSignature, PrivateKey};
+    use tari_crypto::{errors::RangeProofError, keys::SecretKey};
 
-    use super::{batch_verify_range_proofs, TransactionOutput};
+    use super::{batch_verify_metadata_signatures, batch_verify_range_proofs, TransactionOutput};
     use crate::transactions::{
         key_manager::{create_memory_db_key_manager, MemoryDbKeyManager, TransactionKeyManagerInterface},
         tari_amount::MicroMinotari,
@@ -797,6 +877,75 @@ mod test {
         assert!(batch_verify_range_proofs(&factories.range_proof, &outputs).is_err());
     }
 
+    #[tokio::test]
+    async fn it_does_batch_verify_metadata_signatures() {
+        let key_manager = create_memory_db_key_manager().unwrap();
+        let test_params = TestParams::new(&key_manager).await;
+
+        let mut outputs = Vec::new();
+        for range_proof_type in [
+            RangeProofType::BulletProofPlus,
+            RangeProofType::RevealedValue,
+            RangeProofType::BulletProofPlus,
+        ] {
+            outputs.push(
+                create_output(
+                    &test_params,
+                    MicroMinotari(10),
+                    MicroMinotari(10),
+                    range_proof_type,
+                    &key_manager,
+                )
+                .await
+                .unwrap(),
+            );
+        }
+
+        assert!(batch_verify_metadata_signatures(&outputs.iter().collect::<Vec<_>>()).is_ok());
+        assert!(batch_verify_metadata_signatures(&[]).is_ok());
+    }
+
+    #[tokio::test]
+    async fn it_does_not_batch_verify_invalid_metadata_signatures() {
+        let key_manager = create_memory_db_key_manager().unwrap();
+        let test_params = TestParams::new(&key_manager).await;
+
+        let valid_output = create_output(
+            &test_params,
+            MicroMinotari(10),
+            MicroMinotari(10),
+            RangeProofType::BulletProofPlus,
+            &key_manager,
+        )
+        .await
+        .unwrap();
+        // Changing the minimum value promise changes the challenge
+        let wrong_challenge = create_invalid_output(
+            &test_params,
+            MicroMinotari(10),
+            MicroMinotari(5),
+            RangeProofType::BulletProofPlus,
+            &key_manager,
+        )
+        .await;
+        let mut wrong_sender_offset_response = valid_output.clone();
+        let signature = &valid_output.metadata_signature;
+        wrong_sender_offset_response.metadata_signature = ComAndPubSignature::new(
+            signature.ephemeral_commitment().clone(),
+            signature.ephemeral_pubkey().clone(),
+            signature.u_a().clone(),
+            signature.u_x().clone(),
+            PrivateKey::random(&mut OsRng),
+        );
+        let mut swapped_signature = valid_output.clone();
+        swapped_signature.metadata_signature = wrong_challenge.metadata_signature.clone();
+
+        for invalid_output in [&wrong_challenge, &wrong_sender_offset_response, &swapped_signature] {
+            assert!(invalid_output.verify_metadata_signature().is_err());
+            assert!(batch_verify_metadata_signatures(&[&valid_output, invalid_output]).is_err());
+        }
+    }
+
     async fn create_output(
         test_params: &TestParams,
         value: MicroMinotari,
@@ -267,16 +267,17 @@ impl WalletOutput {
                 &message,
             )
             .await?;
-        let challenge = TransactionInput::finalize_script_signature_challenge(
-            &version,
-            commitment_partial_script_signature.ephemeral_commitment(),
-            &total_ephemeral_public_key,
-            &script_public_key,
-            &commitment,
-            &message,
-        );
         let script_key_partial_script_signature = key_manager
-            .sign_with_nonce_and_challenge(&self.script_key_id, &ephemeral_public_key_self.key_id, &challenge)
+            .get_script_signature_share(
+                &self.script_key_id,
+                &ephemeral_public_key_self.key_id,
+                &version,
+                commitment_partial_script_signature.ephemeral_commitment(),
+                &total_ephemeral_public_key,
+                &script_public_key,
+                &commitment,
+                &message,
+            )
             .await?;
         let script_signature = &commitment_partial_script_signature + &script_key_partial_script_signature;
 
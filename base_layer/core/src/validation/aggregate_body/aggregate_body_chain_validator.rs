@@ -20,12 +20,13 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::collections::HashSet;
+use std::{collections::HashSet, sync::Arc};
 
 use log::warn;
-use tari_common_types::types::FixedHash;
+use tari_common_types::types::{FixedHash, HashOutput};
 use tari_utilities::hex::Hex;
 
+use super::{BodyValidationDetails, MempoolSpentSet, PartialInputValidation, UtxoFilter};
 use crate::{
     chain_storage::BlockchainBackend,
     consensus::{ConsensusConstants, ConsensusManager},
@@ -35,8 +36,9 @@ use crate::{
     },
     validation::{
         helpers::{
+            check_coinbase_maturity,
             check_input_is_utxo,
-            check_not_duplicate_txo,
+            check_output_unique_in_body_and_utxo_set,
             check_tari_encrypted_data_byte_size,
             check_tari_script_byte_size,
         },
@@ -50,11 +52,59 @@ pub const LOG_TARGET: &str = "c::val::aggregate_body_chain_linked_validator";
 #[derive(Clone)]
 pub struct AggregateBodyChainLinkedValidator {
     consensus_manager: ConsensusManager,
+    record_spent_outputs: bool,
+    utxo_filter: Option<Arc<dyn UtxoFilter>>,
+    mempool_spent_set: Option<Arc<dyn MempoolSpentSet>>,
 }
 
 impl AggregateBodyChainLinkedValidator {
     pub fn new(consensus_manager: ConsensusManager) -> Self {
-        Self { consensus_manager }
+        Self {
+            consensus_manager,
+            record_spent_outputs: false,
+            utxo_filter: None,
+            mempool_spent_set: None,
+        }
+    }
+
+    /// When enabled, the hashes of the outputs spent by the inputs of each validated body are returned in its
+    /// [BodyValidationDetails], so that they do not have to be hashed again.
+    pub fn with_spent_output_recording(mut self, record_spent_outputs: bool) -> Self {
+        self.record_spent_outputs = record_spent_outputs;
+        self
+    }
+
+    /// Consults `filter` before looking an input up in the UTXO set. An input the filter does not contain is handled
+    /// as not found without a database read, so it is reported as unknown rather than as already spent. Inputs the
+    /// filter may contain, including false positives, are looked up as usual.
+    pub fn with_utxo_filter(mut self, filter: Arc<dyn UtxoFilter>) -> Self {
+        self.utxo_filter = Some(filter);
+        self
+    }
+
+    /// Reports the inputs of each validated body that are also spent by a transaction in `mempool_spent_set` in its
+    /// [BodyValidationDetails]
+    pub fn with_mempool_spent_set(mut self, mempool_spent_set: Arc<dyn MempoolSpentSet>) -> Self {
+        self.mempool_spent_set = Some(mempool_spent_set);
+        self
+    }
+
+    /// Returns the details of a body whose inputs spend the outputs with the given hashes, as they are returned after
+    /// validating the body with `utxo_lookups` UTXO set lookups
+    pub(crate) fn body_validation_details(
+        &self,
+        utxo_lookups: u64,
+        spent_output_hashes: Vec<HashOutput>,
+    ) -> BodyValidationDetails {
+        let mempool_spent_inputs = self.mempool_spent_set.as_ref().map(|mempool_spent_set| {
+            spent_output_hashes
+                .iter()
+                .filter(|hash| mempool_spent_set.contains(hash))
+                .copied()
+                .collect()
+        });
+        let spent_output_hashes = self.record_spent_outputs.then_some(spent_output_hashes);
+        BodyValidationDetails::new(utxo_lookups, spent_output_hashes, mempool_spent_inputs)
     }
 
     pub fn validate<B: BlockchainBackend>(
@@ -63,12 +113,41 @@ impl AggregateBodyChainLinkedValidator {
         height: u64,
         db: &B,
     ) -> Result<AggregateBody, ValidationError> {
+        let (body, _) = self.validate_with_details(body, height, db)?;
+        Ok(body)
+    }
+
+    /// Validates the body in the same way as [validate](Self::validate) and also returns what was learned about it
+    pub fn validate_with_details<B: BlockchainBackend>(
+        &self,
+        body: &AggregateBody,
+        height: u64,
+        db: &B,
+    ) -> Result<(AggregateBody, BodyValidationDetails), ValidationError> {
         let constants = self.consensus_manager.consensus_constants(height);
 
         self.validate_consensus(body, db, constants)?;
-        let body = self.validate_input_and_maturity(body, db, constants, height)?;
+        let (body, details, _) = self.validate_input_and_maturity(body, db, constants, height, false)?;
+        Ok((body, details))
+    }
 
-        Ok(body)
+    /// Validates the body in the same way as [validate](Self::validate), except that inputs that spend outputs which
+    /// are neither in the UTXO set nor created by the body do not fail validation with
+    /// [ValidationError::UnknownInputs]. The rest of the body is validated as far as it can be. This is meant for
+    /// tooling, e.g. to check a block whose ancestors are not fully synced. The checks that need the spent outputs are
+    /// skipped for the unknown inputs, so the body can only be taken out of the returned [PartialInputValidation] as
+    /// valid if there are none.
+    pub fn validate_best_effort<B: BlockchainBackend>(
+        &self,
+        body: &AggregateBody,
+        height: u64,
+        db: &B,
+    ) -> Result<PartialInputValidation, ValidationError> {
+        let constants = self.consensus_manager.consensus_constants(height);
+
+        self.validate_consensus(body, db, constants)?;
+        let (body, _, unknown_inputs) = self.validate_input_and_maturity(body, db, constants, height, true)?;
+        Ok(PartialInputValidation::new(body, unknown_inputs)?)
     }
 
     fn validate_consensus<B: BlockchainBackend>(
@@ -86,35 +165,93 @@ impl AggregateBodyChainLinkedValidator {
         Ok(())
     }
 
+    /// Returns the validated body and its details, followed by the hashes of the outputs spent by unknown inputs in
+    /// input order. There can only be unknown inputs if `best_effort` is set.
     fn validate_input_and_maturity<B: BlockchainBackend>(
         &self,
         body: &AggregateBody,
         db: &B,
         constants: &ConsensusConstants,
         height: u64,
-    ) -> Result<AggregateBody, ValidationError> {
+        best_effort: bool,
+    ) -> Result<(AggregateBody, BodyValidationDetails, Vec<HashOutput>), ValidationError> {
         // inputs may be "slim", only containing references to outputs
         // so we need to resolve those references, creating a new body in the process
-        let inputs = validate_input_not_pruned(body, db)?;
+        let (inputs, unresolved_inputs) = validate_input_not_pruned(body, db, best_effort)?;
         // UNCHECKED: sorting has been checked by the AggregateBodyInternalConsistencyValidator
         let body = AggregateBody::new_sorted_unchecked(inputs, body.outputs().to_vec(), body.kernels().to_vec());
 
-        validate_input_maturity(&body, height)?;
-        check_inputs_are_utxos(db, &body)?;
-        check_outputs(db, constants, &body)?;
-        verify_no_duplicated_inputs_outputs(&body)?;
-        check_total_burned(&body)?;
-        verify_timelocks(&body, height)?;
+        // Inputs that could not be resolved are kept in the returned body, but nothing can be checked about them
+        let resolved_body;
+        let checked_body = if unresolved_inputs.is_empty() {
+            &body
+        } else {
+            let inputs = body.inputs().iter().filter(|i| !i.is_compact()).cloned().collect();
+            resolved_body =
+                AggregateBody::new_sorted_unchecked(inputs, body.outputs().to_vec(), body.kernels().to_vec());
+            &resolved_body
+        };
 
-        Ok(body)
+        self.validate_coinbase_input_maturity(checked_body, db, height)?;
+        validate_input_maturity(checked_body, height)?;
+        let mut utxo_lookups = 0;
+        let (spent_output_hashes, not_found_inputs) =
+            check_inputs_are_utxos(db, checked_body, &mut utxo_lookups, self.utxo_filter.as_deref())?;
+        if !best_effort && !not_found_inputs.is_empty() {
+            return Err(ValidationError::UnknownInputs(not_found_inputs));
+        }
+        check_outputs(db, constants, checked_body)?;
+        // Every output was looked up once to check that it is not in the UTXO set yet
+        utxo_lookups += checked_body.outputs().len() as u64;
+        verify_no_duplicated_inputs(checked_body)?;
+        check_total_burned(checked_body)?;
+        verify_timelocks(checked_body, height)?;
+        let details = self.body_validation_details(utxo_lookups, spent_output_hashes);
+
+        let unknown_inputs = if best_effort {
+            body.inputs()
+                .iter()
+                .map(|input| input.output_hash())
+                .filter(|hash| unresolved_inputs.contains(hash) || not_found_inputs.contains(hash))
+                .collect()
+        } else {
+            Vec::new()
+        };
+
+        Ok((body, details, unknown_inputs))
+    }
+
+    /// Checks the inputs that spend coinbase outputs against the coinbase lock of the rules the coinbase was mined
+    /// under. A coinbase that is not in the database yet is taken to be mined at `height`.
+    fn validate_coinbase_input_maturity<B: BlockchainBackend>(
+        &self,
+        body: &AggregateBody,
+        db: &B,
+        height: u64,
+    ) -> Result<(), ValidationError> {
+        for input in body.inputs() {
+            if !input.features()?.is_coinbase() {
+                continue;
+            }
+            let mined_height = db
+                .fetch_output(&input.output_hash())?
+                .map_or(height, |output| output.mined_height);
+            let constants = self.consensus_manager.consensus_constants(mined_height);
+            check_coinbase_maturity(input, mined_height, height, constants)?;
+        }
+        Ok(())
     }
 }
 
+/// Resolves the compact inputs of the body to the outputs they spend. When `allow_unknown` is set, compact inputs that
+/// cannot be resolved are left compact and their output hashes are returned instead of failing.
 fn validate_input_not_pruned<B: BlockchainBackend>(
     body: &AggregateBody,
     db: &B,
-) -> Result<Vec<TransactionInput>, ValidationError> {
+    allow_unknown: bool,
+) -> Result<(Vec<TransactionInput>, Vec<HashOutput>), ValidationError> {
     let mut inputs: Vec<TransactionInput> = body.inputs().clone();
+    let mut unresolved_inputs = Vec::new();
     for input in &mut inputs {
         if input.is_compact() {
             let output = match db.fetch_output(&input.output_hash()) {
@@ -124,6 +261,9 @@ fn validate_input_not_pruned<B: BlockchainBackend>(
                         let input_output_hash = input.output_hash();
                         if let Some(found) = body.outputs().iter().find(|o| o.hash() == input_output_hash) {
                             found.clone()
+                        } else if allow_unknown {
+                            unresolved_inputs.push(input_output_hash);
+                            continue;
                         } else {
                             warn!(
                                 target: LOG_TARGET,
@@ -156,7 +296,7 @@ fn validate_input_not_pruned<B: BlockchainBackend>(
         }
     }
 
-    Ok(inputs)
+    Ok((inputs, unresolved_inputs))
 }
 
 fn validate_input_maturity(body: &AggregateBody, height: u64) -> Result<(), ValidationError> {
@@ -212,15 +352,31 @@ fn check_validator_node_registration_utxo(
     Ok(())
 }
 
-/// This function checks that all inputs in the blocks are valid UTXO's to be spent
-fn check_inputs_are_utxos<B: BlockchainBackend>(db: &B, body: &AggregateBody) -> Result<(), ValidationError> {
+/// This function checks that all inputs in the blocks are valid UTXO's to be spent. Returns the hashes of the outputs
+/// the inputs spend, followed by the hashes of the outputs that were not found.
+fn check_inputs_are_utxos<B: BlockchainBackend>(
+    db: &B,
+    body: &AggregateBody,
+    utxo_lookups: &mut u64,
+    utxo_filter: Option<&dyn UtxoFilter>,
+) -> Result<(Vec<HashOutput>, Vec<HashOutput>), ValidationError> {
     let mut not_found_inputs = Vec::new();
     let mut output_hashes = None;
+    let mut spent_output_hashes = Vec::with_capacity(body.inputs().len());
 
     for input in body.inputs() {
+        let in_utxo_set = match utxo_filter {
+            // A definite miss is handled like an input that is not in the database. It may still spend an output of
+            // this body.
+            Some(filter) if !filter.may_contain(&input.output_hash()) => Err(ValidationError::UnknownInput),
+            _ => {
+                *utxo_lookups += 1;
+                check_input_is_utxo(db, input)
+            },
+        };
         // If spending a unique_id, a new output must contain the unique id
-        match check_input_is_utxo(db, input) {
-            Ok(_) => continue,
+        match in_utxo_set {
+            Ok(output_hash) => spent_output_hashes.push(output_hash),
             Err(ValidationError::UnknownInput) => {
                 // Lazily allocate and hash outputs as needed
                 if output_hashes.is_none() {
@@ -230,6 +386,7 @@ fn check_inputs_are_utxos<B: BlockchainBackend>(db: &B, body: &AggregateBody) ->
                 let output_hashes = output_hashes.as_ref().unwrap();
                 let input_output_hash = input.output_hash();
                 if output_hashes.iter().any(|val| val == &input_output_hash) {
+                    spent_output_hashes.push(input_output_hash);
                     continue;
                 }
                 warn!(
@@ -245,17 +402,13 @@ fn check_inputs_are_utxos<B: BlockchainBackend>(db: &B, body: &AggregateBody) ->
         }
     }
 
-    if !not_found_inputs.is_empty() {
-        return Err(ValidationError::UnknownInputs(not_found_inputs));
-    }
-
-    Ok(())
+    Ok((spent_output_hashes, not_found_inputs))
 }
 
 /// This function checks:
 /// 1. that the output type is permitted
 /// 2. the byte size of TariScript does not exceed the maximum
-/// 3. that the outputs do not already exist in the UTxO set.
+/// 3. that the outputs are sorted and unique in the body and do not already exist in the UTxO set, in the same pass
 pub fn check_outputs<B: BlockchainBackend>(
     db: &B,
     constants: &ConsensusConstants,
@@ -263,17 +416,19 @@ pub fn check_outputs<B: BlockchainBackend>(
 ) -> Result<(), ValidationError> {
     let max_script_size = constants.max_script_byte_size();
     let max_encrypted_data_size = constants.max_extra_encrypted_data_byte_size();
+    let mut previous = None;
     for output in body.outputs() {
         check_tari_script_byte_size(&output.script, max_script_size)?;
         check_tari_encrypted_data_byte_size(&output.encrypted_data, max_encrypted_data_size)?;
-        check_not_duplicate_txo(db, output)?;
+        check_output_unique_in_body_and_utxo_set(db, previous, output)?;
         check_validator_node_registration_utxo(constants, output)?;
+        previous = Some(output);
     }
     Ok(())
 }
 
-/// This function checks the body contains no duplicated inputs or outputs.
-fn verify_no_duplicated_inputs_outputs(body: &AggregateBody) -> Result<(), ValidationError> {
+/// This function checks the body contains no duplicated inputs. Duplicated outputs are caught by [check_outputs].
+fn verify_no_duplicated_inputs(body: &AggregateBody) -> Result<(), ValidationError> {
     if body.contains_duplicated_inputs() {
         warn!(
             target: LOG_TARGET,
@@ -281,13 +436,6 @@ fn verify_no_duplicated_inputs_outputs(body: &AggregateBody) -> Result<(), Valid
         );
         return Err(ValidationError::UnsortedOrDuplicateInput);
     }
-    if body.contains_duplicated_outputs() {
-        warn!(
-            target: LOG_TARGET,
-            "AggregateBody validation failed due to double output"
-        );
-        return Err(ValidationError::UnsortedOrDuplicateOutput);
-    }
     Ok(())
 }
 
@@ -20,26 +20,28 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::{collections::HashSet, convert::TryInto};
+use std::{
+    collections::HashSet,
+    convert::TryInto,
+    panic::{self, AssertUnwindSafe},
+};
 
 use log::{trace, warn};
-use tari_common_types::types::{Commitment, CommitmentFactory, HashOutput, PrivateKey, PublicKey, RangeProofService};
-use tari_crypto::{
-    commitment::HomomorphicCommitmentFactory,
-    keys::PublicKey as PublicKeyTrait,
-    ristretto::pedersen::PedersenCommitment,
-};
+use tari_common_types::types::{CommitmentFactory, HashOutput, PrivateKey, PublicKey, RangeProofService};
+use tari_comms::types::CommsDHKE;
+use tari_crypto::{commitment::HomomorphicCommitmentFactory, keys::PublicKey as PublicKeyTrait};
 use tari_script::ScriptContext;
-use tari_utilities::hex::Hex;
 
 use crate::{
     consensus::{ConsensusConstants, ConsensusManager},
+    one_sided::shared_secret_to_output_encryption_key,
     transactions::{
         aggregated_body::AggregateBody,
         tari_amount::MicroMinotari,
         transaction_components::{
-            transaction_output::batch_verify_range_proofs,
-            KernelSum,
+            transaction_output::{batch_verify_metadata_signatures, batch_verify_range_proofs},
+            EncryptedData,
+            RangeProofType,
             TransactionError,
             TransactionInput,
             TransactionKernel,
@@ -49,7 +51,11 @@ use crate::{
     },
     validation::{
         helpers::{
+            check_balance_only,
             check_covenant_length,
+            check_kernel_features,
+            check_min_output_value,
+            check_output_maturity,
             check_permitted_output_types,
             check_permitted_range_proof_types,
             check_tari_encrypted_data_byte_size,
@@ -68,6 +74,8 @@ pub const LOG_TARGET: &str = "c::val::aggregate_body_internal_consistency_valida
 #[derive(Clone)]
 pub struct AggregateBodyInternalConsistencyValidator {
     bypass_range_proof_verification: bool,
+    bypass_kernel_signature_verification: bool,
+    batch_metadata_signature_verification: bool,
     consensus_manager: ConsensusManager,
     factories: CryptoFactories,
 }
@@ -80,16 +88,43 @@ impl AggregateBodyInternalConsistencyValidator {
     ) -> Self {
         Self {
             bypass_range_proof_verification,
+            bypass_kernel_signature_verification: false,
+            batch_metadata_signature_verification: false,
             consensus_manager,
             factories,
         }
     }
 
+    /// Skips verifying the kernel signatures. The kernel sum is still checked, so the body must balance, but a kernel
+    /// excess that was not signed by its owner is not detected. Only bodies from a trusted source, e.g. a checkpoint,
+    /// may be validated like this.
+    pub fn with_kernel_signature_verification_bypass(mut self, bypass_kernel_signature_verification: bool) -> Self {
+        if bypass_kernel_signature_verification {
+            warn!(
+                target: LOG_TARGET,
+                "Kernel signature verification is bypassed. Kernels are NOT checked to be signed by their owners, so only \
+                 bodies from a trusted source must be validated."
+            );
+        }
+        self.bypass_kernel_signature_verification = bypass_kernel_signature_verification;
+        self
+    }
+
+    /// Verifies the metadata signatures of all outputs in a single batch instead of one at a time, which is faster
+    /// for bodies with many outputs. The body is rejected if any signature is invalid either way.
+    pub fn with_batch_metadata_signature_verification(mut self, batch_metadata_signature_verification: bool) -> Self {
+        self.batch_metadata_signature_verification = batch_metadata_signature_verification;
+        self
+    }
+
     /// Validate this transaction by checking the following:
     /// 1. The sum of inputs, outputs and fees equal the (public excess value + offset)
-    /// 1. The signature signs the canonical message with the private excess
+    /// 1. The signature signs the canonical message with the private excess, unless bypassed
     /// 1. Range proofs of the outputs are valid
     ///
+    /// Cut-through is not permitted: an output that is spent in the same body must stay in the body along with the
+    /// input that spends it. Removing both keeps the commitments balanced, but the script offset no longer matches.
+    ///
     /// This function does NOT check that inputs come from the UTXO set
     /// The reward is the total amount of MicroTari rewarded for this block (block reward + total fees), this should be
     /// 0 for a transaction
@@ -105,19 +140,20 @@ impl AggregateBodyInternalConsistencyValidator {
         let total_reward = total_reward.unwrap_or(MicroMinotari::zero());
 
         // old internal validator
-        verify_kernel_signatures(body)?;
+        if !self.bypass_kernel_signature_verification {
+            verify_kernel_signatures(body)?;
+        }
 
         let constants = self.consensus_manager.consensus_constants(height);
 
         validate_versions(body, constants)?;
 
         for output in body.outputs() {
-            check_permitted_output_types(constants, output)?;
-            check_script_size(output, constants.max_script_byte_size())?;
-            check_encrypted_data_byte_size(output, constants.max_extra_encrypted_data_byte_size())?;
-            check_covenant_length(&output.covenant, constants.max_covenant_length())?;
-            check_permitted_range_proof_types(constants, output)?;
-            check_validator_node_registration_utxo(constants, output)?;
+            check_output_consensus_rules(constants, height, output)?;
+        }
+
+        for kernel in body.kernels() {
+            check_kernel_features(kernel)?;
         }
 
         check_weight(body, height, constants)?;
@@ -127,16 +163,34 @@ impl AggregateBodyInternalConsistencyValidator {
         check_maturity(height, body.inputs())?;
         check_kernel_lock_height(height, body.kernels())?;
 
-        let total_offset = self.factories.commitment.commit_value(tx_offset, total_reward.0);
-        validate_kernel_sum(body, total_offset, &self.factories.commitment)?;
+        check_balance_only(
+            body.inputs(),
+            body.outputs(),
+            body.kernels(),
+            tx_offset,
+            total_reward,
+            &self.factories,
+        )?;
 
+        validate_revealed_values(body)?;
         if !self.bypass_range_proof_verification {
             validate_range_proofs(body, &self.factories.range_proof)?;
         }
-        verify_metadata_signatures(body)?;
+        if self.batch_metadata_signature_verification {
+            batch_verify_output_metadata_signatures(body)?;
+        } else {
+            verify_metadata_signatures(body)?;
+        }
 
         let script_offset_g = PublicKey::from_secret_key(script_offset);
-        validate_script_and_script_offset(body, script_offset_g, &self.factories.commitment, prev_header, height)?;
+        validate_script_and_script_offset(
+            body,
+            script_offset_g,
+            &self.factories.commitment,
+            prev_header,
+            height,
+            constants.reject_identity_sender_offset_keys(),
+        )?;
         validate_covenants(body, height)?;
 
         check_total_burned(body)?;
@@ -145,6 +199,65 @@ impl AggregateBodyInternalConsistencyValidator {
     }
 }
 
+/// Check a single output of a block at `height` against the consensus rules that apply to it on its own
+pub(super) fn check_output_consensus_rules(
+    constants: &ConsensusConstants,
+    height: u64,
+    output: &TransactionOutput,
+) -> Result<(), ValidationError> {
+    check_permitted_output_types(constants, output)?;
+    check_script_size(output, constants.max_script_byte_size())?;
+    check_encrypted_data_byte_size(output, constants.max_extra_encrypted_data_byte_size())?;
+    check_covenant_length(&output.covenant, constants.max_covenant_length())?;
+    check_permitted_range_proof_types(constants, output)?;
+    check_validator_node_registration_utxo(constants, output)?;
+    check_min_output_value(constants, output)?;
+    check_output_maturity(constants, height, output)?;
+    Ok(())
+}
+
+/// Validate the checks of this aggregate body that do not depend on any network's consensus rules:
+/// 1. Kernel and metadata signatures are valid
+/// 1. Inputs, outputs and kernels are sorted and unique
+/// 1. The non-coinbase inputs, outputs and fees balance against their kernels and the offset
+/// 1. Range proofs of the outputs are valid
+/// 1. Burned outputs and burn kernels match up
+///
+/// The coinbase outputs and kernel are excluded from the balance, because their value depends on the emission
+/// schedule of the network.
+pub(crate) fn validate_network_agnostic_consistency(
+    body: &AggregateBody,
+    tx_offset: &PrivateKey,
+    factories: &CryptoFactories,
+    bypass_range_proof_verification: bool,
+) -> Result<(), ValidationError> {
+    verify_kernel_signatures(body)?;
+    check_sorting_and_duplicates(body)?;
+
+    let non_coinbase_body = AggregateBody::new_sorted_unchecked(
+        body.inputs().clone(),
+        body.outputs().iter().filter(|o| !o.is_coinbase()).cloned().collect(),
+        body.kernels().iter().filter(|k| !k.is_coinbase()).cloned().collect(),
+    );
+    check_balance_only(
+        non_coinbase_body.inputs(),
+        non_coinbase_body.outputs(),
+        non_coinbase_body.kernels(),
+        tx_offset,
+        MicroMinotari::zero(),
+        factories,
+    )?;
+
+    validate_revealed_values(body)?;
+    if !bypass_range_proof_verification {
+        validate_range_proofs(body, &factories.range_proof)?;
+    }
+    verify_metadata_signatures(body)?;
+    check_total_burned(body)?;
+
+    Ok(())
+}
+
 /// Verify the signatures in all kernels contained in this aggregate body. Clients must provide an offset that
 /// will be added to the public key used in the signature verification.
 fn verify_kernel_signatures(body: &AggregateBody) -> Result<(), ValidationError> {
@@ -201,81 +314,115 @@ fn check_sorting_and_duplicates(body: &AggregateBody) -> Result<(), ValidationEr
     Ok(())
 }
 
-/// Confirm that the (sum of the outputs) - (sum of inputs) = Kernel excess
-///
-/// The offset_and_reward commitment includes the offset & the total coinbase reward (block reward + fees for
-/// block balances, or zero for transaction balances)
-fn validate_kernel_sum(
-    body: &AggregateBody,
-    offset_and_reward: Commitment,
-    factory: &CommitmentFactory,
-) -> Result<(), ValidationError> {
-    trace!(target: LOG_TARGET, "Checking kernel total");
-    let KernelSum { sum: excess, fees } = sum_kernels(body, offset_and_reward)?;
-    let sum_io = sum_commitments(body)?;
-    trace!(target: LOG_TARGET, "Total outputs - inputs:{}", sum_io.to_hex());
-    let fees = factory.commit_value(&PrivateKey::default(), fees.into());
-    trace!(
-        target: LOG_TARGET,
-        "Comparing sum.  excess:{} == sum {} + fees {}",
-        excess.to_hex(),
-        sum_io.to_hex(),
-        fees.to_hex()
-    );
-    if excess != &sum_io + &fees {
-        return Err(ValidationError::InvalidAccountingBalance);
-    }
-
-    Ok(())
-}
-/// Calculate the sum of the kernels, taking into account the provided offset, and their constituent fees
-fn sum_kernels(body: &AggregateBody, offset_with_fee: PedersenCommitment) -> Result<KernelSum, ValidationError> {
-    // Sum all kernel excesses and fees
-    let mut kernel_sum = KernelSum {
-        fees: MicroMinotari(0),
-        sum: offset_with_fee,
-    };
-    for kernel in body.kernels() {
-        kernel_sum.fees = kernel_sum
-            .fees
-            .checked_add(kernel.fee)
-            .ok_or(ValidationError::InvalidAccountingBalance)?;
-        kernel_sum.sum = &kernel_sum.sum + &kernel.excess;
+/// Verifies the bulletproof+ range proofs of the outputs. Outputs with a revealed value have no range proof and are
+/// checked by [validate_revealed_values] instead.
+fn validate_range_proofs(body: &AggregateBody, range_proof_service: &RangeProofService) -> Result<(), ValidationError> {
+    trace!(target: LOG_TARGET, "Checking range proofs");
+    let outputs = body
+        .outputs()
+        .iter()
+        .enumerate()
+        .filter(|(_, o)| o.features.range_proof_type == RangeProofType::BulletProofPlus)
+        .collect::<Vec<_>>();
+    let batch_result = catch_verification_panic(None, || {
+        let proofs = outputs.iter().map(|(_, o)| *o).collect::<Vec<_>>();
+        batch_verify_range_proofs(range_proof_service, &proofs).map_err(|e| TransactionError::from(e).into())
+    });
+    if let Err(ValidationError::OutputVerificationPanic { .. }) = batch_result {
+        // The batch does not tell which proof made it panic, so look for it by verifying the proofs one at a time
+        for (index, output) in &outputs {
+            catch_verification_panic(Some(*index), || {
+                output.verify_range_proof(range_proof_service).map_err(Into::into)
+            })?;
+        }
     }
-    Ok(kernel_sum)
+    batch_result
 }
 
-/// Calculate the sum of the outputs - inputs
-fn sum_commitments(body: &AggregateBody) -> Result<Commitment, ValidationError> {
-    let sum_inputs = body
-        .inputs()
-        .iter()
-        .map(|i| i.commitment())
-        .collect::<Result<Vec<&Commitment>, _>>()?
-        .into_iter()
-        .sum::<Commitment>();
-    let sum_outputs = body.outputs().iter().map(|o| &o.commitment).sum::<Commitment>();
-    Ok(&sum_outputs - &sum_inputs)
+/// Checks that the value of every output with a revealed value matches its commitment. This is cheap compared to a
+/// range proof, so it is done even when range proof verification is bypassed.
+fn validate_revealed_values(body: &AggregateBody) -> Result<(), ValidationError> {
+    trace!(target: LOG_TARGET, "Checking revealed values");
+    for (index, output) in body.outputs().iter().enumerate() {
+        if output.features.range_proof_type != RangeProofType::RevealedValue {
+            continue;
+        }
+        catch_verification_panic(Some(index), || {
+            output.revealed_value_range_proof_check().map_err(|e| {
+                warn!(
+                    target: LOG_TARGET,
+                    "Revealed value of output {} does not match its commitment: {}", index, e
+                );
+                ValidationError::RevealedValueMismatch { index }
+            })
+        })?;
+    }
+    Ok(())
 }
 
-fn validate_range_proofs(
+/// Check that the value and mask encrypted for the holder of `view_key` open the output commitment. Interactive outputs
+/// are encrypted with the view key and one-sided outputs with the key shared with the sender offset key, so both are
+/// tried. Outputs sent to other wallets cannot be decrypted and are skipped. The encrypted data is not covered by
+/// consensus, so this is not part of validation.
+pub(crate) fn validate_encrypted_values(
     body: &AggregateBody,
-    range_proof_service: &RangeProofService,
-) -> Result<(), TransactionError> {
-    trace!(target: LOG_TARGET, "Checking range proofs");
-    let outputs = body.outputs().iter().collect::<Vec<_>>();
-    batch_verify_range_proofs(range_proof_service, &outputs)?;
+    view_key: &PrivateKey,
+    factory: &CommitmentFactory,
+) -> Result<(), ValidationError> {
+    trace!(target: LOG_TARGET, "Checking encrypted values");
+    for (index, output) in body.outputs().iter().enumerate() {
+        let decrypted =
+            EncryptedData::decrypt_data(view_key, output.commitment(), output.encrypted_data()).or_else(|_| {
+                let shared_secret = CommsDHKE::new(view_key, &output.sender_offset_public_key);
+                let encryption_key = shared_secret_to_output_encryption_key(&shared_secret)?;
+                EncryptedData::decrypt_data(&encryption_key, output.commitment(), output.encrypted_data())
+            });
+        let (value, mask, _) = match decrypted {
+            Ok(opening) => opening,
+            // The output belongs to another wallet
+            Err(_) => continue,
+        };
+        if factory.commit_value(&mask, value.as_u64()) != *output.commitment() {
+            warn!(
+                target: LOG_TARGET,
+                "Encrypted value of output {} does not match its commitment", index
+            );
+            return Err(ValidationError::EncryptedValueMismatch { index });
+        }
+    }
     Ok(())
 }
 
 fn verify_metadata_signatures(body: &AggregateBody) -> Result<(), ValidationError> {
     trace!(target: LOG_TARGET, "Checking sender signatures");
-    for o in body.outputs() {
-        o.verify_metadata_signature()?;
+    for (index, o) in body.outputs().iter().enumerate() {
+        catch_verification_panic(Some(index), || o.verify_metadata_signature().map_err(Into::into))?;
     }
     Ok(())
 }
 
+fn batch_verify_output_metadata_signatures(body: &AggregateBody) -> Result<(), ValidationError> {
+    trace!(target: LOG_TARGET, "Checking sender signatures in a batch");
+    let batch_result = catch_verification_panic(None, || {
+        let outputs = body.outputs().iter().collect::<Vec<_>>();
+        batch_verify_metadata_signatures(&outputs).map_err(Into::into)
+    });
+    if batch_result.is_err() {
+        // The batch does not tell which signature is invalid, so look for it by verifying them one at a time
+        verify_metadata_signatures(body)?;
+    }
+    batch_result
+}
+
+/// Turns a panic while verifying the output at `index` (e.g. on corrupt data) into an error that identifies the output
+pub(super) fn catch_verification_panic<F>(index: Option<usize>, verify: F) -> Result<(), ValidationError>
+where F: FnOnce() -> Result<(), ValidationError> {
+    panic::catch_unwind(AssertUnwindSafe(verify)).unwrap_or_else(|_| {
+        warn!(target: LOG_TARGET, "Output verification panicked (output index: {:?})", index);
+        Err(ValidationError::OutputVerificationPanic { index })
+    })
+}
+
 /// this will validate the script and script offset of the aggregate body.
 fn validate_script_and_script_offset(
     body: &AggregateBody,
@@ -283,6 +430,7 @@ fn validate_script_and_script_offset(
     factory: &CommitmentFactory,
     prev_header: Option<HashOutput>,
     height: u64,
+    reject_identity_sender_offset_keys: bool,
 ) -> Result<(), ValidationError> {
     trace!(target: LOG_TARGET, "Checking script and script offset");
     // lets count up the input script public keys
@@ -295,9 +443,17 @@ fn validate_script_and_script_offset(
 
     // Now lets gather the output public keys and hashes.
     let mut output_keys = PublicKey::default();
-    for output in body.outputs() {
+    for (index, output) in body.outputs().iter().enumerate() {
         // We should not count the coinbase tx here
         if !output.is_coinbase() {
+            // An identity key contributes nothing to the offset, so catch it here rather than as an offset mismatch
+            if reject_identity_sender_offset_keys && output.sender_offset_public_key == PublicKey::default() {
+                warn!(
+                    target: LOG_TARGET,
+                    "Output {} has an identity sender offset public key", index
+                );
+                return Err(ValidationError::InvalidSenderOffsetKey { index });
+            }
             output_keys = output_keys + output.sender_offset_public_key.clone();
         }
     }
@@ -449,9 +605,18 @@ mod test {
     use crate::{
         covenants::Covenant,
         transactions::{
-            key_manager::create_memory_db_key_manager,
+            key_manager::{
+                create_memory_db_key_manager,
+                SecretTransactionKeyManagerInterface,
+                TransactionKeyManagerInterface,
+            },
             test_helpers,
-            transaction_components::{KernelFeatures, OutputFeatures, TransactionInputVersion},
+            transaction_components::{
+                encrypted_data::PaymentId,
+                KernelFeatures,
+                OutputFeatures,
+                TransactionInputVersion,
+            },
         },
     };
 
@@ -564,6 +729,61 @@ mod test {
         assert!(check_total_burned(&body2).is_err());
     }
 
+    #[tokio::test]
+    async fn it_checks_the_encrypted_values_the_view_key_can_decrypt() {
+        let key_manager = create_memory_db_key_manager().unwrap();
+        let (mut output, commitment_mask_key_id, _) = test_helpers::create_utxo(
+            100.into(),
+            &key_manager,
+            &OutputFeatures::default(),
+            &script!(Nop),
+            &Covenant::default(),
+            0.into(),
+        )
+        .await;
+        let view_key_id = key_manager.get_view_key().await.unwrap().key_id;
+        let view_key = key_manager.get_private_key(&view_key_id).await.unwrap();
+        let factory = CommitmentFactory::default();
+
+        let body = AggregateBody::new(Vec::new(), vec![output.clone()], Vec::new());
+        validate_encrypted_values(&body, &view_key, &factory).unwrap();
+        // The output belongs to another wallet, so it is skipped
+        validate_encrypted_values(&body, &PrivateKey::from(1u64), &factory).unwrap();
+
+        let mask = key_manager.get_private_key(&commitment_mask_key_id).await.unwrap();
+        output.encrypted_data =
+            EncryptedData::encrypt_data(&view_key, &output.commitment, 101.into(), &mask, PaymentId::Empty).unwrap();
+        let body = AggregateBody::new(Vec::new(), vec![output], Vec::new());
+        let err = validate_encrypted_values(&body, &view_key, &factory).unwrap_err();
+        assert!(matches!(err, ValidationError::EncryptedValueMismatch { index: 0 }));
+    }
+
+    #[tokio::test]
+    async fn it_rejects_identity_sender_offset_keys() {
+        let key_manager = create_memory_db_key_manager().unwrap();
+        let (mut output, _, _) = test_helpers::create_utxo(
+            100.into(),
+            &key_manager,
+            &OutputFeatures::default(),
+            &script!(Nop),
+            &Covenant::default(),
+            0.into(),
+        )
+        .await;
+        let factory = CommitmentFactory::default();
+
+        let script_offset = PublicKey::default() - output.sender_offset_public_key.clone();
+        let body = AggregateBody::new(Vec::new(), vec![output.clone()], Vec::new());
+        validate_script_and_script_offset(&body, script_offset, &factory, None, 0, true).unwrap();
+
+        output.sender_offset_public_key = PublicKey::default();
+        let body = AggregateBody::new(Vec::new(), vec![output], Vec::new());
+        let err = validate_script_and_script_offset(&body, PublicKey::default(), &factory, None, 0, true).unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidSenderOffsetKey { index: 0 }));
+        // The rule is not active unless the consensus constants enable it
+        validate_script_and_script_offset(&body, PublicKey::default(), &factory, None, 0, false).unwrap();
+    }
+
     mod transaction_ordering {
         use super::*;
 
@@ -613,4 +833,58 @@ mod test {
             assert!(matches!(err, ValidationError::UnsortedOrDuplicateOutput));
         }
     }
+
+    mod catch_verification_panic {
+        use tari_test_utils::unpack_enum;
+
+        use super::*;
+
+        #[test]
+        fn it_reports_the_index_of_the_output_that_panicked() {
+            let outputs: Vec<&[u8]> = vec![&[1, 2, 3], &[4, 5, 6], &[]];
+            let err = outputs
+                .iter()
+                .enumerate()
+                .try_for_each(|(index, data)| {
+                    catch_verification_panic(Some(index), || {
+                        // Corrupt (truncated) data makes the verifier index out of bounds
+                        let _last = data[2];
+                        Ok(())
+                    })
+                })
+                .unwrap_err();
+
+            unpack_enum!(ValidationError::OutputVerificationPanic { index } = err);
+            assert_eq!(index, Some(2));
+        }
+
+        #[test]
+        fn it_passes_through_verification_results() {
+            catch_verification_panic(Some(0), || Ok(())).unwrap();
+            let err = catch_verification_panic(Some(0), || Err(ValidationError::InvalidAccountingBalance)).unwrap_err();
+            assert!(matches!(err, ValidationError::InvalidAccountingBalance));
+        }
+    }
+
+    mod validate_revealed_values {
+        use super::*;
+        use crate::transactions::test_helpers::TestParams;
+
+        #[tokio::test]
+        async fn it_checks_the_revealed_value_against_the_commitment() {
+            let key_manager = create_memory_db_key_manager().unwrap();
+            let test_params = TestParams::new(&key_manager).await;
+            let coinbase =
+                test_helpers::create_coinbase_wallet_output(&test_params, 1, None, RangeProofType::RevealedValue).await;
+            let mut output = coinbase.to_transaction_output(&key_manager).await.unwrap();
+
+            let body = AggregateBody::new(vec![], vec![output.clone()], vec![]);
+            validate_revealed_values(&body).unwrap();
+
+            output.minimum_value_promise = output.minimum_value_promise + MicroMinotari::from(1);
+            let body = AggregateBody::new(vec![], vec![output], vec![]);
+            let err = validate_revealed_values(&body).unwrap_err();
+            assert!(matches!(err, ValidationError::RevealedValueMismatch { index: 0 }));
+        }
+    }
 }
@@ -0,0 +1,63 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use tari_common_types::types::HashOutput;
+
+/// What was learned about a body while validating it, returned along with the validated body. Each call to validate a
+/// body returns its own details, so bodies can be validated at the same time with one validator.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BodyValidationDetails {
+    utxo_lookups: u64,
+    spent_output_hashes: Option<Vec<HashOutput>>,
+    mempool_spent_inputs: Option<Vec<HashOutput>>,
+}
+
+impl BodyValidationDetails {
+    pub(crate) fn new(
+        utxo_lookups: u64,
+        spent_output_hashes: Option<Vec<HashOutput>>,
+        mempool_spent_inputs: Option<Vec<HashOutput>>,
+    ) -> Self {
+        Self {
+            utxo_lookups,
+            spent_output_hashes,
+            mempool_spent_inputs,
+        }
+    }
+
+    /// The number of UTXO set lookups made against the database while validating the body
+    pub fn utxo_lookups(&self) -> u64 {
+        self.utxo_lookups
+    }
+
+    /// The hashes of the outputs spent by the inputs of the body, in input order, or `None` if spent output recording
+    /// is not enabled
+    pub fn spent_output_hashes(&self) -> Option<&[HashOutput]> {
+        self.spent_output_hashes.as_deref()
+    }
+
+    /// The hashes of the outputs spent by the inputs of the body that are also spent in the mempool, in input order, or
+    /// `None` if no mempool spent set is configured. The mempool transactions that spend them conflict with the body.
+    pub fn mempool_spent_inputs(&self) -> Option<&[HashOutput]> {
+        self.mempool_spent_inputs.as_deref()
+    }
+}
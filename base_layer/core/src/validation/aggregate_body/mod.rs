@@ -22,5 +22,16 @@
 
 mod aggregate_body_internal_validator;
 pub use aggregate_body_internal_validator::AggregateBodyInternalConsistencyValidator;
+pub(crate) use aggregate_body_internal_validator::{validate_encrypted_values, validate_network_agnostic_consistency};
 mod aggregate_body_chain_validator;
 pub use aggregate_body_chain_validator::AggregateBodyChainLinkedValidator;
+mod body_validation_details;
+pub use body_validation_details::BodyValidationDetails;
+mod mempool_spent_set;
+pub use mempool_spent_set::MempoolSpentSet;
+mod output_validator_session;
+pub use output_validator_session::{OutputValidationData, OutputValidatorSession};
+mod partial_input_validation;
+pub use partial_input_validation::PartialInputValidation;
+mod utxo_filter;
+pub use utxo_filter::{UtxoBloomFilter, UtxoFilter};
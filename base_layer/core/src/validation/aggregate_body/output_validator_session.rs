@@ -0,0 +1,186 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashSet, sync::Arc};
+
+use tari_common_types::types::{Commitment, PublicKey, RangeProofService};
+
+use super::aggregate_body_internal_validator::{catch_verification_panic, check_output_consensus_rules};
+use crate::{
+    consensus::{ConsensusConstants, ConsensusManager},
+    transactions::{transaction_components::TransactionOutput, CryptoFactories},
+    validation::{helpers::validate_output_version, ValidationError},
+};
+
+/// The aggregate of the outputs accepted by an [OutputValidatorSession]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OutputValidationData {
+    /// The number of outputs that were validated
+    pub num_outputs: usize,
+    /// The sum of the output commitments
+    pub commitment_sum: Commitment,
+    /// The sum of the sender offset public keys of the outputs other than coinbase outputs, as used in the script
+    /// offset check
+    pub sender_offset_public_key_sum: PublicKey,
+}
+
+/// Validates outputs one at a time as they are added, e.g. while a block is being assembled, so that they do not all
+/// have to be validated in one pass when the block is submitted.
+///
+/// Every output is checked against the per-output consensus rules, its range proof and metadata signature are
+/// verified, and it is rejected if its commitment was already added to the session. The checks that need the whole
+/// body (sorting, weight, kernel sum and script offset) are not done here.
+pub struct OutputValidatorSession {
+    consensus_constants: ConsensusConstants,
+    height: u64,
+    range_proof_service: Arc<RangeProofService>,
+    bypass_range_proof_verification: bool,
+    commitments: HashSet<Commitment>,
+    commitment_sum: Commitment,
+    sender_offset_public_key_sum: PublicKey,
+}
+
+impl OutputValidatorSession {
+    pub fn new(
+        consensus_manager: &ConsensusManager,
+        height: u64,
+        factories: &CryptoFactories,
+        bypass_range_proof_verification: bool,
+    ) -> Self {
+        Self {
+            consensus_constants: consensus_manager.consensus_constants(height).clone(),
+            height,
+            range_proof_service: factories.range_proof.clone(),
+            bypass_range_proof_verification,
+            commitments: HashSet::new(),
+            commitment_sum: Commitment::default(),
+            sender_offset_public_key_sum: PublicKey::default(),
+        }
+    }
+
+    /// Validates the output and adds it to the session. An output that fails validation is not added.
+    pub fn add_output(&mut self, output: &TransactionOutput) -> Result<(), ValidationError> {
+        let index = Some(self.commitments.len());
+        if self.commitments.contains(&output.commitment) {
+            return Err(ValidationError::UnsortedOrDuplicateOutput);
+        }
+        validate_output_version(&self.consensus_constants, output)?;
+        check_output_consensus_rules(&self.consensus_constants, self.height, output)?;
+        if !self.bypass_range_proof_verification {
+            catch_verification_panic(index, || {
+                output.verify_range_proof(&self.range_proof_service).map_err(Into::into)
+            })?;
+        }
+        catch_verification_panic(index, || output.verify_metadata_signature().map_err(Into::into))?;
+
+        self.commitment_sum = &self.commitment_sum + &output.commitment;
+        // The script offset check does not count coinbase outputs
+        if !output.is_coinbase() {
+            self.sender_offset_public_key_sum = &self.sender_offset_public_key_sum + &output.sender_offset_public_key;
+        }
+        self.commitments.insert(output.commitment.clone());
+        Ok(())
+    }
+
+    /// Returns the number of outputs accepted so far
+    pub fn num_outputs(&self) -> usize {
+        self.commitments.len()
+    }
+
+    pub fn finalize(self) -> OutputValidationData {
+        OutputValidationData {
+            num_outputs: self.commitments.len(),
+            commitment_sum: self.commitment_sum,
+            sender_offset_public_key_sum: self.sender_offset_public_key_sum,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use tari_common::configuration::Network;
+    use tari_script::script;
+
+    use super::*;
+    use crate::{
+        covenants::Covenant,
+        transactions::{
+            key_manager::create_memory_db_key_manager,
+            test_helpers,
+            transaction_components::{OutputFeatures, RangeProofType},
+        },
+    };
+
+    #[tokio::test]
+    async fn it_accumulates_the_outputs_it_accepts() {
+        let key_manager = create_memory_db_key_manager().unwrap();
+        let mut outputs = Vec::new();
+        for value in [100, 101] {
+            let (output, _, _) = test_helpers::create_utxo(
+                value.into(),
+                &key_manager,
+                &OutputFeatures::default(),
+                &script!(Nop),
+                &Covenant::default(),
+                0.into(),
+            )
+            .await;
+            outputs.push(output);
+        }
+
+        let rules = ConsensusManager::builder(Network::LocalNet).build().unwrap();
+        let factories = CryptoFactories::default();
+        let mut session = OutputValidatorSession::new(&rules, 1, &factories, false);
+        for output in &outputs {
+            session.add_output(output).unwrap();
+        }
+        let err = session.add_output(&outputs[0]).unwrap_err();
+        assert!(matches!(err, ValidationError::UnsortedOrDuplicateOutput));
+
+        let mut tampered = outputs[1].clone();
+        tampered.sender_offset_public_key = PublicKey::default();
+        assert!(session.add_output(&tampered).is_err());
+
+        let maturity = 1 + rules.consensus_constants(1).coinbase_min_maturity();
+        let (coinbase, _, _) = test_helpers::create_utxo(
+            rules.get_block_reward_at(1),
+            &key_manager,
+            &OutputFeatures::create_coinbase(maturity, None, RangeProofType::BulletProofPlus),
+            &script!(Nop),
+            &Covenant::default(),
+            0.into(),
+        )
+        .await;
+        session.add_output(&coinbase).unwrap();
+
+        let data = session.finalize();
+        assert_eq!(data.num_outputs, 3);
+        assert_eq!(
+            data.commitment_sum,
+            &(&outputs[0].commitment + &outputs[1].commitment) + &coinbase.commitment
+        );
+        assert_eq!(
+            data.sender_offset_public_key_sum,
+            &outputs[0].sender_offset_public_key + &outputs[1].sender_offset_public_key
+        );
+    }
+}
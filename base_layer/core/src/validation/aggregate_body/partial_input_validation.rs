@@ -0,0 +1,89 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use tari_common_types::types::{Commitment, HashOutput};
+
+use crate::{
+    transactions::{aggregated_body::AggregateBody, transaction_components::TransactionError},
+    validation::ValidationError,
+};
+
+/// The outcome of validating a body in best-effort mode, where inputs that spend unknown outputs do not fail
+/// validation. See `AggregateBodyChainLinkedValidator::validate_best_effort`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialInputValidation {
+    body: AggregateBody,
+    unknown_inputs: Vec<HashOutput>,
+    input_commitment_sum: Option<Commitment>,
+}
+
+impl PartialInputValidation {
+    pub(super) fn new(body: AggregateBody, unknown_inputs: Vec<HashOutput>) -> Result<Self, TransactionError> {
+        let input_commitment_sum = if unknown_inputs.is_empty() {
+            let sum = body.inputs().iter().try_fold(Commitment::default(), |sum, input| {
+                Ok::<_, TransactionError>(&sum + input.commitment()?)
+            })?;
+            Some(sum)
+        } else {
+            None
+        };
+        Ok(Self {
+            body,
+            unknown_inputs,
+            input_commitment_sum,
+        })
+    }
+
+    /// Returns true if every input was found, in which case the body passed the same checks as in strict mode
+    pub fn is_complete(&self) -> bool {
+        self.unknown_inputs.is_empty()
+    }
+
+    /// The body as far as it could be validated. Inputs that are unknown are left as the body gave them, and must not
+    /// be taken as checked.
+    pub fn body(&self) -> &AggregateBody {
+        &self.body
+    }
+
+    /// The hashes of the outputs spent by inputs that are neither in the UTXO set nor created by the body, in input
+    /// order. None of the checks that need the spent output, such as maturity, were run for these inputs.
+    pub fn unknown_inputs(&self) -> &[HashOutput] {
+        &self.unknown_inputs
+    }
+
+    /// The sum of the input commitments, or `None` if any input is unknown. A sum that leaves out the outputs the
+    /// unknown inputs spend cannot be balanced against the outputs and kernels, and neither can any other aggregate
+    /// over the inputs, such as their script keys.
+    pub fn input_commitment_sum(&self) -> Option<&Commitment> {
+        self.input_commitment_sum.as_ref()
+    }
+
+    /// Returns the validated body, as strict validation would have returned it, or
+    /// [ValidationError::UnknownInputs] if any input is unknown
+    pub fn into_body(self) -> Result<AggregateBody, ValidationError> {
+        if self.is_complete() {
+            Ok(self.body)
+        } else {
+            Err(ValidationError::UnknownInputs(self.unknown_inputs))
+        }
+    }
+}
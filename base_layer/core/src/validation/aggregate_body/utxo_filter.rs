@@ -0,0 +1,120 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tari_common_types::types::HashOutput;
+
+/// A probabilistic filter over the hashes of the outputs in the UTXO set. Validators consult it before looking an
+/// input up in the database: an output the filter does not contain is certainly not in the UTXO set, so the lookup is
+/// skipped.
+///
+/// False positives are allowed and fall through to the database lookup, but a false negative would reject a valid
+/// input, so a filter must contain every unspent output. The caller owns the filter and keeps it up to date as blocks
+/// are committed.
+pub trait UtxoFilter: Send + Sync {
+    /// Returns false only if the output with this hash is not in the UTXO set
+    fn may_contain(&self, output_hash: &HashOutput) -> bool;
+}
+
+/// A Bloom filter [UtxoFilter]. Outputs can be added while the filter is shared with validators, but not removed, so
+/// spent outputs stay in the filter as false positives until it is rebuilt.
+pub struct UtxoBloomFilter {
+    bits: Vec<AtomicU64>,
+    num_hashes: u32,
+}
+
+impl UtxoBloomFilter {
+    /// Creates an empty filter sized for `expected_items` outputs with a false positive rate of about 1% at that size
+    pub fn new(expected_items: usize) -> Self {
+        // About 9.6 bits and 7 hash functions per item give a false positive rate of 1%
+        let num_bits = expected_items.saturating_mul(10).max(64);
+        Self::with_size(num_bits, 7)
+    }
+
+    /// Creates an empty filter with at least `num_bits` bits (rounded up to a multiple of 64) and `num_hashes` hash
+    /// functions
+    pub fn with_size(num_bits: usize, num_hashes: u32) -> Self {
+        let num_words = num_bits.div_ceil(64).max(1);
+        Self {
+            bits: (0..num_words).map(|_| AtomicU64::new(0)).collect(),
+            num_hashes: num_hashes.max(1),
+        }
+    }
+
+    /// Adds the output with this hash, e.g. when a block that creates it is committed
+    pub fn insert(&self, output_hash: &HashOutput) {
+        for index in self.bit_indexes(output_hash) {
+            self.bits[index / 64].fetch_or(1 << (index % 64), Ordering::Relaxed);
+        }
+    }
+
+    fn bit_indexes(&self, output_hash: &HashOutput) -> impl Iterator<Item = usize> {
+        // Output hashes are uniformly distributed, so their bytes can be used as the hash functions directly. The
+        // indexes are derived from two of them by double hashing.
+        let bytes = output_hash.as_slice();
+        let h1 = u64::from_le_bytes(std::array::from_fn(|i| bytes[i]));
+        let h2 = u64::from_le_bytes(std::array::from_fn(|i| bytes[8 + i])) | 1;
+        let num_bits = (self.bits.len() * 64) as u64;
+        #[allow(clippy::cast_possible_truncation)]
+        (0..u64::from(self.num_hashes)).map(move |i| (h1.wrapping_add(i.wrapping_mul(h2)) % num_bits) as usize)
+    }
+}
+
+impl UtxoFilter for UtxoBloomFilter {
+    fn may_contain(&self, output_hash: &HashOutput) -> bool {
+        self.bit_indexes(output_hash)
+            .all(|index| self.bits[index / 64].load(Ordering::Relaxed) & (1 << (index % 64)) != 0)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use rand::{rngs::OsRng, RngCore};
+
+    use super::*;
+
+    fn random_hash() -> HashOutput {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        HashOutput::from(bytes)
+    }
+
+    #[test]
+    fn it_contains_every_inserted_output() {
+        let filter = UtxoBloomFilter::new(1000);
+        let hashes = (0..1000).map(|_| random_hash()).collect::<Vec<_>>();
+        for hash in &hashes {
+            filter.insert(hash);
+        }
+        assert!(hashes.iter().all(|hash| filter.may_contain(hash)));
+
+        let false_positives = (0..1000).filter(|_| filter.may_contain(&random_hash())).count();
+        assert!(false_positives < 50, "{} false positives", false_positives);
+    }
+
+    #[test]
+    fn it_contains_nothing_when_empty() {
+        let filter = UtxoBloomFilter::new(10);
+        assert!(!filter.may_contain(&random_hash()));
+    }
+}
@@ -20,49 +20,281 @@
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::sync::{Arc, RwLock};
+use std::{
+    panic,
+    slice,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+        Mutex,
+        MutexGuard,
+        RwLock,
+        RwLockWriteGuard,
+    },
+    thread,
+    time::{Duration, Instant},
+};
 
-use log::error;
-use tari_common_types::chain_metadata::ChainMetadata;
+use log::{error, warn};
+use tari_common_types::{
+    chain_metadata::ChainMetadata,
+    types::{BlockHash, PrivateKey},
+};
 use tari_utilities::hex::Hex;
 
-use super::BlockBodyInternalConsistencyValidator;
+use super::{
+    validated_block_cache::ValidatedBlockCache,
+    BlockBodyInternalConsistencyValidator,
+    PhaseTimings,
+    ValidationReport,
+    ValidationStatsCollector,
+};
 use crate::{
     blocks::{Block, ChainBlock},
-    chain_storage::{self, BlockchainBackend, ChainStorageError},
+    borsh::SerializedSize,
+    chain_storage::{self, BlockAddResult, BlockchainBackend, BlockchainDatabase, ChainStorageError, MmrRoots},
     consensus::ConsensusManager,
-    transactions::CryptoFactories,
+    transactions::{aggregated_body::AggregateBody, CryptoFactories},
     validation::{
-        aggregate_body::AggregateBodyChainLinkedValidator,
-        helpers::check_mmr_roots,
+        aggregate_body::{
+            validate_encrypted_values,
+            AggregateBodyChainLinkedValidator,
+            BodyValidationDetails,
+            MempoolSpentSet,
+            UtxoFilter,
+        },
+        header::ValidatedHeader,
+        helpers::{
+            check_body_header_counts,
+            check_coinbase_counts,
+            check_header_linkage,
+            check_median_time_past,
+            check_mmr_roots,
+        },
         BlockBodyValidator,
         CandidateBlockValidator,
         ValidationError,
     },
     OutputSmt,
+    OutputSmtInclusionProof,
 };
 
 const LOG_TARGET: &str = "c::val::block_body_full_validator";
 
+/// Blocks normally validate well within a second, so only blocks that are far slower than that are logged
+pub const DEFAULT_SLOW_VALIDATION_THRESHOLD: Duration = Duration::from_secs(10);
+/// The number of blocks [BlockBodyFullValidator::validate_batch_independent] validates at the same time by default
+pub const DEFAULT_BATCH_CONCURRENCY: usize = 4;
+/// The most blocks [BlockBodyFullValidator::validate_batch_independent] validates at the same time when the available
+/// parallelism of the machine cannot be determined
+pub const MAX_BATCH_CONCURRENCY: usize = 16;
+
+/// The optional behaviour of a [BlockBodyFullValidator]. The default performs the standard consensus checks only.
+#[derive(Debug, Clone)]
+pub struct BlockBodyFullValidatorConfig {
+    /// Skip range proof verification. Only safe for blocks that have already been validated.
+    pub bypass_range_proof_verification: bool,
+    /// Skip kernel signature verification. Only safe for blocks from a trusted source, e.g. during checkpoint sync.
+    /// The kernel sum and coinbase checks still run.
+    pub bypass_kernel_signature_verification: bool,
+    /// Verify the metadata signatures of a block in a single batch, see
+    /// [BlockBodyInternalConsistencyValidator::with_batch_metadata_signature_verification]
+    pub batch_metadata_signature_verification: bool,
+    /// See [BlockBodyFullValidator::check_encrypted_values]. `None` disables the check.
+    pub encrypted_value_view_key: Option<PrivateKey>,
+    /// See [BlockBodyFullValidator::with_header_linkage_check]
+    pub check_header_linkage: bool,
+    /// See [BlockBodyFullValidator::with_body_header_count_check]
+    pub check_body_header_counts: bool,
+    /// See [BlockBodyFullValidator::with_median_timestamp_check]
+    pub check_median_timestamp: bool,
+    /// See [BlockBodyFullValidator::with_spent_output_recording]
+    pub record_spent_outputs: bool,
+    /// See [BlockBodyFullValidator::with_validated_block_cache]. `None` disables the cache.
+    pub validated_block_cache_capacity: Option<usize>,
+    /// See [BlockBodyFullValidator::with_slow_validation_threshold]
+    pub slow_validation_threshold: Duration,
+    /// See [BlockBodyFullValidator::with_batch_concurrency]
+    pub batch_concurrency: usize,
+}
+
+impl Default for BlockBodyFullValidatorConfig {
+    fn default() -> Self {
+        Self {
+            bypass_range_proof_verification: false,
+            bypass_kernel_signature_verification: false,
+            batch_metadata_signature_verification: false,
+            encrypted_value_view_key: None,
+            check_header_linkage: false,
+            check_body_header_counts: false,
+            check_median_timestamp: false,
+            record_spent_outputs: false,
+            validated_block_cache_capacity: None,
+            slow_validation_threshold: DEFAULT_SLOW_VALIDATION_THRESHOLD,
+            batch_concurrency: DEFAULT_BATCH_CONCURRENCY,
+        }
+    }
+}
+
 pub struct BlockBodyFullValidator {
     consensus_manager: ConsensusManager,
+    factories: CryptoFactories,
+    bypass_range_proof_verification: bool,
+    bypass_kernel_signature_verification: bool,
+    batch_metadata_signature_verification: bool,
+    encrypted_value_view_key: Option<PrivateKey>,
     block_internal_validator: BlockBodyInternalConsistencyValidator,
     aggregate_body_chain_validator: AggregateBodyChainLinkedValidator,
+    check_header_linkage: bool,
+    check_body_header_counts: bool,
+    check_median_timestamp: bool,
+    slow_validation_threshold: Duration,
+    batch_concurrency: usize,
+    validated_block_cache: Option<Mutex<ValidatedBlockCache>>,
+    stats_collector: Option<Arc<ValidationStatsCollector>>,
 }
 
 impl BlockBodyFullValidator {
     pub fn new(rules: ConsensusManager, bypass_range_proof_verification: bool) -> Self {
-        let factories = CryptoFactories::default();
-        let block_internal_validator =
-            BlockBodyInternalConsistencyValidator::new(rules.clone(), bypass_range_proof_verification, factories);
-        let aggregate_body_chain_validator = AggregateBodyChainLinkedValidator::new(rules.clone());
+        Self::with_factories(rules, bypass_range_proof_verification, CryptoFactories::default())
+    }
+
+    /// Creates a validator that uses the given commitment and range proof factories instead of the defaults. This is
+    /// needed for networks that are not built on the default Pedersen generators.
+    pub fn with_factories(
+        rules: ConsensusManager,
+        bypass_range_proof_verification: bool,
+        factories: CryptoFactories,
+    ) -> Self {
+        let config = BlockBodyFullValidatorConfig {
+            bypass_range_proof_verification,
+            ..Default::default()
+        };
+        Self::from_config(rules, factories, config)
+    }
+
+    /// Creates a validator with all of its optional behaviour taken from `config`
+    pub fn from_config(
+        rules: ConsensusManager,
+        factories: CryptoFactories,
+        config: BlockBodyFullValidatorConfig,
+    ) -> Self {
+        let block_internal_validator = BlockBodyInternalConsistencyValidator::new(
+            rules.clone(),
+            config.bypass_range_proof_verification,
+            factories.clone(),
+        )
+        .with_kernel_signature_verification_bypass(config.bypass_kernel_signature_verification)
+        .with_batch_metadata_signature_verification(config.batch_metadata_signature_verification);
+        let aggregate_body_chain_validator = AggregateBodyChainLinkedValidator::new(rules.clone())
+            .with_spent_output_recording(config.record_spent_outputs);
         Self {
             consensus_manager: rules,
+            factories,
+            bypass_range_proof_verification: config.bypass_range_proof_verification,
+            bypass_kernel_signature_verification: config.bypass_kernel_signature_verification,
+            batch_metadata_signature_verification: config.batch_metadata_signature_verification,
+            encrypted_value_view_key: config.encrypted_value_view_key,
             block_internal_validator,
             aggregate_body_chain_validator,
+            check_header_linkage: config.check_header_linkage,
+            check_body_header_counts: config.check_body_header_counts,
+            check_median_timestamp: config.check_median_timestamp,
+            slow_validation_threshold: config.slow_validation_threshold,
+            batch_concurrency: cap_batch_concurrency(config.batch_concurrency),
+            validated_block_cache: config
+                .validated_block_cache_capacity
+                .map(|capacity| Mutex::new(ValidatedBlockCache::new(capacity))),
+            stats_collector: None,
         }
     }
 
+    /// When enabled, blocks validated without chain metadata are first checked to link to a known header before any
+    /// of the (expensive) body validation is done.
+    pub fn with_header_linkage_check(mut self, check_header_linkage: bool) -> Self {
+        self.check_header_linkage = check_header_linkage;
+        self
+    }
+
+    /// When enabled, the number of kernels and outputs in the body is checked against the MMR sizes in the header
+    /// before any of the (expensive) body validation is done.
+    pub fn with_body_header_count_check(mut self, check_body_header_counts: bool) -> Self {
+        self.check_body_header_counts = check_body_header_counts;
+        self
+    }
+
+    /// When enabled, the header timestamp is checked to not be earlier than the median timestamp of the previous
+    /// blocks. This repeats a header validation check, so it is only needed when the header may not have been
+    /// validated.
+    pub fn with_median_timestamp_check(mut self, check_median_timestamp: bool) -> Self {
+        self.check_median_timestamp = check_median_timestamp;
+        self
+    }
+
+    /// Logs a warning with the time spent in each validation phase for every block whose body takes longer than
+    /// `threshold` to validate, whether or not it is valid. Defaults to [DEFAULT_SLOW_VALIDATION_THRESHOLD].
+    pub fn with_slow_validation_threshold(mut self, threshold: Duration) -> Self {
+        self.slow_validation_threshold = threshold;
+        self
+    }
+
+    /// Validates at most `batch_concurrency` blocks at the same time in
+    /// [validate_batch_independent](Self::validate_batch_independent). Defaults to [DEFAULT_BATCH_CONCURRENCY]. Each
+    /// block is validated on its own thread, so the concurrency is capped at the available parallelism of the machine,
+    /// or at [MAX_BATCH_CONCURRENCY] if that cannot be determined, and a warning is logged when it is capped.
+    pub fn with_batch_concurrency(mut self, batch_concurrency: usize) -> Self {
+        self.batch_concurrency = cap_batch_concurrency(batch_concurrency);
+        self
+    }
+
+    /// The number of blocks validated at the same time in
+    /// [validate_batch_independent](Self::validate_batch_independent), after capping
+    pub fn batch_concurrency(&self) -> usize {
+        self.batch_concurrency
+    }
+
+    /// When enabled, the hashes of the outputs spent by the inputs of every block validated with
+    /// [validate_with_details](Self::validate_with_details) are returned with it, e.g. to maintain a spent output
+    /// index.
+    pub fn with_spent_output_recording(mut self, record_spent_outputs: bool) -> Self {
+        self.aggregate_body_chain_validator = self
+            .aggregate_body_chain_validator
+            .with_spent_output_recording(record_spent_outputs);
+        self
+    }
+
+    /// Consults `filter` before looking inputs up in the UTXO set, see
+    /// [AggregateBodyChainLinkedValidator::with_utxo_filter]. Blocks validated with
+    /// [validate_under_rules](Self::validate_under_rules) do not use the filter.
+    pub fn with_utxo_filter(mut self, filter: Arc<dyn UtxoFilter>) -> Self {
+        self.aggregate_body_chain_validator = self.aggregate_body_chain_validator.with_utxo_filter(filter);
+        self
+    }
+
+    /// Reports the inputs of every block validated with [validate_with_details](Self::validate_with_details) that are
+    /// also spent in the mempool, see [BodyValidationDetails::mempool_spent_inputs].
+    pub fn with_mempool_spent_set(mut self, mempool_spent_set: Arc<dyn MempoolSpentSet>) -> Self {
+        self.aggregate_body_chain_validator = self
+            .aggregate_body_chain_validator
+            .with_mempool_spent_set(mempool_spent_set);
+        self
+    }
+
+    /// Keeps up to `capacity` successfully validated blocks so that a block that is received again (e.g. from
+    /// several peers) is not validated again while the chain tip is still its parent. A block is only taken from the
+    /// cache if its body is the body that was validated.
+    pub fn with_validated_block_cache(mut self, capacity: usize) -> Self {
+        self.validated_block_cache = Some(Mutex::new(ValidatedBlockCache::new(capacity)));
+        self
+    }
+
+    /// Records every block validated with [validate](Self::validate) in `collector`. Blocks are only timed while a
+    /// collector is set.
+    pub fn with_stats_collector(mut self, collector: Arc<ValidationStatsCollector>) -> Self {
+        self.stats_collector = Some(collector);
+        self
+    }
+
     pub fn validate<B: BlockchainBackend>(
         &self,
         backend: &B,
@@ -70,34 +302,441 @@ impl BlockBodyFullValidator {
         metadata_option: Option<&ChainMetadata>,
         smt: Arc<RwLock<OutputSmt>>,
     ) -> Result<Block, ValidationError> {
-        if let Some(metadata) = metadata_option {
-            validate_block_metadata(block, metadata)?;
+        let (block, _) = self.validate_with_details(backend, block, metadata_option, smt)?;
+        Ok(block)
+    }
+
+    /// Validates the block in the same way as [validate](Self::validate) and also returns what was learned about its
+    /// body, such as the outputs its inputs spend. A block taken from the validated block cache was not looked up in
+    /// the UTXO set again, so its details count no lookups.
+    pub fn validate_with_details<B: BlockchainBackend>(
+        &self,
+        backend: &B,
+        block: &Block,
+        metadata_option: Option<&ChainMetadata>,
+        smt: Arc<RwLock<OutputSmt>>,
+    ) -> Result<(Block, BodyValidationDetails), ValidationError> {
+        let Some(collector) = &self.stats_collector else {
+            return self.validate_block(backend, block, metadata_option, smt);
+        };
+        let timer = Instant::now();
+        let result = self.validate_block(backend, block, metadata_option, smt);
+        collector.record(block, timer.elapsed(), result.is_ok());
+        result
+    }
+
+    fn validate_block<B: BlockchainBackend>(
+        &self,
+        backend: &B,
+        block: &Block,
+        metadata_option: Option<&ChainMetadata>,
+        smt: Arc<RwLock<OutputSmt>>,
+    ) -> Result<(Block, BodyValidationDetails), ValidationError> {
+        match metadata_option {
+            Some(metadata) => validate_block_metadata(block, metadata)?,
+            None if self.check_header_linkage => check_header_linkage(backend, &block.header)?,
+            None => {},
+        }
+
+        let block_hash = block.hash();
+        if let Some(block) = self.get_cached_block(backend, block, &block_hash)? {
+            // The chain validator did not see this block, so report what it would have
+            let details = self
+                .aggregate_body_chain_validator
+                .body_validation_details(0, block.body.inputs().iter().map(|i| i.output_hash()).collect());
+            return Ok((block, details));
+        }
+
+        let (block, details) = self.validate_body_with(
+            backend,
+            block,
+            smt,
+            &self.consensus_manager,
+            &self.aggregate_body_chain_validator,
+            &self.block_internal_validator,
+        )?;
+
+        if let Some(cache) = &self.validated_block_cache {
+            lock_cache(cache)?.insert(block_hash, block.clone());
+        }
+
+        Ok((block, details))
+    }
+
+    /// Validates the block in the same way as [validate](Self::validate), but under the given consensus rules instead
+    /// of the validator's own. This allows a block to be tested against alternative rule sets, e.g. the rules before
+    /// and after a hard fork. The validated block cache is neither read nor updated, since its entries are only valid
+    /// under the validator's own rules.
+    pub fn validate_under_rules<B: BlockchainBackend>(
+        &self,
+        backend: &B,
+        block: &Block,
+        metadata_option: Option<&ChainMetadata>,
+        smt: Arc<RwLock<OutputSmt>>,
+        rules: &ConsensusManager,
+    ) -> Result<Block, ValidationError> {
+        match metadata_option {
+            Some(metadata) => validate_block_metadata(block, metadata)?,
+            None if self.check_header_linkage => check_header_linkage(backend, &block.header)?,
+            None => {},
+        }
+
+        let aggregate_body_chain_validator = AggregateBodyChainLinkedValidator::new(rules.clone());
+        let block_internal_validator = BlockBodyInternalConsistencyValidator::new(
+            rules.clone(),
+            self.bypass_range_proof_verification,
+            self.factories.clone(),
+        )
+        .with_kernel_signature_verification_bypass(self.bypass_kernel_signature_verification)
+        .with_batch_metadata_signature_verification(self.batch_metadata_signature_verification);
+        let (block, _) = self.validate_body_with(
+            backend,
+            block,
+            smt,
+            rules,
+            &aggregate_body_chain_validator,
+            &block_internal_validator,
+        )?;
+        Ok(block)
+    }
+
+    /// Validates blocks that do not build on each other, e.g. competing tips, in the same way as
+    /// [validate](Self::validate) and returns the result of each block in the order the blocks were given. Every block
+    /// is validated against the current state of the database, so a block that spends an output of another block in
+    /// the batch is rejected. Up to [with_batch_concurrency](Self::with_batch_concurrency) blocks are validated at the
+    /// same time, although the MMR roots of one block at a time are checked since they need the SMT write lock. Each
+    /// block is returned with its details, as [validate_with_details](Self::validate_with_details) returns them.
+    pub fn validate_batch_independent<B: BlockchainBackend>(
+        &self,
+        backend: &B,
+        blocks: Vec<Block>,
+        smt: Arc<RwLock<OutputSmt>>,
+    ) -> Vec<Result<(Block, BodyValidationDetails), ValidationError>> {
+        let next_block = AtomicUsize::new(0);
+        let num_workers = self.batch_concurrency.clamp(1, blocks.len().max(1));
+        let mut results = Vec::with_capacity(blocks.len());
+        results.resize_with(blocks.len(), || None);
+        thread::scope(|scope| {
+            let workers = (0..num_workers)
+                .map(|_| {
+                    scope.spawn(|| {
+                        let mut worker_results = Vec::new();
+                        loop {
+                            let index = next_block.fetch_add(1, Ordering::Relaxed);
+                            let Some(block) = blocks.get(index) else {
+                                break;
+                            };
+                            worker_results.push((index, self.validate_with_details(backend, block, None, smt.clone())));
+                        }
+                        worker_results
+                    })
+                })
+                .collect::<Vec<_>>();
+            for worker in workers {
+                let worker_results = worker.join().unwrap_or_else(|e| panic::resume_unwind(e));
+                for (index, result) in worker_results {
+                    results[index] = Some(result);
+                }
+            }
+        });
+        // Every index below the number of blocks is taken by exactly one worker, so a missing result means a block was
+        // lost in the merge and must not be taken as validated
+        results
+            .into_iter()
+            .enumerate()
+            .map(|(index, result)| {
+                result.unwrap_or_else(|| {
+                    error!(target: LOG_TARGET, "Block {} of the batch has no validation result", index);
+                    Err(ValidationError::Internal(format!(
+                        "block {} of the batch has no validation result",
+                        index
+                    )))
+                })
+            })
+            .collect()
+    }
+
+    /// Returns the proof that the output at `output_index` of the block is included in the output SMT the block
+    /// header commits to, for serving to light clients. The block must be the one returned by
+    /// [validate](Self::validate), so that its inputs hold the outputs they spend, and it must build on the current
+    /// tip.
+    pub fn output_inclusion_proof<B: BlockchainBackend>(
+        &self,
+        backend: &B,
+        block: &Block,
+        output_index: usize,
+        smt: Arc<RwLock<OutputSmt>>,
+    ) -> Result<OutputSmtInclusionProof, ValidationError> {
+        let mut output_smt = write_smt(&smt)?;
+        let proof = chain_storage::calculate_output_inclusion_proof(backend, block, output_index, &mut output_smt)?;
+        Ok(proof)
+    }
+
+    /// Returns the MMR roots and sizes the block header must hold once the block's outputs and kernels are added to
+    /// the tip, calculated in the same way as validation does. Block builders can fill in a header with
+    /// [MmrRoots::apply_to_header]. The inputs of the block must hold the outputs they spend, and the block must build
+    /// on the current tip.
+    pub fn compute_mmr_roots<B: BlockchainBackend>(
+        &self,
+        backend: &B,
+        block: &Block,
+        smt: Arc<RwLock<OutputSmt>>,
+    ) -> Result<MmrRoots, ValidationError> {
+        let mut output_smt = write_smt(&smt)?;
+        let mmr_roots = chain_storage::calculate_mmr_roots(backend, &self.consensus_manager, block, &mut output_smt)?;
+        Ok(mmr_roots)
+    }
+
+    /// Validates the block with this validator and commits it to the database in one step. Both happen while the
+    /// database write lock is held, so the UTXO set cannot change between validating the block and committing it. Use
+    /// this instead of calling [validate](BlockBodyValidator::validate) and then adding the block to the database.
+    ///
+    /// The block is added as [BlockchainDatabase::add_block] would add it, so a block that does not extend the best
+    /// chain is added as an orphan and only validated if it causes a reorg. The block committed to the chain is the one
+    /// [validate](Self::validate) returns. Every block that is added to the chain is then checked with
+    /// [check_encrypted_values](Self::check_encrypted_values), and a mismatch is logged without undoing the commit.
+    pub fn validate_and_commit<B: BlockchainBackend>(
+        &self,
+        db: &BlockchainDatabase<B>,
+        block: Arc<Block>,
+    ) -> Result<BlockAddResult, ChainStorageError> {
+        let result = db.add_block_with_validator(block, self)?;
+        let added = match &result {
+            BlockAddResult::Ok(block) => slice::from_ref(block),
+            BlockAddResult::ChainReorg { added, .. } => added.as_slice(),
+            BlockAddResult::BlockExists | BlockAddResult::OrphanBlock => &[],
+        };
+        for block in added {
+            if let Err(e) = self.check_encrypted_values(block.block()) {
+                warn!(
+                    target: LOG_TARGET,
+                    "Accepted block #{} ({}) has an encrypted value that is not valid for the watched wallet: {}",
+                    block.height(),
+                    block.hash().to_hex(),
+                    e
+                );
+            }
+        }
+        Ok(result)
+    }
+
+    /// Checks that the encrypted value of every output of the block that the configured view key can decrypt opens
+    /// the output commitment, failing with [ValidationError::EncryptedValueMismatch] otherwise. Nothing is checked if
+    /// no view key is configured. The encrypted data is not covered by consensus, so this is not part of validation:
+    /// a block that fails the check is still valid, and the check is meant to be run on blocks that were accepted.
+    pub fn check_encrypted_values(&self, block: &Block) -> Result<(), ValidationError> {
+        match &self.encrypted_value_view_key {
+            Some(view_key) => validate_encrypted_values(&block.body, view_key, &self.factories.commitment),
+            None => Ok(()),
+        }
+    }
+
+    /// Validates the block body in the same way as [validate_body](BlockBodyValidator::validate_body) and returns
+    /// a report of the outcome instead of the validated block, with the time spent in each phase, the element
+    /// counts, the fees and coinbase value, and the classified error if the block was rejected. The block is
+    /// validated once; the report is built from that run. The validated block cache is neither read nor updated and
+    /// the run is not recorded in the validation statistics.
+    pub fn validate_body_report<B: BlockchainBackend>(
+        &self,
+        backend: &B,
+        header: ValidatedHeader,
+        body: AggregateBody,
+        smt: Arc<RwLock<OutputSmt>>,
+    ) -> ValidationReport {
+        let block = Block::new(header.into_header(), body);
+        let timer = Instant::now();
+        let mut timings = PhaseTimings::default();
+        let result = if self.check_header_linkage {
+            check_header_linkage(backend, &block.header)
+        } else {
+            Ok(())
+        }
+        .and_then(|_| {
+            self.validate_body_phases(
+                backend,
+                &block,
+                smt,
+                &self.consensus_manager,
+                &self.aggregate_body_chain_validator,
+                &self.block_internal_validator,
+                &mut timings,
+            )
+        });
+        let elapsed = timer.elapsed();
+        self.log_slow_validation(&block, elapsed, &timings, result.is_ok());
+        ValidationReport::new(
+            &block,
+            &self.consensus_manager,
+            elapsed,
+            timings,
+            result.as_ref().map(|_| ()),
+        )
+    }
+
+    fn validate_body_with<B: BlockchainBackend>(
+        &self,
+        backend: &B,
+        block: &Block,
+        smt: Arc<RwLock<OutputSmt>>,
+        rules: &ConsensusManager,
+        aggregate_body_chain_validator: &AggregateBodyChainLinkedValidator,
+        block_internal_validator: &BlockBodyInternalConsistencyValidator,
+    ) -> Result<(Block, BodyValidationDetails), ValidationError> {
+        let timer = Instant::now();
+        let mut timings = PhaseTimings::default();
+        let result = self.validate_body_phases(
+            backend,
+            block,
+            smt,
+            rules,
+            aggregate_body_chain_validator,
+            block_internal_validator,
+            &mut timings,
+        );
+        self.log_slow_validation(block, timer.elapsed(), &timings, result.is_ok());
+        result
+    }
+
+    /// Whether a block whose body took `elapsed` to validate is logged as slow
+    pub(crate) fn is_slow_validation(&self, elapsed: Duration) -> bool {
+        elapsed > self.slow_validation_threshold
+    }
+
+    fn log_slow_validation(&self, block: &Block, elapsed: Duration, timings: &PhaseTimings, accepted: bool) {
+        if self.is_slow_validation(elapsed) {
+            warn!(
+                target: LOG_TARGET,
+                "Validating {} block #{} ({}) took {:.2?}, above the threshold of {:.2?}. Time per phase: {}",
+                if accepted { "valid" } else { "invalid" },
+                block.header.height,
+                block.hash().to_hex(),
+                elapsed,
+                self.slow_validation_threshold,
+                timings,
+            );
         }
+    }
+
+    fn validate_body_phases<B: BlockchainBackend>(
+        &self,
+        backend: &B,
+        block: &Block,
+        smt: Arc<RwLock<OutputSmt>>,
+        rules: &ConsensusManager,
+        aggregate_body_chain_validator: &AggregateBodyChainLinkedValidator,
+        block_internal_validator: &BlockBodyInternalConsistencyValidator,
+        timings: &mut PhaseTimings,
+    ) -> Result<(Block, BodyValidationDetails), ValidationError> {
+        let mut timer = Instant::now();
+        // The size is checked before any of the (expensive) body validation is done
+        check_block_serialized_size(
+            block,
+            rules
+                .consensus_constants(block.header.height)
+                .max_block_serialized_size(),
+        )?;
+        if self.check_body_header_counts {
+            check_body_header_counts(backend, block)?;
+        }
+        if self.check_median_timestamp {
+            check_median_time_past(backend, rules, &block.header)?;
+        }
+        // A block with the wrong number of coinbases is rejected from the features alone, before any db lookups
+        check_coinbase_counts(&block.body)?;
+        timings.checks = timer.elapsed();
 
         // validate the block body against the current db
         let body = &block.body;
         let height = block.header.height;
         // the inputs may be only references to outputs, that's why the validator returns a new body and we need a new
         // block
-        let body = self.aggregate_body_chain_validator.validate(body, height, backend)?;
+        timer = Instant::now();
+        let (body, details) = aggregate_body_chain_validator.validate_with_details(body, height, backend)?;
         let block = Block::new(block.header.clone(), body);
+        timings.chain_linked = timer.elapsed();
 
         // validate the internal consistency of the block body
-        self.block_internal_validator.validate(&block)?;
+        timer = Instant::now();
+        block_internal_validator.validate(&block)?;
+        timings.internal_consistency = timer.elapsed();
 
         // validate the merkle mountain range roots+
-        let mut output_smt = smt.write().map_err(|e| {
-            error!(
-                target: LOG_TARGET,
-                "Validator could not get a write lock on the smt {:?}", e
-            );
-            ChainStorageError::AccessError("write lock on smt".into())
-        })?;
-        let mmr_roots = chain_storage::calculate_mmr_roots(backend, &self.consensus_manager, &block, &mut output_smt)?;
+        timer = Instant::now();
+        let mut output_smt = write_smt(&smt)?;
+        let mmr_roots = chain_storage::calculate_mmr_roots(backend, rules, &block, &mut output_smt)?;
         check_mmr_roots(&block.header, &mmr_roots)?;
+        timings.mmr_roots = timer.elapsed();
 
-        Ok(block)
+        Ok((block, details))
+    }
+
+    fn get_cached_block<B: BlockchainBackend>(
+        &self,
+        backend: &B,
+        block: &Block,
+        block_hash: &BlockHash,
+    ) -> Result<Option<Block>, ValidationError> {
+        let cache = match &self.validated_block_cache {
+            Some(cache) => cache,
+            None => return Ok(None),
+        };
+        let tip = backend.fetch_chain_metadata()?;
+        let mut cache = lock_cache(cache)?;
+        // Blocks above the next height can only be cached if the chain has since been reorged to below them
+        cache.remove_above_height(tip.best_block_height() + 1);
+        // A block is only valid on top of the tip it was validated against
+        if block.header.prev_hash != *tip.best_block_hash() {
+            return Ok(None);
+        }
+        // The block hash only commits to the body through the MMR roots, which are not checked for a cached block, so a
+        // known header that comes with another body is validated like any other block
+        Ok(cache.get(block_hash).filter(|cached| cached.body == block.body))
+    }
+}
+
+/// Caps the number of blocks validated at the same time, since each is validated on its own thread and a bad config
+/// value could otherwise exhaust the threads of the node
+fn cap_batch_concurrency(batch_concurrency: usize) -> usize {
+    let max = thread::available_parallelism().map_or(MAX_BATCH_CONCURRENCY, |n| n.get());
+    if batch_concurrency > max {
+        warn!(
+            target: LOG_TARGET,
+            "Batch validation concurrency of {} is above the maximum of {}, using {}", batch_concurrency, max, max
+        );
+        return max;
     }
+    batch_concurrency
+}
+
+fn check_block_serialized_size(block: &Block, max: usize) -> Result<(), ValidationError> {
+    // The size is counted without allocating a buffer for the serialized block
+    let size = block
+        .get_serialized_size()
+        .map_err(|e| ValidationError::SerializationError(e.to_string()))?;
+    if size > max {
+        return Err(ValidationError::BlockSerializedSizeTooLarge { size, max });
+    }
+    Ok(())
+}
+
+fn write_smt(smt: &RwLock<OutputSmt>) -> Result<RwLockWriteGuard<'_, OutputSmt>, ValidationError> {
+    smt.write().map_err(|e| {
+        error!(
+            target: LOG_TARGET,
+            "Validator could not get a write lock on the smt {:?}", e
+        );
+        ChainStorageError::AccessError("write lock on smt".into()).into()
+    })
+}
+
+fn lock_cache(cache: &Mutex<ValidatedBlockCache>) -> Result<MutexGuard<'_, ValidatedBlockCache>, ValidationError> {
+    cache.lock().map_err(|e| {
+        error!(
+            target: LOG_TARGET,
+            "Validator could not get a lock on the validated block cache {:?}", e
+        );
+        ChainStorageError::AccessError("lock on validated block cache".into()).into()
+    })
 }
 
 impl<B: BlockchainBackend> CandidateBlockValidator<B> for BlockBodyFullValidator {
@@ -107,15 +746,21 @@ impl<B: BlockchainBackend> CandidateBlockValidator<B> for BlockBodyFullValidator
         block: &ChainBlock,
         metadata: &ChainMetadata,
         smt: Arc<RwLock<OutputSmt>>,
-    ) -> Result<(), ValidationError> {
-        self.validate(backend, block.block(), Some(metadata), smt)?;
-        Ok(())
+    ) -> Result<Block, ValidationError> {
+        self.validate(backend, block.block(), Some(metadata), smt)
     }
 }
 
 impl<B: BlockchainBackend> BlockBodyValidator<B> for BlockBodyFullValidator {
-    fn validate_body(&self, backend: &B, block: &Block, smt: Arc<RwLock<OutputSmt>>) -> Result<Block, ValidationError> {
-        self.validate(backend, block, None, smt)
+    fn validate_body(
+        &self,
+        backend: &B,
+        header: ValidatedHeader,
+        body: AggregateBody,
+        smt: Arc<RwLock<OutputSmt>>,
+    ) -> Result<Block, ValidationError> {
+        let block = Block::new(header.into_header(), body);
+        self.validate(backend, &block, None, smt)
     }
 }
 
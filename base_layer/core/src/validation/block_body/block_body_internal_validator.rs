@@ -61,6 +61,23 @@ impl BlockBodyInternalConsistencyValidator {
         }
     }
 
+    /// See [AggregateBodyInternalConsistencyValidator::with_kernel_signature_verification_bypass]. The coinbase checks
+    /// and the kernel sum still run.
+    pub fn with_kernel_signature_verification_bypass(mut self, bypass_kernel_signature_verification: bool) -> Self {
+        self.aggregate_body_validator = self
+            .aggregate_body_validator
+            .with_kernel_signature_verification_bypass(bypass_kernel_signature_verification);
+        self
+    }
+
+    /// See [AggregateBodyInternalConsistencyValidator::with_batch_metadata_signature_verification]
+    pub fn with_batch_metadata_signature_verification(mut self, batch_metadata_signature_verification: bool) -> Self {
+        self.aggregate_body_validator = self
+            .aggregate_body_validator
+            .with_batch_metadata_signature_verification(batch_metadata_signature_verification);
+        self
+    }
+
     pub fn validate(&self, block: &Block) -> Result<(), ValidationError> {
         validate_block_specific_checks(block, &self.consensus_manager, &self.factories)?;
         validate_block_aggregate_body(block, &self.aggregate_body_validator, &self.consensus_manager)?;
@@ -0,0 +1,83 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use log::warn;
+use tari_common_types::types::BlockHash;
+use tari_utilities::hex::Hex;
+
+use crate::{
+    blocks::Block,
+    transactions::CryptoFactories,
+    validation::{aggregate_body::validate_network_agnostic_consistency, ValidationError},
+};
+
+pub const LOG_TARGET: &str = "c::val::generic_block_body_validator";
+
+/// The result of a successful [GenericBlockBodyValidator] run. This only says that the block body is structurally
+/// sound; it is NOT a consensus pass, since none of the network-specific rules have been applied.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[must_use]
+pub struct StructurallyValidBlock {
+    pub block_hash: BlockHash,
+    pub height: u64,
+}
+
+/// Validates a block body without any network's consensus rules, for tooling that inspects blocks from an unknown
+/// network. Only signatures, sorting and uniqueness, range proofs, burns and the balance of the non-coinbase part of
+/// the body are checked. Emission, weight, maturity, version and output type rules are skipped.
+#[derive(Clone)]
+pub struct GenericBlockBodyValidator {
+    bypass_range_proof_verification: bool,
+    factories: CryptoFactories,
+}
+
+impl GenericBlockBodyValidator {
+    pub fn new(bypass_range_proof_verification: bool, factories: CryptoFactories) -> Self {
+        Self {
+            bypass_range_proof_verification,
+            factories,
+        }
+    }
+
+    pub fn validate(&self, block: &Block) -> Result<StructurallyValidBlock, ValidationError> {
+        validate_network_agnostic_consistency(
+            &block.body,
+            &block.header.total_kernel_offset,
+            &self.factories,
+            self.bypass_range_proof_verification,
+        )
+        .map_err(|err| {
+            warn!(
+                target: LOG_TARGET,
+                "Generic validation failed on block:{}:{:?}",
+                block.hash().to_hex(),
+                err
+            );
+            err
+        })?;
+
+        Ok(StructurallyValidBlock {
+            block_hash: block.hash(),
+            height: block.header.height,
+        })
+    }
+}
@@ -27,4 +27,21 @@ mod block_body_internal_validator;
 pub use block_body_internal_validator::BlockBodyInternalConsistencyValidator;
 
 mod block_body_full_validator;
-pub use block_body_full_validator::BlockBodyFullValidator;
+pub use block_body_full_validator::{
+    BlockBodyFullValidator,
+    BlockBodyFullValidatorConfig,
+    DEFAULT_BATCH_CONCURRENCY,
+    DEFAULT_SLOW_VALIDATION_THRESHOLD,
+    MAX_BATCH_CONCURRENCY,
+};
+
+mod generic_block_body_validator;
+pub use generic_block_body_validator::{GenericBlockBodyValidator, StructurallyValidBlock};
+
+mod validated_block_cache;
+
+mod validation_report;
+pub use validation_report::{PhaseTimings, ValidationErrorClass, ValidationErrorReport, ValidationReport};
+
+mod validation_stats;
+pub use validation_stats::{ValidationStats, ValidationStatsCollector};
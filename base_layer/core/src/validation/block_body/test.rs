@@ -19,38 +19,81 @@
 //  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
 //  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 //  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
-use std::sync::Arc;
+use std::{
+    collections::HashSet,
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
+use blake2::Blake2b;
+use digest::consts::U32;
 use tari_common::configuration::Network;
-use tari_common_types::{key_branches::TransactionKeyManagerBranch, tari_address::TariAddress};
+use tari_common_types::{
+    key_branches::TransactionKeyManagerBranch,
+    tari_address::TariAddress,
+    types::{
+        CommitmentFactory,
+        FixedHash,
+        PrivateKey,
+        RangeProofService,
+        Signature,
+        RANGE_PROOF_AGGREGATION_FACTOR,
+        RANGE_PROOF_BIT_LENGTH,
+    },
+};
+use tari_crypto::{commitment::ExtensionDegree, hash_domain, hashing::DomainSeparatedHasher};
 use tari_key_manager::key_manager_service::KeyId;
+use tari_mmr::sparse_merkle_tree::{NodeHash, NodeKey, ValueHash};
 use tari_script::{push_pubkey_script, script};
 use tari_test_utils::unpack_enum;
+use tari_utilities::{hex::Hex, ByteArray};
 use tokio::time::Instant;
 
-use super::BlockBodyFullValidator;
+use super::{
+    BlockBodyFullValidator,
+    BlockBodyFullValidatorConfig,
+    BlockBodyInternalConsistencyValidator,
+    ValidationErrorClass,
+    ValidationStatsCollector,
+    DEFAULT_SLOW_VALIDATION_THRESHOLD,
+    MAX_BATCH_CONCURRENCY,
+};
 use crate::{
+    base_node::sync::SyncValidators,
     block_spec,
-    blocks::BlockValidationError,
-    consensus::{ConsensusConstantsBuilder, ConsensusManager},
+    blocks::{Block, BlockHeader, BlockValidationError, ChainBlock},
+    borsh::SerializedSize,
+    chain_storage::{BlockchainBackend, ChainStorageError},
+    consensus::{ConsensusConstantsBuilder, ConsensusManager, DomainMmrHashing},
+    covenant,
     proof_of_work::Difficulty,
-    test_helpers::{blockchain::TestBlockchain, BlockSpec},
+    test_helpers::{
+        blockchain::{create_custom_blockchain_and_backend, TempDatabase, TestBlockchain},
+        BlockSpec,
+    },
     transactions::{
         aggregated_body::AggregateBody,
         key_manager::TariKeyId,
         tari_amount::{uT, T},
-        test_helpers::schema_to_transaction,
+        test_helpers::{schema_to_transaction, TransactionSchema},
         transaction_components::{
             encrypted_data::{PaymentId, STATIC_ENCRYPTED_DATA_SIZE_TOTAL},
             EncryptedData,
             RangeProofType,
             TransactionError,
+            TransactionOutput,
         },
         CoinbaseBuilder,
         CryptoFactories,
     },
     txn_schema,
-    validation::{BlockBodyValidator, ValidationError},
+    validation::{
+        aggregate_body::{AggregateBodyChainLinkedValidator, UtxoBloomFilter},
+        header::ValidatedHeader,
+        BlockBodyValidator,
+        ValidationError,
+    },
+    OutputSmt,
 };
 async fn setup_with_rules(rules: ConsensusManager, check_rangeproof: bool) -> (TestBlockchain, BlockBodyFullValidator) {
     let blockchain = TestBlockchain::create(rules.clone()).await;
@@ -71,6 +114,39 @@ async fn setup(check_rangeproof: bool) -> (TestBlockchain, BlockBodyFullValidato
     setup_with_rules(rules, check_rangeproof).await
 }
 
+/// Validates the body of `block`. These tests only exercise body validation, so the header is taken as validated.
+fn validate_body<B: BlockchainBackend>(
+    validator: &BlockBodyFullValidator,
+    backend: &B,
+    block: &Block,
+    smt: Arc<RwLock<OutputSmt>>,
+) -> Result<Block, ValidationError> {
+    let header = ValidatedHeader::new_unchecked(block.header.clone());
+    validator.validate_body(backend, header, block.body.clone(), smt)
+}
+
+/// Returns `block` with the MMR roots and sizes validation calculates for it. The block must build on the tip.
+fn with_mmr_roots(blockchain: &TestBlockchain, block: &Block) -> Block {
+    let (mut block, mmr_roots) = blockchain.db().calculate_mmr_roots(block.clone()).unwrap();
+    mmr_roots.apply_to_header(&mut block.header);
+    block
+}
+
+/// Adds block A to the chain and creates block B on top of it, which spends the coinbase of A to two outputs.
+/// `modify_schema` can change the spending transaction before it is built.
+async fn create_coinbase_spend(
+    blockchain: &mut TestBlockchain,
+    modify_schema: impl FnOnce(&mut TransactionSchema),
+) -> Arc<ChainBlock> {
+    let (_, coinbase_a) = blockchain.add_next_tip(block_spec!("A")).await.unwrap();
+    let mut schema = txn_schema!(from: vec![coinbase_a], to: vec![50 * T, 12 * T]);
+    modify_schema(&mut schema);
+    let (txs, _) = schema_to_transaction(&[schema], &blockchain.km).await;
+    let txs = txs.into_iter().map(|t| Arc::try_unwrap(t).unwrap()).collect::<Vec<_>>();
+    let (block, _) = blockchain.create_next_tip(block_spec!("B", transactions: txs)).await;
+    block
+}
+
 #[tokio::test]
 async fn it_passes_if_large_output_block_is_valid() {
     // we use this test to benchmark a block with multiple outputs
@@ -104,7 +180,7 @@ async fn it_passes_if_large_output_block_is_valid() {
     let txn = blockchain.db().db_read_access().unwrap();
     let start = Instant::now();
     let smt = blockchain.db().smt().clone();
-    assert!(validator.validate_body(&*txn, &block, smt).is_ok());
+    assert!(validate_body(&validator, &*txn, &block, smt).is_ok());
     let finished = start.elapsed();
     // this here here for benchmarking purposes.
     // we can extrapolate full block validation by multiplying the time by 4.6, this we get from the max_weight /weight
@@ -139,7 +215,101 @@ async fn it_validates_when_a_coinbase_is_spent() {
 
     let txn = blockchain.db().db_read_access().unwrap();
     let smt = blockchain.db().smt().clone();
-    assert!(validator.validate_body(&*txn, &block, smt).is_ok());
+    assert!(validate_body(&validator, &*txn, &block, smt).is_ok());
+}
+
+#[tokio::test]
+async fn it_counts_utxo_lookups_per_block() {
+    let (mut blockchain, validator) = setup(false).await;
+    let (_, coinbase_a) = blockchain.add_next_tip(block_spec!("A")).await.unwrap();
+
+    let schema1 = txn_schema!(from: vec![coinbase_a.clone()], to: vec![9000 * uT]);
+    let (txs, _outputs) = schema_to_transaction(&[schema1], &blockchain.km).await;
+
+    let txs = txs.into_iter().map(|t| Arc::try_unwrap(t).unwrap()).collect::<Vec<_>>();
+    let (chain_block, _coinbase_b) = blockchain
+        .create_next_tip(block_spec!("B",parent: "A", transactions: txs))
+        .await;
+    let block = with_mmr_roots(&blockchain, chain_block.block());
+
+    let expected = (block.body.inputs().len() + block.body.outputs().len()) as u64;
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let (_, details) = validator
+        .validate_with_details(&*txn, &block, None, smt.clone())
+        .unwrap();
+    assert_eq!(details.utxo_lookups(), expected);
+
+    // Every validation counts its own lookups rather than accumulating them
+    let (_, details) = validator.validate_with_details(&*txn, &block, None, smt).unwrap();
+    assert_eq!(details.utxo_lookups(), expected);
+}
+
+#[tokio::test]
+async fn it_validates_with_the_provided_crypto_factories() {
+    let rules = ConsensusManager::builder(Network::LocalNet)
+        .add_consensus_constants(
+            ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_coinbase_lockheight(0)
+                .build(),
+        )
+        .build()
+        .unwrap();
+    let (mut blockchain, _) = setup_with_rules(rules.clone(), false).await;
+    let (_, coinbase_a) = blockchain.add_next_tip(block_spec!("A")).await.unwrap();
+
+    let schema1 = txn_schema!(from: vec![coinbase_a.clone()], to: vec![9000 * uT]);
+    let (txs, _outputs) = schema_to_transaction(&[schema1], &blockchain.km).await;
+
+    let txs = txs.into_iter().map(|t| Arc::try_unwrap(t).unwrap()).collect::<Vec<_>>();
+    let (chain_block, _coinbase_b) = blockchain
+        .create_next_tip(block_spec!("B",parent: "A", transactions: txs))
+        .await;
+    let block = with_mmr_roots(&blockchain, chain_block.block());
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+
+    let validator = BlockBodyFullValidator::with_factories(rules.clone(), false, CryptoFactories::new(64));
+    assert!(validate_body(&validator, &*txn, &block, smt.clone()).is_ok());
+
+    // A range proof service that only covers 32-bit values cannot verify the 64-bit range proofs in the block
+    let validator = BlockBodyFullValidator::with_factories(rules, false, CryptoFactories::new(32));
+    assert!(validate_body(&validator, &*txn, &block, smt).is_err());
+}
+
+#[tokio::test]
+async fn it_validates_sync_blocks_with_the_provided_crypto_factories() {
+    let (mut blockchain, _) = setup(false).await;
+    let block = create_coinbase_spend(&mut blockchain, |_| {}).await;
+    let block = with_mmr_roots(&blockchain, block.block());
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let validate = |factories: CryptoFactories| {
+        let validators = SyncValidators::<TempDatabase>::full_consensus(blockchain.rules().clone(), factories, false);
+        let header = ValidatedHeader::new_unchecked(block.header.clone());
+        validators
+            .block_body
+            .validate_body(&*txn, header, block.body.clone(), smt.clone())
+    };
+
+    assert!(validate(CryptoFactories::default()).is_ok());
+
+    // The range proofs in the block were made for commitments with a single blinding factor, so they do not verify
+    // against a commitment factory with an extra base point
+    let commitment = CommitmentFactory::new_with_extension_degree(ExtensionDegree::AddOneBasePoint).unwrap();
+    let range_proof = RangeProofService::init(
+        RANGE_PROOF_BIT_LENGTH,
+        RANGE_PROOF_AGGREGATION_FACTOR,
+        commitment.clone(),
+    )
+    .unwrap();
+    let factories = CryptoFactories {
+        commitment: Arc::new(commitment),
+        range_proof: Arc::new(range_proof),
+    };
+    assert!(validate(factories).is_err());
 }
 
 #[tokio::test]
@@ -182,7 +352,7 @@ async fn it_passes_if_large_block_is_valid() {
     let txn = blockchain.db().db_read_access().unwrap();
     let start = Instant::now();
     let smt = blockchain.db().smt();
-    validator.validate_body(&*txn, &block, smt).unwrap();
+    validate_body(&validator, &*txn, &block, smt).unwrap();
     // assert!(validator.validate_body(&*txn, &block).is_ok());
     let finished = start.elapsed();
     // this here here for benchmarking purposes.
@@ -211,7 +381,142 @@ async fn it_passes_if_block_is_valid() {
 
     let txn = blockchain.db().db_read_access().unwrap();
     let smt = blockchain.db().smt();
-    assert!(validator.validate_body(&*txn, &block, smt).is_ok());
+    assert!(validate_body(&validator, &*txn, &block, smt).is_ok());
+}
+
+#[tokio::test]
+async fn it_checks_header_linkage_before_the_body() {
+    let (blockchain, validator) = setup(true).await;
+    let validator = validator.with_header_linkage_check(true);
+
+    let (chain_block, _) = blockchain.create_next_tip(BlockSpec::default()).await;
+
+    let block = with_mmr_roots(&blockchain, chain_block.block());
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    assert!(validate_body(&validator, &*txn, &block, smt.clone()).is_ok());
+
+    let mut wrong_height = block.clone();
+    wrong_height.header.height += 1;
+    let err = validate_body(&validator, &*txn, &wrong_height, smt.clone()).unwrap_err();
+    assert!(matches!(err, ValidationError::IncorrectHeight { .. }));
+
+    let mut orphan = block;
+    orphan.header.prev_hash = FixedHash::zero();
+    let err = validate_body(&validator, &*txn, &orphan, smt).unwrap_err();
+    assert!(matches!(err, ValidationError::OrphanBlock { .. }));
+}
+
+#[tokio::test]
+async fn it_checks_body_counts_against_the_header_mmr_sizes() {
+    let (blockchain, validator) = setup(true).await;
+    let validator = validator.with_body_header_count_check(true);
+
+    let (chain_block, _) = blockchain.create_next_tip(BlockSpec::default()).await;
+
+    let block = with_mmr_roots(&blockchain, chain_block.block());
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    assert!(validate_body(&validator, &*txn, &block, smt.clone()).is_ok());
+
+    let mut extra_kernel = block.clone();
+    extra_kernel.header.kernel_mmr_size += 1;
+    let err = validate_body(&validator, &*txn, &extra_kernel, smt.clone()).unwrap_err();
+    assert!(matches!(err, ValidationError::BodyHeaderCountMismatch {
+        kind: "Kernel",
+        ..
+    }));
+
+    let mut missing_output = block;
+    missing_output.header.output_smt_size -= 1;
+    let err = validate_body(&validator, &*txn, &missing_output, smt).unwrap_err();
+    assert!(matches!(err, ValidationError::BodyHeaderCountMismatch {
+        kind: "Output",
+        ..
+    }));
+}
+
+#[tokio::test]
+async fn it_only_returns_cached_results_for_the_validated_body() {
+    let (blockchain, validator) = setup(true).await;
+    let validator = validator.with_validated_block_cache(10);
+
+    let (chain_block, _) = blockchain.create_next_tip(BlockSpec::default()).await;
+
+    let block = with_mmr_roots(&blockchain, chain_block.block());
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let validated = validate_body(&validator, &*txn, &block, smt.clone()).unwrap();
+    let cached = validate_body(&validator, &*txn, &block, smt.clone()).unwrap();
+    assert_eq!(cached, validated);
+
+    // A known header with another body is not a cache hit, so the body is validated and rejected
+    let mut tampered = block.clone();
+    tampered.body = AggregateBody::empty();
+    let err = validate_body(&validator, &*txn, &tampered, smt.clone()).unwrap_err();
+    assert!(matches!(err, ValidationError::EmptyBlock));
+
+    // The validated block is still cached
+    let cached = validate_body(&validator, &*txn, &block, smt).unwrap();
+    assert_eq!(cached, validated);
+}
+
+#[tokio::test]
+async fn it_applies_the_validator_config() {
+    let (blockchain, _) = setup(true).await;
+    let config = BlockBodyFullValidatorConfig {
+        bypass_range_proof_verification: true,
+        check_body_header_counts: true,
+        ..Default::default()
+    };
+    let validator = BlockBodyFullValidator::from_config(blockchain.rules().clone(), CryptoFactories::default(), config);
+
+    let (chain_block, _) = blockchain.create_next_tip(BlockSpec::default()).await;
+
+    let mut block = with_mmr_roots(&blockchain, chain_block.block());
+    block.header.output_smt_size += 1;
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let err = validate_body(&validator, &*txn, &block, smt).unwrap_err();
+    assert!(matches!(err, ValidationError::BodyHeaderCountMismatch {
+        kind: "Output",
+        ..
+    }));
+}
+
+#[tokio::test]
+async fn it_rejects_an_empty_block() {
+    let (blockchain, validator) = setup(true).await;
+
+    let (chain_block, _) = blockchain.create_next_tip(BlockSpec::default()).await;
+    let mut block = chain_block.block().clone();
+    block.body = AggregateBody::empty();
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let err = validate_body(&validator, &*txn, &block, smt).unwrap_err();
+    assert!(matches!(err, ValidationError::EmptyBlock));
+}
+
+#[tokio::test]
+async fn it_checks_the_median_timestamp_when_enabled() {
+    let (blockchain, validator) = setup(true).await;
+
+    let (chain_block, _) = blockchain.create_next_tip(BlockSpec::default()).await;
+    let mut block = with_mmr_roots(&blockchain, chain_block.block());
+    block.header.timestamp = 0.into();
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    validate_body(&validator, &*txn, &block, smt.clone()).unwrap();
+
+    let validator = validator.with_median_timestamp_check(true);
+    let err = validate_body(&validator, &*txn, &block, smt).unwrap_err();
+    assert!(matches!(err, ValidationError::TimestampTooEarly { .. }));
 }
 
 #[tokio::test]
@@ -223,7 +528,7 @@ async fn it_checks_the_coinbase_reward() {
         .await;
     let txn = blockchain.db().db_read_access().unwrap();
     let smt = blockchain.db().smt();
-    let err = validator.validate_body(&*txn, block.block(), smt).unwrap_err();
+    let err = validate_body(&validator, &*txn, block.block(), smt).unwrap_err();
     println!("err {:?}", err);
     assert!(matches!(
         err,
@@ -271,7 +576,7 @@ async fn it_allows_multiple_coinbases() {
     let block = blockchain.mine_block("GB", block, Difficulty::min());
     let smt = blockchain.db().smt();
     let txn = blockchain.db().db_read_access().unwrap();
-    let err = validator.validate_body(&*txn, block.block(), smt).unwrap_err();
+    let err = validate_body(&validator, &*txn, block.block(), smt).unwrap_err();
     assert!(matches!(
         err,
         ValidationError::BlockError(BlockValidationError::TransactionError(TransactionError::NoCoinbase))
@@ -302,7 +607,7 @@ async fn it_checks_duplicate_kernel() {
         .await;
     let txn = blockchain.db().db_read_access().unwrap();
     let smt = blockchain.db().smt();
-    let err = validator.validate_body(&*txn, block.block(), smt).unwrap_err();
+    let err = validate_body(&validator, &*txn, block.block(), smt).unwrap_err();
     assert!(matches!(err, ValidationError::DuplicateKernelError(_)));
 }
 
@@ -336,7 +641,7 @@ async fn it_checks_double_spends() {
         .await;
     let txn = blockchain.db().db_read_access().unwrap();
     let smt = blockchain.db().smt();
-    let err = validator.validate_body(&*txn, block.block(), smt).unwrap_err();
+    let err = validate_body(&validator, &*txn, block.block(), smt).unwrap_err();
     assert!(matches!(err, ValidationError::ContainsSTxO));
 }
 
@@ -358,7 +663,7 @@ async fn it_checks_input_maturity() {
         .await;
     let txn = blockchain.db().db_read_access().unwrap();
     let smt = blockchain.db().smt();
-    let err = validator.validate_body(&*txn, block.block(), smt).unwrap_err();
+    let err = validate_body(&validator, &*txn, block.block(), smt).unwrap_err();
     assert!(matches!(
         err,
         ValidationError::TransactionError(TransactionError::InputMaturity)
@@ -366,6 +671,37 @@ async fn it_checks_input_maturity() {
     unpack_enum!(ValidationError::TransactionError(TransactionError::InputMaturity) = err);
 }
 
+#[tokio::test]
+async fn it_rejects_spending_an_immature_coinbase() {
+    let rules = ConsensusManager::builder(Network::LocalNet)
+        .add_consensus_constants(
+            ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_coinbase_lockheight(3)
+                .build(),
+        )
+        .build()
+        .unwrap();
+    let (mut blockchain, validator) = setup_with_rules(rules, true).await;
+
+    // The coinbase of A is mined at height 1 and matures at height 4
+    let (_, coinbase_a) = blockchain.add_next_tip(block_spec!("A")).await.unwrap();
+    blockchain.add_next_tip(block_spec!("B", parent: "A")).await.unwrap();
+    let schema = txn_schema!(from: vec![coinbase_a.clone()], to: vec![50 * T]);
+    let (txs, _) = schema_to_transaction(&[schema], &blockchain.km).await;
+    let txs = txs.into_iter().map(|t| Arc::try_unwrap(t).unwrap()).collect::<Vec<_>>();
+    let (block, _) = blockchain
+        .create_next_tip(block_spec!("C", parent: "B", transactions: txs))
+        .await;
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let err = validate_body(&validator, &*txn, block.block(), smt).unwrap_err();
+    assert!(matches!(err, ValidationError::ImmatureCoinbaseSpend {
+        spending_height: 3,
+        mature_height: 4
+    }));
+}
+
 #[tokio::test]
 async fn it_checks_txo_sort_order() {
     let (mut blockchain, validator) = setup(true).await;
@@ -387,7 +723,7 @@ async fn it_checks_txo_sort_order() {
 
     let txn = blockchain.db().db_read_access().unwrap();
     let smt = blockchain.db().smt();
-    let err = validator.validate_body(&*txn, block.block(), smt).unwrap_err();
+    let err = validate_body(&validator, &*txn, block.block(), smt).unwrap_err();
     assert!(matches!(err, ValidationError::UnsortedOrDuplicateOutput));
 }
 
@@ -414,10 +750,543 @@ async fn it_limits_the_script_byte_size() {
 
     let txn = blockchain.db().db_read_access().unwrap();
     let smt = blockchain.db().smt();
-    let err = validator.validate_body(&*txn, block.block(), smt).unwrap_err();
+    let err = validate_body(&validator, &*txn, block.block(), smt).unwrap_err();
     assert!(matches!(err, ValidationError::TariScriptExceedsMaxSize { .. }));
 }
 
+#[tokio::test]
+async fn it_limits_the_covenant_length() {
+    let rules = ConsensusManager::builder(Network::LocalNet)
+        .add_consensus_constants(
+            ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_coinbase_lockheight(0)
+                .with_max_covenant_length(0)
+                .build(),
+        )
+        .build()
+        .unwrap();
+    let (mut blockchain, validator) = setup_with_rules(rules, true).await;
+
+    let block = create_coinbase_spend(&mut blockchain, |schema| schema.covenant = covenant!(identity())).await;
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let err = validate_body(&validator, &*txn, block.block(), smt).unwrap_err();
+    assert!(matches!(err, ValidationError::CovenantTooLarge { max_size: 0, .. }));
+}
+
+#[tokio::test]
+async fn it_validates_a_block_under_alternative_rules() {
+    let rules = ConsensusManager::builder(Network::LocalNet)
+        .add_consensus_constants(
+            ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_coinbase_lockheight(0)
+                .build(),
+        )
+        .build()
+        .unwrap();
+    let (mut blockchain, validator) = setup_with_rules(rules, true).await;
+
+    let block = create_coinbase_spend(&mut blockchain, |schema| schema.covenant = covenant!(identity())).await;
+
+    let strict_rules = ConsensusManager::builder(Network::LocalNet)
+        .add_consensus_constants(
+            ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_coinbase_lockheight(0)
+                .with_max_covenant_length(0)
+                .build(),
+        )
+        .build()
+        .unwrap();
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let block = with_mmr_roots(&blockchain, block.block());
+    validate_body(&validator, &*txn, &block, smt.clone()).unwrap();
+    let err = validator
+        .validate_under_rules(&*txn, &block, None, smt, &strict_rules)
+        .unwrap_err();
+    assert!(matches!(err, ValidationError::CovenantTooLarge { max_size: 0, .. }));
+}
+
+#[tokio::test]
+async fn it_records_the_spent_output_hashes() {
+    let (mut blockchain, validator) = setup(true).await;
+    let block = create_coinbase_spend(&mut blockchain, |_| {}).await;
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let block = with_mmr_roots(&blockchain, block.block());
+    let (_, details) = validator
+        .validate_with_details(&*txn, &block, None, smt.clone())
+        .unwrap();
+    assert!(details.spent_output_hashes().is_none());
+
+    let validator = validator.with_spent_output_recording(true);
+    let (validated, details) = validator.validate_with_details(&*txn, &block, None, smt).unwrap();
+    let expected = validated
+        .body
+        .inputs()
+        .iter()
+        .map(|i| i.output_hash())
+        .collect::<Vec<_>>();
+    assert_eq!(expected.len(), 1);
+    assert_eq!(details.spent_output_hashes(), Some(expected.as_slice()));
+}
+
+#[tokio::test]
+async fn it_skips_utxo_lookups_for_inputs_missing_from_the_utxo_filter() {
+    let (mut blockchain, validator) = setup(true).await;
+    let filter = Arc::new(UtxoBloomFilter::new(100));
+    let validator = validator.with_utxo_filter(filter.clone());
+
+    let block = create_coinbase_spend(&mut blockchain, |_| {}).await;
+    let spent_output_hash = block.block().body.inputs()[0].output_hash();
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let block = with_mmr_roots(&blockchain, block.block());
+    // The filter does not contain the coinbase yet, so it is reported as unknown without a lookup
+    let err = validate_body(&validator, &*txn, &block, smt.clone()).unwrap_err();
+    unpack_enum!(ValidationError::UnknownInputs(not_found) = err);
+    assert_eq!(not_found, vec![spent_output_hash]);
+
+    filter.insert(&spent_output_hash);
+    validate_body(&validator, &*txn, &block, smt).unwrap();
+}
+
+#[tokio::test]
+async fn it_reports_inputs_also_spent_in_the_mempool() {
+    let (mut blockchain, validator) = setup(true).await;
+    let mempool_spent_set: Arc<RwLock<HashSet<FixedHash>>> = Arc::new(RwLock::new(HashSet::new()));
+    let validator = validator.with_mempool_spent_set(mempool_spent_set.clone());
+
+    let block = create_coinbase_spend(&mut blockchain, |_| {}).await;
+    let spent_output_hash = block.block().body.inputs()[0].output_hash();
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let block = with_mmr_roots(&blockchain, block.block());
+    let (_, details) = validator
+        .validate_with_details(&*txn, &block, None, smt.clone())
+        .unwrap();
+    assert_eq!(details.mempool_spent_inputs(), Some(&[][..]));
+
+    mempool_spent_set.write().unwrap().insert(spent_output_hash);
+    let (_, details) = validator.validate_with_details(&*txn, &block, None, smt).unwrap();
+    assert_eq!(details.mempool_spent_inputs(), Some(&[spent_output_hash][..]));
+}
+
+#[tokio::test]
+async fn it_reports_unknown_inputs_in_best_effort_mode() {
+    let (mut blockchain, _) = setup(true).await;
+
+    let block = create_coinbase_spend(&mut blockchain, |_| {}).await;
+    let body = &block.block().body;
+    let height = block.block().header.height;
+    let spent_output_hash = body.inputs()[0].output_hash();
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    // The filter does not contain the coinbase yet, as if the block spent an output of a block that is not synced
+    let filter = Arc::new(UtxoBloomFilter::new(100));
+    let validator = AggregateBodyChainLinkedValidator::new(blockchain.rules().clone()).with_utxo_filter(filter.clone());
+    let err = validator.validate(body, height, &*txn).unwrap_err();
+    assert!(matches!(err, ValidationError::UnknownInputs(_)));
+
+    let partial = validator.validate_best_effort(body, height, &*txn).unwrap();
+    assert!(!partial.is_complete());
+    assert_eq!(partial.unknown_inputs(), &[spent_output_hash]);
+    assert!(partial.input_commitment_sum().is_none());
+    // A body with unknown inputs is never handed out as validated
+    let err = partial.into_body().unwrap_err();
+    unpack_enum!(ValidationError::UnknownInputs(unknown_inputs) = err);
+    assert_eq!(unknown_inputs, vec![spent_output_hash]);
+
+    filter.insert(&spent_output_hash);
+    let partial = validator.validate_best_effort(body, height, &*txn).unwrap();
+    assert!(partial.is_complete());
+    let commitment_sum = partial.input_commitment_sum().cloned();
+    let validated = partial.into_body().unwrap();
+    assert_eq!(
+        commitment_sum.as_ref(),
+        Some(validated.inputs()[0].commitment().unwrap())
+    );
+    assert_eq!(validated, validator.validate(body, height, &*txn).unwrap());
+}
+
+#[tokio::test]
+async fn it_rejects_outputs_repeated_in_the_body_or_the_utxo_set() {
+    let (mut blockchain, _) = setup(true).await;
+
+    let (block_a, _) = blockchain.add_next_tip(block_spec!("A")).await.unwrap();
+    let (block_b, _) = blockchain.create_next_tip(block_spec!("B")).await;
+    let height = block_b.block().header.height;
+    let kernels = block_b.block().body.kernels().clone();
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let validator = AggregateBodyChainLinkedValidator::new(blockchain.rules().clone());
+
+    let mined_output = block_a.block().body.outputs()[0].clone();
+    let body = AggregateBody::new(vec![], vec![mined_output], kernels.clone());
+    let err = validator.validate(&body, height, &*txn).unwrap_err();
+    assert!(matches!(err, ValidationError::ContainsDuplicateUtxoCommitment));
+
+    let output = block_b.block().body.outputs()[0].clone();
+    let body = AggregateBody::new_sorted_unchecked(vec![], vec![output.clone(), output], kernels);
+    let err = validator.validate(&body, height, &*txn).unwrap_err();
+    assert!(matches!(err, ValidationError::UnsortedOrDuplicateOutput));
+}
+
+#[tokio::test]
+async fn it_validates_independent_blocks_in_a_batch() {
+    let (mut blockchain, validator) = setup(true).await;
+    let validator = validator.with_batch_concurrency(2);
+
+    blockchain.add_next_tip(block_spec!("A")).await.unwrap();
+    let (tip_1, _) = blockchain.create_next_tip(block_spec!("B1")).await;
+    let (tip_2, _) = blockchain.create_next_tip(block_spec!("B2")).await;
+    let (tip_3, _) = blockchain.create_next_tip(block_spec!("B3")).await;
+    let tip_1 = with_mmr_roots(&blockchain, tip_1.block());
+    let tip_2 = with_mmr_roots(&blockchain, tip_2.block());
+    let mut invalid_tip = with_mmr_roots(&blockchain, tip_3.block());
+    invalid_tip.header.total_kernel_offset = &invalid_tip.header.total_kernel_offset + &PrivateKey::from(1u64);
+    let blocks = vec![tip_1.clone(), invalid_tip, tip_2.clone()];
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let results = validator.validate_batch_independent(&*txn, blocks, blockchain.db().smt());
+    assert_eq!(results.len(), 3);
+    let (validated, details) = results[0].as_ref().unwrap();
+    assert_eq!(validated.hash(), tip_1.hash());
+    assert_eq!(details.utxo_lookups(), tip_1.body.outputs().len() as u64);
+    assert!(matches!(results[1], Err(ValidationError::InvalidAccountingBalance)));
+    let (validated, details) = results[2].as_ref().unwrap();
+    assert_eq!(validated.hash(), tip_2.hash());
+    assert_eq!(details.utxo_lookups(), tip_2.body.outputs().len() as u64);
+}
+
+#[tokio::test]
+async fn it_caps_the_batch_concurrency() {
+    let (_, validator) = setup(true).await;
+    let max = std::thread::available_parallelism().map_or(MAX_BATCH_CONCURRENCY, |n| n.get());
+
+    let validator = validator.with_batch_concurrency(10_000);
+    assert_eq!(validator.batch_concurrency(), max);
+    let validator = validator.with_batch_concurrency(1);
+    assert_eq!(validator.batch_concurrency(), 1);
+}
+
+hash_domain!(TestKernelMmrHashDomain, "com.tari.test.kernel_mmr", 1);
+hash_domain!(TestInputMmrHashDomain, "com.tari.test.input_mmr", 1);
+
+#[tokio::test]
+async fn it_validates_blocks_under_custom_mmr_hashing() {
+    let constants = ConsensusConstantsBuilder::new(Network::LocalNet)
+        .with_coinbase_lockheight(0)
+        .build();
+    let rules = ConsensusManager::builder(Network::LocalNet)
+        .add_consensus_constants(constants.clone())
+        .with_mmr_hashing(Box::new(DomainMmrHashing::<
+            DomainSeparatedHasher<Blake2b<U32>, TestKernelMmrHashDomain>,
+            DomainSeparatedHasher<Blake2b<U32>, TestInputMmrHashDomain>,
+        >::new()))
+        .build()
+        .unwrap();
+    let mut blockchain = TestBlockchain::new(create_custom_blockchain_and_backend(rules.clone()), rules.clone()).await;
+
+    blockchain.add_next_tip(block_spec!("A")).await.unwrap();
+    let (block, _) = blockchain.create_next_tip(block_spec!("B")).await;
+    let block = with_mmr_roots(&blockchain, block.block());
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let validator = BlockBodyFullValidator::new(rules, true);
+    validate_body(&validator, &*txn, &block, smt.clone()).unwrap();
+
+    // The roots do not match those of the default hashing
+    let default_rules = ConsensusManager::builder(Network::LocalNet)
+        .add_consensus_constants(constants)
+        .build()
+        .unwrap();
+    let validator = BlockBodyFullValidator::new(default_rules, true);
+    let err = validate_body(&validator, &*txn, &block, smt).unwrap_err();
+    assert!(matches!(
+        err,
+        ValidationError::BlockError(BlockValidationError::MismatchedMmrRoots { kind: "Kernel" })
+    ));
+}
+
+#[tokio::test]
+async fn it_limits_the_serialized_block_size() {
+    let (blockchain, _) = setup(true).await;
+    let (block, _) = blockchain.create_next_tip(block_spec!("A")).await;
+    let block = with_mmr_roots(&blockchain, block.block());
+    let size = block.get_serialized_size().unwrap();
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let validator_with_max_size = |max_size| {
+        let rules = ConsensusManager::builder(Network::LocalNet)
+            .add_consensus_constants(
+                ConsensusConstantsBuilder::new(Network::LocalNet)
+                    .with_coinbase_lockheight(0)
+                    .with_max_block_transaction_weight(127_795)
+                    .with_max_block_serialized_size(max_size)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+        BlockBodyFullValidator::new(rules, true)
+    };
+    let err = validate_body(&validator_with_max_size(size - 1), &*txn, &block, smt.clone()).unwrap_err();
+    assert!(
+        matches!(err, ValidationError::BlockSerializedSizeTooLarge { size: s, max } if s == size && max == size - 1)
+    );
+    assert!(err.get_ban_reason().is_some());
+
+    validate_body(&validator_with_max_size(size), &*txn, &block, smt).unwrap();
+}
+
+#[tokio::test]
+async fn it_builds_output_inclusion_proofs_against_the_output_mr() {
+    let (mut blockchain, validator) = setup(true).await;
+    let block = create_coinbase_spend(&mut blockchain, |_| {}).await;
+
+    let block = with_mmr_roots(&blockchain, block.block());
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let block = validate_body(&validator, &*txn, &block, smt.clone()).unwrap();
+    let smt_root_before = smt.read().unwrap().unsafe_hash().clone();
+
+    let output_mr = NodeHash::try_from(block.header.output_mr.as_slice()).unwrap();
+    for (i, output) in block.body.outputs().iter().enumerate() {
+        let proof = validator.output_inclusion_proof(&*txn, &block, i, smt.clone()).unwrap();
+        let key = NodeKey::try_from(output.commitment.as_bytes()).unwrap();
+        let value = ValueHash::try_from(output.smt_hash(block.header.height).as_slice()).unwrap();
+        assert!(proof.validate(&key, &value, &output_mr));
+    }
+    // The SMT is left at the tip
+    assert_eq!(smt.read().unwrap().unsafe_hash(), &smt_root_before);
+
+    let num_outputs = block.body.outputs().len();
+    let err = validator
+        .output_inclusion_proof(&*txn, &block, num_outputs, smt)
+        .unwrap_err();
+    assert!(matches!(err, ValidationError::FatalStorageError(_)));
+}
+
+#[tokio::test]
+async fn it_computes_the_mmr_roots_and_sizes_of_the_header() {
+    let (mut blockchain, validator) = setup(true).await;
+    let block = create_coinbase_spend(&mut blockchain, |_| {}).await;
+
+    let block = with_mmr_roots(&blockchain, block.block());
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let block = validate_body(&validator, &*txn, &block, smt.clone()).unwrap();
+
+    // A header with its roots and sizes cleared is restored by applying the computed roots
+    let mut header = block.header.clone();
+    header.kernel_mr = FixedHash::zero();
+    header.kernel_mmr_size = 0;
+    header.output_mr = FixedHash::zero();
+    header.output_smt_size = 0;
+    let mmr_roots = validator.compute_mmr_roots(&*txn, &block, smt.clone()).unwrap();
+    mmr_roots.apply_to_header(&mut header);
+    assert_eq!(header, block.header);
+    assert_eq!(mmr_roots.output_smt_size, block.header.output_smt_size);
+    assert_eq!(mmr_roots.kernel_mmr_size, block.header.kernel_mmr_size);
+
+    // The SMT is left at the tip, so the block still validates
+    validate_body(&validator, &*txn, &block, smt).unwrap();
+}
+
+#[tokio::test]
+async fn it_checks_the_kernel_sum_when_kernel_signatures_are_bypassed() {
+    let (blockchain, _) = setup(true).await;
+    let (block, _) = blockchain.create_next_tip(block_spec!("A")).await;
+
+    // Replace the coinbase kernel signature with one that does not verify
+    let (header, inputs, outputs, mut kernels) = block.block().clone().dissolve();
+    let public_nonce = kernels[0].excess_sig.get_public_nonce().clone();
+    kernels[0].excess_sig = Signature::new(public_nonce, PrivateKey::default());
+    let forged = Block::new(header, AggregateBody::new_sorted_unchecked(inputs, outputs, kernels));
+
+    let rules = blockchain.rules().clone();
+    let validator = BlockBodyInternalConsistencyValidator::new(rules.clone(), false, CryptoFactories::default());
+    let err = validator.validate(&forged).unwrap_err();
+    assert!(matches!(
+        err,
+        ValidationError::TransactionError(TransactionError::InvalidSignatureError(_))
+    ));
+
+    let validator = BlockBodyInternalConsistencyValidator::new(rules, false, CryptoFactories::default())
+        .with_kernel_signature_verification_bypass(true);
+    validator.validate(&forged).unwrap();
+
+    // The kernels must still balance against the offset
+    let mut unbalanced = forged;
+    unbalanced.header.total_kernel_offset = PrivateKey::from(1u64);
+    let err = validator.validate(&unbalanced).unwrap_err();
+    assert!(matches!(err, ValidationError::InvalidAccountingBalance));
+}
+
+#[tokio::test]
+async fn it_only_commits_blocks_that_pass_validation() {
+    let (blockchain, validator) = setup(true).await;
+    let (block, _) = blockchain.create_next_tip(block_spec!("A")).await;
+    let block = with_mmr_roots(&blockchain, block.block());
+
+    // The database is set up with mock validators, so only the full validator rejects the block
+    let mut unbalanced = block.clone();
+    unbalanced.header.total_kernel_offset = PrivateKey::from(1u64);
+    let err = validator
+        .validate_and_commit(blockchain.db(), Arc::new(unbalanced))
+        .unwrap_err();
+    assert!(matches!(err, ChainStorageError::ValidationError { .. }));
+    assert_eq!(blockchain.db().get_height().unwrap(), 0);
+
+    let result = validator.validate_and_commit(blockchain.db(), Arc::new(block)).unwrap();
+    assert!(result.is_added());
+    assert_eq!(blockchain.db().get_height().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn it_does_not_commit_a_cached_header_with_another_body() {
+    let (blockchain, validator) = setup(true).await;
+    let validator = validator.with_validated_block_cache(10);
+    let (block, _) = blockchain.create_next_tip(block_spec!("A")).await;
+    let block = with_mmr_roots(&blockchain, block.block());
+
+    {
+        let txn = blockchain.db().db_read_access().unwrap();
+        validate_body(&validator, &*txn, &block, blockchain.db().smt()).unwrap();
+    }
+
+    let mut tampered = block;
+    tampered.body = AggregateBody::empty();
+    let err = validator
+        .validate_and_commit(blockchain.db(), Arc::new(tampered))
+        .unwrap_err();
+    assert!(matches!(err, ChainStorageError::ValidationError {
+        source: ValidationError::EmptyBlock
+    }));
+    assert_eq!(blockchain.db().get_height().unwrap(), 0);
+}
+
+#[tokio::test]
+async fn it_logs_blocks_above_the_slow_validation_threshold() {
+    let (blockchain, validator) = setup(true).await;
+    let (block, _) = blockchain.create_next_tip(block_spec!("A")).await;
+    let block = with_mmr_roots(&blockchain, block.block());
+
+    assert!(!validator.is_slow_validation(Duration::from_secs(1)));
+    assert!(validator.is_slow_validation(DEFAULT_SLOW_VALIDATION_THRESHOLD + Duration::from_millis(1)));
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    // Every block is above a zero threshold, which only logs a warning
+    let validator = validator.with_slow_validation_threshold(Duration::ZERO);
+    assert!(validator.is_slow_validation(Duration::from_nanos(1)));
+    assert!(!validator.is_slow_validation(Duration::ZERO));
+    validate_body(&validator, &*txn, &block, smt).unwrap();
+}
+
+#[tokio::test]
+async fn it_reports_the_outcome_of_validation() {
+    let (blockchain, validator) = setup(true).await;
+    let (block, _) = blockchain.create_next_tip(block_spec!("A")).await;
+    let block = with_mmr_roots(&blockchain, block.block());
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let header = ValidatedHeader::new_unchecked(block.header.clone());
+    let body = block.body.clone();
+    let report = validator.validate_body_report(&*txn, header, body, smt.clone());
+    assert!(report.accepted);
+    assert!(report.error.is_none());
+    assert_eq!(report.height, 1);
+    assert_eq!(report.num_outputs, block.body.outputs().len());
+    assert_eq!(report.num_kernels, block.body.kernels().len());
+    let total_fees = block.body.get_total_fee().unwrap();
+    assert_eq!(report.total_fees, Some(total_fees));
+    assert_eq!(
+        report.coinbase_value,
+        Some(blockchain.rules().get_block_reward_at(1) + total_fees)
+    );
+    let json: serde_json::Value = serde_json::from_str(&report.to_json()).unwrap();
+    assert_eq!(json["accepted"], true);
+    assert_eq!(json["block_hash"], block.hash().to_hex());
+
+    let mut unbalanced = block.clone();
+    unbalanced.header.total_kernel_offset = PrivateKey::from(1u64);
+    let header = ValidatedHeader::new_unchecked(unbalanced.header);
+    let report = validator.validate_body_report(&*txn, header, unbalanced.body, smt);
+    assert!(!report.accepted);
+    let error = report.error.unwrap();
+    assert_eq!(error.classification, ValidationErrorClass::LongBan);
+    // The balance is checked in the internal consistency phase, so the roots were not checked
+    assert_eq!(report.timings.mmr_roots, Duration::ZERO);
+}
+
+#[tokio::test]
+async fn it_collects_validation_stats() {
+    let (mut blockchain, validator) = setup(true).await;
+    let collector = Arc::new(ValidationStatsCollector::new());
+    let validator = validator.with_stats_collector(collector.clone());
+    assert_eq!(collector.summary().num_blocks, 0);
+    assert!(collector.summary().validation_time_percentile(50.0).is_none());
+
+    let block = create_coinbase_spend(&mut blockchain, |_| {}).await;
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let block = with_mmr_roots(&blockchain, block.block());
+    let validated = validate_body(&validator, &*txn, &block, smt.clone()).unwrap();
+    let expected_fee = validated.body.get_total_fee().unwrap();
+
+    // Without its kernels, the block is rejected
+    let mut invalid_block = block.clone();
+    invalid_block.body = AggregateBody::new(
+        invalid_block.body.inputs().clone(),
+        invalid_block.body.outputs().clone(),
+        vec![],
+    );
+    validate_body(&validator, &*txn, &invalid_block, smt).unwrap_err();
+
+    let stats = collector.summary();
+    assert_eq!(stats.num_blocks, 2);
+    assert_eq!(stats.num_rejected, 1);
+    assert_eq!(stats.total_inputs, 2);
+    assert_eq!(stats.total_outputs, 2 * validated.body.outputs().len() as u64);
+    assert_eq!(stats.total_kernels, validated.body.kernels().len() as u64);
+    assert_eq!(stats.total_fees, expected_fee);
+    assert_eq!(stats.avg_inputs(), 1.0);
+    let median = stats.validation_time_percentile(50.0).unwrap();
+    assert!(median <= stats.validation_time_percentile(100.0).unwrap());
+    assert!(stats.validation_time_percentile(100.0).unwrap() <= stats.total_validation_time());
+
+    collector.reset();
+    assert_eq!(collector.summary().num_blocks, 0);
+}
+
+#[test]
+fn it_keeps_validation_times_in_fixed_buckets() {
+    let collector = ValidationStatsCollector::new();
+    let block = Block::new(BlockHeader::new(0), AggregateBody::empty());
+    for millis in [3, 30, 300, 200_000] {
+        collector.record(&block, Duration::from_millis(millis), true);
+    }
+
+    // Each time is reported as the upper bound of its bucket, capped at the longest time
+    let stats = collector.summary();
+    assert_eq!(stats.validation_time_percentile(0.0), Some(Duration::from_millis(5)));
+    assert_eq!(stats.validation_time_percentile(25.0), Some(Duration::from_millis(5)));
+    assert_eq!(stats.validation_time_percentile(50.0), Some(Duration::from_millis(50)));
+    assert_eq!(stats.validation_time_percentile(75.0), Some(Duration::from_millis(500)));
+    assert_eq!(stats.validation_time_percentile(100.0), Some(Duration::from_secs(200)));
+    assert_eq!(stats.total_validation_time(), Duration::from_millis(200_333));
+}
+
 #[tokio::test]
 async fn it_limits_the_encrypted_data_byte_size() {
     let rules = ConsensusManager::builder(Network::LocalNet)
@@ -443,7 +1312,7 @@ async fn it_limits_the_encrypted_data_byte_size() {
 
     let txn = blockchain.db().db_read_access().unwrap();
     let smt = blockchain.db().smt();
-    let err = validator.validate_body(&*txn, block.block(), smt).unwrap_err();
+    let err = validate_body(&validator, &*txn, block.block(), smt).unwrap_err();
     assert!(matches!(err, ValidationError::EncryptedDataExceedsMaxSize { .. }));
 }
 
@@ -469,7 +1338,7 @@ async fn it_rejects_invalid_input_metadata() {
 
     let txn = blockchain.db().db_read_access().unwrap();
     let smt = blockchain.db().smt();
-    let err = validator.validate_body(&*txn, block.block(), smt).unwrap_err();
+    let err = validate_body(&validator, &*txn, block.block(), smt).unwrap_err();
     assert!(matches!(err, ValidationError::UnknownInputs(_)));
 }
 
@@ -499,7 +1368,7 @@ async fn it_rejects_zero_conf_double_spends() {
         .await;
     let smt = blockchain.db().smt();
     let txn = blockchain.db().db_read_access().unwrap();
-    let err = validator.validate_body(&*txn, &unmined, smt).unwrap_err();
+    let err = validate_body(&validator, &*txn, &unmined, smt).unwrap_err();
     assert!(matches!(err, ValidationError::UnsortedOrDuplicateInput));
 }
 
@@ -714,4 +1583,199 @@ mod orphan_validator {
         unpack_enum!(ValidationError::OutputTypeNotMatchedToRangeProofType { output_type } = err);
         assert!(output_type == OutputType::Standard || output_type == OutputType::Coinbase);
     }
+
+    #[tokio::test]
+    async fn it_rejects_an_incorrect_header_script_offset() {
+        let rules = ConsensusManager::builder(Network::LocalNet)
+            .add_consensus_constants(
+                ConsensusConstantsBuilder::new(Network::LocalNet)
+                    .with_coinbase_lockheight(0)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+        let mut blockchain = TestBlockchain::create(rules.clone()).await;
+        let validator = BlockBodyInternalConsistencyValidator::new(rules, true, CryptoFactories::default());
+        let (_, coinbase) = blockchain.append(block_spec!("1", parent: "GB")).await.unwrap();
+
+        let schema = txn_schema!(from: vec![coinbase.clone()], to: vec![201 * T]);
+        let (tx, _) = schema_to_transaction(&[schema], &blockchain.km).await;
+
+        let transactions = tx.into_iter().map(|b| Arc::try_unwrap(b).unwrap()).collect::<Vec<_>>();
+
+        let (mut unmined, _) = blockchain
+            .create_unmined_block(block_spec!("2", parent: "1", transactions: transactions))
+            .await;
+        validator.validate(&unmined).unwrap();
+
+        unmined.header.total_script_offset = &unmined.header.total_script_offset + &PrivateKey::from(1u64);
+        let err = validator.validate(&unmined).unwrap_err();
+        assert!(matches!(
+            err,
+            ValidationError::TransactionError(TransactionError::ScriptOffset)
+        ));
+    }
+
+    #[tokio::test]
+    async fn it_rejects_cut_through_blocks() {
+        let rules = ConsensusManager::builder(Network::LocalNet)
+            .add_consensus_constants(
+                ConsensusConstantsBuilder::new(Network::LocalNet)
+                    .with_coinbase_lockheight(0)
+                    .build(),
+            )
+            .build()
+            .unwrap();
+        let mut blockchain = TestBlockchain::create(rules.clone()).await;
+        let validator = BlockBodyInternalConsistencyValidator::new(rules, true, CryptoFactories::default());
+        let (_, coinbase) = blockchain.append(block_spec!("1", parent: "GB")).await.unwrap();
+
+        let schema = txn_schema!(from: vec![coinbase.clone()], to: vec![201 * T]);
+        let (initial_tx, outputs) = schema_to_transaction(&[schema], &blockchain.km).await;
+
+        let schema = txn_schema!(from: vec![outputs[0].clone()], to: vec![200 * T]);
+        let (zero_conf_spend, _) = schema_to_transaction(&[schema], &blockchain.km).await;
+
+        let transactions = initial_tx
+            .into_iter()
+            .chain(zero_conf_spend)
+            .map(|b| Arc::try_unwrap(b).unwrap())
+            .collect::<Vec<_>>();
+
+        let (mut unmined, _) = blockchain
+            .create_unmined_block(block_spec!("2", parent: "1", transactions: transactions))
+            .await;
+        // Spending an output in the block that creates it is allowed
+        validator.validate(&unmined).unwrap();
+
+        // Removing the output and the input that spends it is not, since it breaks the script offset
+        let mut inputs = unmined.body.inputs().clone();
+        let mut outputs = unmined.body.outputs().clone();
+        let spent = outputs
+            .iter()
+            .filter(|o| inputs.iter().any(|i| i.is_equal_to(o)))
+            .cloned()
+            .collect::<Vec<_>>();
+        assert_eq!(spent.len(), 1);
+        inputs.retain(|i| !i.is_equal_to(&spent[0]));
+        outputs.retain(|o| o != &spent[0]);
+        unmined.body = AggregateBody::new(inputs, outputs, unmined.body.kernels().clone());
+        unmined.body.sort();
+
+        let err = validator.validate(&unmined).unwrap_err();
+        assert!(matches!(
+            err,
+            ValidationError::TransactionError(TransactionError::ScriptOffset)
+        ));
+    }
+}
+
+mod generic_validator {
+    use super::*;
+    use crate::validation::block_body::GenericBlockBodyValidator;
+
+    #[tokio::test]
+    async fn it_skips_the_network_emission_rules() {
+        let (blockchain, validator) = setup(true).await;
+        let generic_validator = GenericBlockBodyValidator::new(false, CryptoFactories::default());
+
+        let (block, _) = blockchain
+            .create_chained_block(block_spec!("A", parent: "GB", reward: 10 * T, ))
+            .await;
+        let block = with_mmr_roots(&blockchain, block.block());
+        let txn = blockchain.db().db_read_access().unwrap();
+        let smt = blockchain.db().smt();
+        assert!(validate_body(&validator, &*txn, &block, smt).is_err());
+
+        let result = generic_validator.validate(&block).unwrap();
+        assert_eq!(result.block_hash, block.hash());
+        assert_eq!(result.height, 1);
+    }
+
+    #[tokio::test]
+    async fn it_checks_the_balance_of_the_body() {
+        let (mut blockchain, _) = setup(true).await;
+        let generic_validator = GenericBlockBodyValidator::new(false, CryptoFactories::default());
+        let (_, coinbase_a) = blockchain.add_next_tip(block_spec!("A")).await.unwrap();
+
+        let schema = txn_schema!(from: vec![coinbase_a.clone()], to: vec![50 * T]);
+        let (txs, _) = schema_to_transaction(&[schema], &blockchain.km).await;
+        let txs = txs.into_iter().map(|t| Arc::try_unwrap(t).unwrap()).collect::<Vec<_>>();
+
+        let (mut block, _) = blockchain
+            .create_unmined_block(block_spec!("B->A", transactions: txs))
+            .await;
+        assert!(generic_validator.validate(&block).is_ok());
+
+        block.header.total_kernel_offset = &block.header.total_kernel_offset + &PrivateKey::from(1u64);
+        let err = generic_validator.validate(&block).unwrap_err();
+        assert!(matches!(err, ValidationError::InvalidAccountingBalance));
+    }
+}
+
+#[tokio::test]
+async fn it_rejects_corrupt_range_proofs_and_metadata_signatures() {
+    let (mut blockchain, validator) = setup(false).await;
+    let block = create_coinbase_spend(&mut blockchain, |_| {}).await;
+    let block = with_mmr_roots(&blockchain, block.block());
+    let index = block
+        .body
+        .outputs()
+        .iter()
+        .position(|o| !o.is_coinbase() && o.features.range_proof_type == RangeProofType::BulletProofPlus)
+        .unwrap();
+    let with_output = |output: TransactionOutput| {
+        let mut outputs = block.body.outputs().clone();
+        outputs[index] = output;
+        let mut block = block.clone();
+        block.body = AggregateBody::new(block.body.inputs().clone(), outputs, block.body.kernels().clone());
+        block
+    };
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    let mut output = block.body.outputs()[index].clone();
+    let proof = output.proof.as_mut().unwrap();
+    let middle = proof.0.len() / 2;
+    proof.0[middle] ^= 0xff;
+    let err = validate_body(&validator, &*txn, &with_output(output), smt.clone()).unwrap_err();
+    assert!(matches!(
+        err,
+        ValidationError::TransactionError(TransactionError::RangeProofError(_))
+    ));
+
+    let mut output = block.body.outputs()[index].clone();
+    output.metadata_signature = Default::default();
+    let err = validate_body(&validator, &*txn, &with_output(output), smt).unwrap_err();
+    assert!(matches!(
+        err,
+        ValidationError::TransactionError(TransactionError::InvalidSignatureError(_))
+    ));
+}
+
+#[tokio::test]
+async fn it_batch_verifies_metadata_signatures() {
+    let (mut blockchain, _) = setup(false).await;
+    let config = BlockBodyFullValidatorConfig {
+        batch_metadata_signature_verification: true,
+        ..Default::default()
+    };
+    let validator = BlockBodyFullValidator::from_config(blockchain.rules().clone(), CryptoFactories::default(), config);
+    let block = create_coinbase_spend(&mut blockchain, |_| {}).await;
+    let block = with_mmr_roots(&blockchain, block.block());
+
+    let txn = blockchain.db().db_read_access().unwrap();
+    let smt = blockchain.db().smt();
+    validate_body(&validator, &*txn, &block, smt.clone()).unwrap();
+
+    let mut outputs = block.body.outputs().clone();
+    let index = outputs.iter().position(|o| !o.is_coinbase()).unwrap();
+    outputs[index].metadata_signature = outputs[(index + 1) % outputs.len()].metadata_signature.clone();
+    let mut block = block.clone();
+    block.body = AggregateBody::new(block.body.inputs().clone(), outputs, block.body.kernels().clone());
+    let err = validate_body(&validator, &*txn, &block, smt).unwrap_err();
+    assert!(matches!(
+        err,
+        ValidationError::TransactionError(TransactionError::InvalidSignatureError(_))
+    ));
 }
@@ -0,0 +1,72 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{collections::HashMap, time::Instant};
+
+use tari_common_types::types::BlockHash;
+
+use crate::blocks::Block;
+
+/// A bounded cache of blocks that passed body validation, keyed by block hash. When full, the least recently used
+/// entry is evicted.
+pub(super) struct ValidatedBlockCache {
+    blocks: HashMap<BlockHash, (Instant, Block)>,
+    capacity: usize,
+}
+
+impl ValidatedBlockCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            blocks: HashMap::with_capacity(capacity),
+            capacity,
+        }
+    }
+
+    /// Returns the cached validated block with the given hash, if any, and marks it as recently used
+    pub fn get(&mut self, hash: &BlockHash) -> Option<Block> {
+        let entry = self.blocks.get_mut(hash)?;
+        entry.0 = Instant::now();
+        Some(entry.1.clone())
+    }
+
+    pub fn insert(&mut self, hash: BlockHash, block: Block) {
+        if self.capacity == 0 {
+            return;
+        }
+        if !self.blocks.contains_key(&hash) && self.blocks.len() >= self.capacity {
+            let oldest_key = self
+                .blocks
+                .iter()
+                .min_by_key(|(_, (last_used, _))| *last_used)
+                .map(|(k, _)| *k);
+            if let Some(k) = oldest_key {
+                self.blocks.remove(&k);
+            }
+        }
+        self.blocks.insert(hash, (Instant::now(), block));
+    }
+
+    /// Removes all blocks above the given height, e.g. after the chain was reorged to below them
+    pub fn remove_above_height(&mut self, height: u64) {
+        self.blocks.retain(|_, (_, block)| block.header.height <= height);
+    }
+}
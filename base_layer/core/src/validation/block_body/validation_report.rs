@@ -0,0 +1,142 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{fmt, time::Duration};
+
+use serde::Serialize;
+use tari_utilities::hex::Hex;
+
+use crate::{
+    blocks::Block,
+    common::BanPeriod,
+    consensus::ConsensusManager,
+    transactions::tari_amount::MicroMinotari,
+    validation::ValidationError,
+};
+
+/// The outcome of validating a block with
+/// [validate_body_report](super::BlockBodyFullValidator::validate_body_report), for tooling that consumes validation
+/// results as JSON.
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationReport {
+    pub block_hash: String,
+    pub height: u64,
+    pub accepted: bool,
+    /// The time validation took in total, including the header linkage check that is not part of any phase
+    pub elapsed: Duration,
+    pub timings: PhaseTimings,
+    pub num_inputs: usize,
+    pub num_outputs: usize,
+    pub num_kernels: usize,
+    /// The sum of the kernel fees, or `None` if it overflows
+    pub total_fees: Option<MicroMinotari>,
+    /// The value the coinbase outputs must add up to, i.e. the block reward at the block's height plus the fees, or
+    /// `None` if it overflows
+    pub coinbase_value: Option<MicroMinotari>,
+    /// Why the block was rejected, or `None` if it was accepted
+    pub error: Option<ValidationErrorReport>,
+}
+
+impl ValidationReport {
+    pub(super) fn new(
+        block: &Block,
+        rules: &ConsensusManager,
+        elapsed: Duration,
+        timings: PhaseTimings,
+        result: Result<(), &ValidationError>,
+    ) -> Self {
+        let total_fees = block.body.get_total_fee().ok();
+        let coinbase_value =
+            total_fees.and_then(|fees| rules.get_block_reward_at(block.header.height).checked_add(fees));
+        Self {
+            block_hash: block.hash().to_hex(),
+            height: block.header.height,
+            accepted: result.is_ok(),
+            elapsed,
+            timings,
+            num_inputs: block.body.inputs().len(),
+            num_outputs: block.body.outputs().len(),
+            num_kernels: block.body.kernels().len(),
+            total_fees,
+            coinbase_value,
+            error: result.err().map(ValidationErrorReport::new),
+        }
+    }
+
+    /// Returns the report as JSON
+    pub fn to_json(&self) -> String {
+        // Every field serializes to a JSON value, so this cannot fail
+        serde_json::to_string(self).unwrap_or_default()
+    }
+}
+
+/// A validation error, classified by how the peer that sent the block is treated
+#[derive(Debug, Clone, Serialize)]
+pub struct ValidationErrorReport {
+    pub message: String,
+    pub classification: ValidationErrorClass,
+}
+
+impl ValidationErrorReport {
+    fn new(error: &ValidationError) -> Self {
+        let classification = match error.get_ban_reason().map(|reason| reason.ban_duration()) {
+            Some(BanPeriod::Long) => ValidationErrorClass::LongBan,
+            Some(BanPeriod::Short) => ValidationErrorClass::ShortBan,
+            None => ValidationErrorClass::NoBan,
+        };
+        Self {
+            message: error.to_string(),
+            classification,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ValidationErrorClass {
+    /// The block is invalid and the peer that sent it is banned for a long period
+    LongBan,
+    /// The block is invalid and the peer that sent it is banned for a short period
+    ShortBan,
+    /// The block could not be validated for a reason that is not the peer's fault, e.g. a local storage error
+    NoBan,
+}
+
+/// The time spent in each phase of body validation. A phase that was not reached because an earlier phase failed is
+/// left at zero.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PhaseTimings {
+    pub checks: Duration,
+    pub chain_linked: Duration,
+    pub internal_consistency: Duration,
+    pub mmr_roots: Duration,
+}
+
+impl fmt::Display for PhaseTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "checks: {:.2?}, chain linked: {:.2?}, internal consistency: {:.2?}, mmr roots: {:.2?}",
+            self.checks, self.chain_linked, self.internal_consistency, self.mmr_roots
+        )
+    }
+}
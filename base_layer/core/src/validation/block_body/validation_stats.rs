@@ -0,0 +1,165 @@
+//  Copyright 2024, The Tari Project
+//
+//  Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+//  following conditions are met:
+//
+//  1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+//  disclaimer.
+//
+//  2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+//  following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+//  3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+//  products derived from this software without specific prior written permission.
+//
+//  THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+//  INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+//  DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+//  SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+//  SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+//  WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+//  USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use std::{
+    sync::{Mutex, MutexGuard, PoisonError},
+    time::Duration,
+};
+
+use crate::{blocks::Block, transactions::tari_amount::MicroMinotari};
+
+/// The upper bounds of the buckets validation times are counted in, so that the statistics take the same space however
+/// many blocks are recorded. Times above the last bound are counted in one more bucket.
+const VALIDATION_TIME_BUCKETS: [Duration; 16] = [
+    Duration::from_millis(1),
+    Duration::from_millis(2),
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(20),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(200),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_secs(2),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+    Duration::from_secs(20),
+    Duration::from_secs(50),
+    Duration::from_secs(100),
+];
+
+/// Collects statistics over the blocks a [BlockBodyFullValidator](super::BlockBodyFullValidator) validates, e.g.
+/// to profile validation during an initial sync. A collector can be shared between several validators.
+#[derive(Debug, Default)]
+pub struct ValidationStatsCollector {
+    inner: Mutex<ValidationStats>,
+}
+
+impl ValidationStatsCollector {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Records the outcome of validating `block`, which took `elapsed`
+    pub fn record(&self, block: &Block, elapsed: Duration, accepted: bool) {
+        let mut stats = self.lock();
+        stats.num_blocks += 1;
+        if !accepted {
+            stats.num_rejected += 1;
+        }
+        stats.total_inputs += block.body.inputs().len() as u64;
+        stats.total_outputs += block.body.outputs().len() as u64;
+        stats.total_kernels += block.body.kernels().len() as u64;
+        // The fee of a rejected block may not be valid, so it is not summed
+        if accepted {
+            stats.total_fees = stats
+                .total_fees
+                .checked_add(block.body.get_total_fee().unwrap_or_default())
+                .unwrap_or(stats.total_fees);
+        }
+        let bucket = VALIDATION_TIME_BUCKETS.partition_point(|bound| *bound < elapsed);
+        stats.validation_time_counts[bucket] += 1;
+        stats.total_validation_time = stats.total_validation_time.saturating_add(elapsed);
+        stats.max_validation_time = stats.max_validation_time.max(elapsed);
+    }
+
+    /// Returns a snapshot of the statistics collected so far
+    pub fn summary(&self) -> ValidationStats {
+        self.lock().clone()
+    }
+
+    /// Discards all statistics collected so far
+    pub fn reset(&self) {
+        *self.lock() = ValidationStats::default();
+    }
+
+    fn lock(&self) -> MutexGuard<'_, ValidationStats> {
+        // The statistics are only ever updated field by field, so a poisoned lock still holds usable values
+        self.inner.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Statistics over a number of validated blocks
+#[derive(Debug, Clone, Default)]
+pub struct ValidationStats {
+    pub num_blocks: u64,
+    pub num_rejected: u64,
+    pub total_inputs: u64,
+    pub total_outputs: u64,
+    pub total_kernels: u64,
+    /// The sum of the fees of the accepted blocks
+    pub total_fees: MicroMinotari,
+    validation_time_counts: [u64; VALIDATION_TIME_BUCKETS.len() + 1],
+    total_validation_time: Duration,
+    max_validation_time: Duration,
+}
+
+impl ValidationStats {
+    pub fn avg_inputs(&self) -> f64 {
+        self.average(self.total_inputs)
+    }
+
+    pub fn avg_outputs(&self) -> f64 {
+        self.average(self.total_outputs)
+    }
+
+    pub fn avg_kernels(&self) -> f64 {
+        self.average(self.total_kernels)
+    }
+
+    /// The total time spent validating the blocks
+    pub fn total_validation_time(&self) -> Duration {
+        self.total_validation_time
+    }
+
+    /// Returns a time that `percentile` percent of the blocks were validated within (nearest rank), or `None` if no
+    /// blocks were recorded. `percentile` is clamped to 0..=100. The times are only kept per bucket, so this is the
+    /// upper bound of the bucket the rank falls in, capped at the longest time recorded.
+    pub fn validation_time_percentile(&self, percentile: f64) -> Option<Duration> {
+        if self.num_blocks == 0 {
+            return None;
+        }
+        let percentile = percentile.clamp(0.0, 100.0);
+        #[allow(clippy::cast_possible_truncation)]
+        let rank = (((percentile / 100.0) * self.num_blocks as f64).ceil() as u64).max(1);
+        let mut count = 0;
+        for (bucket, bucket_count) in self.validation_time_counts.iter().enumerate() {
+            count += bucket_count;
+            if count >= rank {
+                let bound = VALIDATION_TIME_BUCKETS
+                    .get(bucket)
+                    .copied()
+                    .unwrap_or(self.max_validation_time);
+                return Some(bound.min(self.max_validation_time));
+            }
+        }
+        Some(self.max_validation_time)
+    }
+
+    fn average(&self, total: u64) -> f64 {
+        if self.num_blocks == 0 {
+            return 0.0;
+        }
+        total as f64 / self.num_blocks as f64
+    }
+}
@@ -21,6 +21,7 @@
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
 use tari_common_types::types::HashOutput;
+use tari_utilities::epoch_time::EpochTime;
 use thiserror::Error;
 
 use crate::{
@@ -88,6 +89,14 @@ pub enum ValidationError {
     IncorrectHeight { expected: u64, block_height: u64 },
     #[error("Expected block previous hash to be {expected}, but was {block_hash}")]
     IncorrectPreviousHash { expected: String, block_hash: String },
+    #[error("Block {block_hash} does not link to a known header (prev_hash: {prev_hash})")]
+    OrphanBlock { block_hash: String, prev_hash: String },
+    #[error("Block header {kind} MMR size is {header_size}, but the block body implies {body_size}")]
+    BodyHeaderCountMismatch {
+        kind: &'static str,
+        header_size: u64,
+        body_size: u64,
+    },
     #[error("Bad block with hash {hash} found")]
     BadBlockFound { hash: String, reason: String },
     #[error("Script exceeded maximum script size, expected less than {max_script_size} but was {actual_script_size}")]
@@ -113,6 +122,31 @@ pub enum ValidationError {
     InvalidBlockchainVersion { version: u16 },
     #[error("Contains Invalid Burn: {0}")]
     InvalidBurnError(String),
+    #[error("Coinbase kernel {kernel} has a non-zero fee")]
+    CoinbaseKernelHasFee { kernel: String },
+    #[error("Burn kernel {kernel} has no burn commitment")]
+    BurnKernelWithoutCommitment { kernel: String },
+    #[error("Kernel {kernel} has a burn commitment but no burn feature")]
+    UnexpectedBurnCommitment { kernel: String },
+    #[error("Output value {value} is below the minimum output value {min}")]
+    DustOutput { value: MicroMinotari, min: MicroMinotari },
+    #[error("Block body is empty: a block must contain at least a coinbase output and a coinbase kernel")]
+    EmptyBlock,
+    #[error("Output verification panicked (output index: {index:?})")]
+    OutputVerificationPanic { index: Option<usize> },
+    #[error("Block timestamp {timestamp} is earlier than the median timestamp {median_timestamp}")]
+    TimestampTooEarly {
+        timestamp: EpochTime,
+        median_timestamp: EpochTime,
+    },
+    #[error("Input spends a coinbase at height {spending_height} before it matures at height {mature_height}")]
+    ImmatureCoinbaseSpend { spending_height: u64, mature_height: u64 },
+    #[error("The revealed value of output {index} does not match its commitment")]
+    RevealedValueMismatch { index: usize },
+    #[error("The serialized block size ({size} bytes) is above the maximum ({max} bytes)")]
+    BlockSerializedSizeTooLarge { size: usize, max: usize },
+    #[error("The encrypted value of output {index} does not match its commitment")]
+    EncryptedValueMismatch { index: usize },
     #[error("Output type '{output_type}' is not permitted")]
     OutputTypeNotPermitted { output_type: OutputType },
     #[error("Range proof type '{range_proof_type}' is not permitted")]
@@ -134,6 +168,12 @@ pub enum ValidationError {
     DifficultyError(#[from] DifficultyError),
     #[error("Covenant too large. Max size: {max_size}, Actual size: {actual_size}")]
     CovenantTooLarge { max_size: usize, actual_size: usize },
+    #[error("The sender offset public key of output {index} is the identity point")]
+    InvalidSenderOffsetKey { index: usize },
+    #[error("Internal validation error: {0}")]
+    Internal(String),
+    #[error("Output maturity {maturity} is beyond the highest maturity {max_maturity} permitted at this height")]
+    InvalidOutputActivationHeight { maturity: u64, max_maturity: u64 },
 }
 
 // ChainStorageError has a ValidationError variant, so to prevent a cyclic dependency we use a string representation in
@@ -153,6 +193,7 @@ impl ValidationError {
             err @ ValidationError::BlockError(_) |
             err @ ValidationError::MaturityError |
             err @ ValidationError::BlockTooLarge { .. } |
+            err @ ValidationError::BlockSerializedSizeTooLarge { .. } |
             err @ ValidationError::UnknownInputs(_) |
             err @ ValidationError::UnknownInput |
             err @ ValidationError::TransactionError(_) |
@@ -168,6 +209,7 @@ impl ValidationError {
             err @ ValidationError::MaxTransactionWeightExceeded |
             err @ ValidationError::IncorrectHeight { .. } |
             err @ ValidationError::IncorrectPreviousHash { .. } |
+            err @ ValidationError::BodyHeaderCountMismatch { .. } |
             err @ ValidationError::BadBlockFound { .. } |
             err @ ValidationError::TariScriptExceedsMaxSize { .. } |
             err @ ValidationError::EncryptedDataExceedsMaxSize { .. } |
@@ -176,6 +218,14 @@ impl ValidationError {
             err @ ValidationError::CovenantError(_) |
             err @ ValidationError::InvalidBlockchainVersion { .. } |
             err @ ValidationError::InvalidBurnError(_) |
+            err @ ValidationError::CoinbaseKernelHasFee { .. } |
+            err @ ValidationError::BurnKernelWithoutCommitment { .. } |
+            err @ ValidationError::UnexpectedBurnCommitment { .. } |
+            err @ ValidationError::DustOutput { .. } |
+            err @ ValidationError::EmptyBlock |
+            err @ ValidationError::TimestampTooEarly { .. } |
+            err @ ValidationError::ImmatureCoinbaseSpend { .. } |
+            err @ ValidationError::RevealedValueMismatch { .. } |
             err @ ValidationError::OutputTypeNotPermitted { .. } |
             err @ ValidationError::RangeProofTypeNotPermitted { .. } |
             err @ ValidationError::OutputTypeNotMatchedToRangeProofType { .. } |
@@ -184,12 +234,20 @@ impl ValidationError {
             err @ ValidationError::InvalidValidatorNodeSignature |
             err @ ValidationError::DifficultyError(_) |
             err @ ValidationError::CoinbaseExceedsMaxLimit |
-            err @ ValidationError::CovenantTooLarge { .. } => Some(BanReason {
+            err @ ValidationError::CovenantTooLarge { .. } |
+            err @ ValidationError::InvalidSenderOffsetKey { .. } |
+            err @ ValidationError::InvalidOutputActivationHeight { .. } => Some(BanReason {
                 reason: err.to_string(),
                 ban_duration: BanPeriod::Long,
             }),
             ValidationError::MergeMineError(e) => e.get_ban_reason(),
-            ValidationError::FatalStorageError(_) | ValidationError::IncorrectNumberOfTimestampsProvided { .. } => None,
+            ValidationError::FatalStorageError(_) |
+            ValidationError::Internal(_) |
+            ValidationError::IncorrectNumberOfTimestampsProvided { .. } |
+            ValidationError::OrphanBlock { .. } |
+            ValidationError::OutputVerificationPanic { .. } |
+            // The encrypted data is not covered by consensus, so a block that fails this check may still be valid
+            ValidationError::EncryptedValueMismatch { .. } => None,
         }
     }
 }
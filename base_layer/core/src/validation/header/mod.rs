@@ -22,3 +22,6 @@
 
 mod header_full_validator;
 pub use header_full_validator::HeaderFullValidator;
+
+mod validated_header;
+pub use validated_header::ValidatedHeader;
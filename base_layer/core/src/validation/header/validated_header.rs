@@ -0,0 +1,57 @@
+// Copyright 2024. The Tari Project
+//
+// Redistribution and use in source and binary forms, with or without modification, are permitted provided that the
+// following conditions are met:
+//
+// 1. Redistributions of source code must retain the above copyright notice, this list of conditions and the following
+// disclaimer.
+//
+// 2. Redistributions in binary form must reproduce the above copyright notice, this list of conditions and the
+// following disclaimer in the documentation and/or other materials provided with the distribution.
+//
+// 3. Neither the name of the copyright holder nor the names of its contributors may be used to endorse or promote
+// products derived from this software without specific prior written permission.
+//
+// THIS SOFTWARE IS PROVIDED BY THE COPYRIGHT HOLDERS AND CONTRIBUTORS "AS IS" AND ANY EXPRESS OR IMPLIED WARRANTIES,
+// INCLUDING, BUT NOT LIMITED TO, THE IMPLIED WARRANTIES OF MERCHANTABILITY AND FITNESS FOR A PARTICULAR PURPOSE ARE
+// DISCLAIMED. IN NO EVENT SHALL THE COPYRIGHT HOLDER OR CONTRIBUTORS BE LIABLE FOR ANY DIRECT, INDIRECT, INCIDENTAL,
+// SPECIAL, EXEMPLARY, OR CONSEQUENTIAL DAMAGES (INCLUDING, BUT NOT LIMITED TO, PROCUREMENT OF SUBSTITUTE GOODS OR
+// SERVICES; LOSS OF USE, DATA, OR PROFITS; OR BUSINESS INTERRUPTION) HOWEVER CAUSED AND ON ANY THEORY OF LIABILITY,
+// WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
+// USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
+
+use crate::blocks::{BlockHeader, ChainHeader};
+
+/// A block header that passed header validation, so its proof of work, timestamp and linkage to its parent are known
+/// to be valid. Body validation relies on this, so [BlockBodyValidator](crate::validation::BlockBodyValidator) only
+/// accepts headers in this form. Header validation produces it with
+/// [validate_header](crate::validation::HeaderChainLinkedValidator::validate_header), and the headers of the chain are
+/// converted into one with [from_chain_header](Self::from_chain_header), since a header is only added to the chain
+/// once it was validated. No other crate can build one, except through the test seam in
+/// [mocks](crate::validation::mocks::mock_validated_header).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatedHeader {
+    header: BlockHeader,
+}
+
+impl ValidatedHeader {
+    /// Wraps a header without validating it. The caller must have validated the header by other means, e.g. in tests
+    /// that only exercise body validation.
+    pub(crate) fn new_unchecked(header: BlockHeader) -> Self {
+        Self { header }
+    }
+
+    /// Takes a header of the chain as validated. Only headers read from the chain database may be passed in.
+    pub(crate) fn from_chain_header(header: ChainHeader) -> Self {
+        let (header, _) = header.into_parts();
+        Self { header }
+    }
+
+    pub fn header(&self) -> &BlockHeader {
+        &self.header
+    }
+
+    pub fn into_header(self) -> BlockHeader {
+        self.header
+    }
+}
@@ -20,17 +20,20 @@
 // WHETHER IN CONTRACT, STRICT LIABILITY, OR TORT (INCLUDING NEGLIGENCE OR OTHERWISE) ARISING IN ANY WAY OUT OF THE
 // USE OF THIS SOFTWARE, EVEN IF ADVISED OF THE POSSIBILITY OF SUCH DAMAGE.
 
-use std::convert::TryFrom;
+use std::{collections::HashSet, convert::TryFrom};
 
 use log::*;
-use tari_common_types::types::FixedHash;
-use tari_crypto::tari_utilities::{epoch_time::EpochTime, hex::Hex};
+use tari_common_types::types::{Commitment, FixedHash, HashOutput, PrivateKey};
+use tari_crypto::{
+    commitment::HomomorphicCommitmentFactory,
+    tari_utilities::{epoch_time::EpochTime, hex::Hex},
+};
 use tari_script::TariScript;
 
 use crate::{
-    blocks::{BlockHeader, BlockHeaderValidationError, BlockValidationError},
+    blocks::{Block, BlockHeader, BlockHeaderValidationError, BlockValidationError},
     borsh::SerializedSize,
-    chain_storage::{BlockchainBackend, MmrRoots, MmrTree},
+    chain_storage::{fetch_headers, BlockchainBackend, DbKey, DbValue, MmrRoots, MmrTree},
     consensus::{ConsensusConstants, ConsensusManager},
     covenants::Covenant,
     proof_of_work::{
@@ -42,12 +45,20 @@ use crate::{
         PowAlgorithm,
         PowError,
     },
-    transactions::transaction_components::{
-        encrypted_data::STATIC_ENCRYPTED_DATA_SIZE_TOTAL,
-        EncryptedData,
-        TransactionInput,
-        TransactionKernel,
-        TransactionOutput,
+    transactions::{
+        aggregated_body::AggregateBody,
+        tari_amount::MicroMinotari,
+        transaction_components::{
+            encrypted_data::STATIC_ENCRYPTED_DATA_SIZE_TOTAL,
+            EncryptedData,
+            KernelSum,
+            RangeProofType,
+            TransactionError,
+            TransactionInput,
+            TransactionKernel,
+            TransactionOutput,
+        },
+        CryptoFactories,
     },
     validation::ValidationError,
 };
@@ -122,6 +133,40 @@ pub fn check_header_timestamp_greater_than_median(
 
     Ok(())
 }
+
+/// Checks that the header timestamp is not earlier than the median timestamp of the main chain blocks before it. This
+/// is the same rule the header validator applies, for blocks whose header may not have been validated.
+pub fn check_median_time_past<B: BlockchainBackend>(
+    db: &B,
+    rules: &ConsensusManager,
+    header: &BlockHeader,
+) -> Result<(), ValidationError> {
+    if header.height == 0 {
+        return Ok(());
+    }
+    let timestamp_count = rules.consensus_constants(header.height).median_timestamp_count() as u64;
+    let timestamps = fetch_headers(db, header.height.saturating_sub(timestamp_count), header.height - 1)?
+        .iter()
+        .map(|h| h.timestamp)
+        .collect::<Vec<_>>();
+    let median_timestamp = calc_median_timestamp(&timestamps)?;
+    if header.timestamp < median_timestamp {
+        warn!(
+            target: LOG_TARGET,
+            "Block #{} {} timestamp {} is earlier than the median timestamp {}",
+            header.height,
+            header.hash().to_hex(),
+            header.timestamp,
+            median_timestamp
+        );
+        return Err(ValidationError::TimestampTooEarly {
+            timestamp: header.timestamp,
+            median_timestamp,
+        });
+    }
+    Ok(())
+}
+
 pub fn check_target_difficulty(
     block_header: &BlockHeader,
     target: Difficulty,
@@ -169,9 +214,42 @@ pub fn is_all_unique_and_sorted<'a, I: IntoIterator<Item = &'a T>, T: PartialOrd
     true
 }
 
+/// Checks that an input spending a coinbase output is only spent once the coinbase lock of the consensus rules has
+/// passed since the coinbase was mined. Unlike [TransactionInput::is_mature_at], this does not trust the maturity in
+/// the output features. `constants` must be the consensus constants at `mined_height`, the rules the coinbase was
+/// minted under. Inputs that do not spend a coinbase output are not checked.
+pub fn check_coinbase_maturity(
+    input: &TransactionInput,
+    mined_height: u64,
+    spending_height: u64,
+    constants: &ConsensusConstants,
+) -> Result<(), ValidationError> {
+    if !input.features()?.is_coinbase() {
+        return Ok(());
+    }
+    let mature_height = mined_height.saturating_add(constants.coinbase_min_maturity());
+    if spending_height < mature_height {
+        warn!(
+            target: LOG_TARGET,
+            "Input {} spends a coinbase mined at height {} before it matures at height {}",
+            input.output_hash().to_hex(),
+            mined_height,
+            mature_height
+        );
+        return Err(ValidationError::ImmatureCoinbaseSpend {
+            spending_height,
+            mature_height,
+        });
+    }
+    Ok(())
+}
+
 /// This function checks that an input is a valid spendable UTXO in the database. It cannot confirm
-/// zero confermation transactions.
-pub fn check_input_is_utxo<B: BlockchainBackend>(db: &B, input: &TransactionInput) -> Result<(), ValidationError> {
+/// zero confermation transactions. Returns the hash of the output the input spends.
+pub fn check_input_is_utxo<B: BlockchainBackend>(
+    db: &B,
+    input: &TransactionInput,
+) -> Result<HashOutput, ValidationError> {
     let output_hash = input.output_hash();
     if let Some(utxo_hash) = db.fetch_unspent_output_hash_by_commitment(input.commitment()?)? {
         // We know that the commitment exists in the UTXO set. Check that the output hash matches (i.e. all fields
@@ -179,7 +257,7 @@ pub fn check_input_is_utxo<B: BlockchainBackend>(db: &B, input: &TransactionInpu
         if utxo_hash == output_hash {
             // Because the retrieved hash matches the new input.output_hash() we know all the fields match and are all
             // still the same
-            return Ok(());
+            return Ok(output_hash);
         }
 
         let output = db.fetch_output(&utxo_hash)?;
@@ -263,6 +341,189 @@ pub fn check_not_duplicate_txo<B: BlockchainBackend>(
     Ok(())
 }
 
+/// Checks an output of a sorted scan over the outputs of a body, so that one pass catches both repeated outputs in the
+/// body and collisions with the UTXO set. `previous` is the output before it in the body, which it must sort after;
+/// an output that does not fails with [ValidationError::UnsortedOrDuplicateOutput]. An output whose commitment is
+/// already in the UTXO set fails with [ValidationError::ContainsDuplicateUtxoCommitment].
+pub fn check_output_unique_in_body_and_utxo_set<B: BlockchainBackend>(
+    db: &B,
+    previous: Option<&TransactionOutput>,
+    output: &TransactionOutput,
+) -> Result<(), ValidationError> {
+    if previous.is_some_and(|previous| output <= previous) {
+        warn!(
+            target: LOG_TARGET,
+            "Output {} is out of order or repeated in the body", output
+        );
+        return Err(ValidationError::UnsortedOrDuplicateOutput);
+    }
+    check_not_duplicate_txo(db, output)
+}
+
+/// Checks the coinbase of the block at `height` in isolation, given only its coinbase output(s) and kernel and the
+/// total fees of the block. This checks that the coinbase maturity is set correctly for the height and that the
+/// coinbase value equals the block reward plus the fees.
+pub fn check_coinbase_reward(
+    rules: &ConsensusManager,
+    height: u64,
+    coinbase_outputs: &[TransactionOutput],
+    coinbase_kernel: &TransactionKernel,
+    total_fees: MicroMinotari,
+    factories: &CryptoFactories,
+) -> Result<(), ValidationError> {
+    let reward = rules
+        .calculate_coinbase_and_fees(height, &[])
+        .ok()
+        .and_then(|block_reward| block_reward.checked_add(total_fees))
+        .ok_or(ValidationError::CoinbaseExceedsMaxLimit)?;
+    let body = AggregateBody::new(vec![], coinbase_outputs.to_vec(), vec![coinbase_kernel.clone()]);
+    body.check_coinbase_output(
+        reward,
+        rules.consensus_constants(height).coinbase_min_maturity(),
+        factories,
+        height,
+    )?;
+    Ok(())
+}
+
+/// Checks only that the inputs, outputs and kernels balance, i.e. that the outputs minus the inputs plus the fees sum
+/// to the kernel excesses plus the offset and the total reward. No signatures, scripts or range proofs are verified, so
+/// this is a cheap check for arithmetic mistakes while assembling a block. The total reward is the block reward plus
+/// the fees for a block, and zero for a transaction.
+pub fn check_balance_only(
+    inputs: &[TransactionInput],
+    outputs: &[TransactionOutput],
+    kernels: &[TransactionKernel],
+    offset: &PrivateKey,
+    total_reward: MicroMinotari,
+    factories: &CryptoFactories,
+) -> Result<(), ValidationError> {
+    trace!(target: LOG_TARGET, "Checking kernel total");
+    let offset_and_reward = factories.commitment.commit_value(offset, total_reward.0);
+    let KernelSum { sum: excess, fees } = sum_kernels(kernels, offset_and_reward)?;
+    let sum_io = sum_commitments(inputs, outputs)?;
+    trace!(target: LOG_TARGET, "Total outputs - inputs:{}", sum_io.to_hex());
+    let fees = factories.commitment.commit_value(&PrivateKey::default(), fees.into());
+    trace!(
+        target: LOG_TARGET,
+        "Comparing sum.  excess:{} == sum {} + fees {}",
+        excess.to_hex(),
+        sum_io.to_hex(),
+        fees.to_hex()
+    );
+    if excess != &sum_io + &fees {
+        return Err(ValidationError::InvalidAccountingBalance);
+    }
+
+    Ok(())
+}
+
+/// Calculate the sum of the kernels, taking into account the provided offset, and their constituent fees
+fn sum_kernels(kernels: &[TransactionKernel], offset_with_fee: Commitment) -> Result<KernelSum, ValidationError> {
+    // Sum all kernel excesses and fees
+    let mut kernel_sum = KernelSum {
+        fees: MicroMinotari(0),
+        sum: offset_with_fee,
+    };
+    for kernel in kernels {
+        kernel_sum.fees = kernel_sum
+            .fees
+            .checked_add(kernel.fee)
+            .ok_or(ValidationError::InvalidAccountingBalance)?;
+        kernel_sum.sum = &kernel_sum.sum + &kernel.excess;
+    }
+    Ok(kernel_sum)
+}
+
+/// Calculate the sum of the outputs - inputs. Each commitment is added exactly once, without collecting them first.
+fn sum_commitments(inputs: &[TransactionInput], outputs: &[TransactionOutput]) -> Result<Commitment, ValidationError> {
+    let sum_inputs = inputs.iter().try_fold(Commitment::default(), |sum, i| {
+        Ok::<_, ValidationError>(&sum + i.commitment()?)
+    })?;
+    let sum_outputs = outputs.iter().map(|o| &o.commitment).sum::<Commitment>();
+    Ok(&sum_outputs - &sum_inputs)
+}
+
+/// Checks that the header builds on a header known to the database and that its height follows on from it. This is
+/// cheap compared to body validation, so it can be used to reject unconnected blocks early.
+pub fn check_header_linkage<B: BlockchainBackend>(db: &B, header: &BlockHeader) -> Result<(), ValidationError> {
+    let prev_header = match db.fetch(&DbKey::HeaderHash(header.prev_hash))? {
+        Some(DbValue::HeaderHash(prev_header)) => prev_header,
+        _ => {
+            warn!(
+                target: LOG_TARGET,
+                "Block #{} {} does not link to a known header (prev_hash: {})",
+                header.height,
+                header.hash().to_hex(),
+                header.prev_hash.to_hex()
+            );
+            return Err(ValidationError::OrphanBlock {
+                block_hash: header.hash().to_hex(),
+                prev_hash: header.prev_hash.to_hex(),
+            });
+        },
+    };
+    if header.height != prev_header.height + 1 {
+        return Err(ValidationError::IncorrectHeight {
+            expected: prev_header.height + 1,
+            block_height: header.height,
+        });
+    }
+    Ok(())
+}
+
+/// Checks that the number of kernels and outputs in the block body accounts for the growth of the kernel MMR and
+/// output SMT sizes in the header since the previous header. This is a cheap pre-filter for `check_mmr_roots`.
+pub fn check_body_header_counts<B: BlockchainBackend>(db: &B, block: &Block) -> Result<(), ValidationError> {
+    let header = &block.header;
+    let prev_header = match db.fetch(&DbKey::HeaderHash(header.prev_hash))? {
+        Some(DbValue::HeaderHash(prev_header)) => prev_header,
+        _ => {
+            return Err(ValidationError::OrphanBlock {
+                block_hash: header.hash().to_hex(),
+                prev_hash: header.prev_hash.to_hex(),
+            })
+        },
+    };
+
+    let kernel_mmr_size = prev_header.kernel_mmr_size + block.body.kernels().len() as u64;
+    if header.kernel_mmr_size != kernel_mmr_size {
+        warn!(
+            target: LOG_TARGET,
+            "Block header kernel MMR size in #{} {} does not match the body. Header: {}, body implies: {}",
+            header.height,
+            header.hash().to_hex(),
+            header.kernel_mmr_size,
+            kernel_mmr_size
+        );
+        return Err(ValidationError::BodyHeaderCountMismatch {
+            kind: "Kernel",
+            header_size: header.kernel_mmr_size,
+            body_size: kernel_mmr_size,
+        });
+    }
+
+    // Burned outputs are never added to the output SMT
+    let num_outputs = block.body.outputs().iter().filter(|o| !o.is_burned()).count() as u64;
+    let output_smt_size = (prev_header.output_smt_size + num_outputs).saturating_sub(block.body.inputs().len() as u64);
+    if header.output_smt_size != output_smt_size {
+        warn!(
+            target: LOG_TARGET,
+            "Block header output SMT size in #{} {} does not match the body. Header: {}, body implies: {}",
+            header.height,
+            header.hash().to_hex(),
+            header.output_smt_size,
+            output_smt_size
+        );
+        return Err(ValidationError::BodyHeaderCountMismatch {
+            kind: "Output",
+            header_size: header.output_smt_size,
+            body_size: output_smt_size,
+        });
+    }
+    Ok(())
+}
+
 pub fn check_mmr_roots(header: &BlockHeader, mmr_roots: &MmrRoots) -> Result<(), ValidationError> {
     if header.kernel_mr != mmr_roots.kernel_mr {
         warn!(
@@ -379,6 +640,90 @@ pub fn check_permitted_output_types(
     Ok(())
 }
 
+/// Checks that the body has at least one coinbase output and exactly one coinbase kernel. This only looks at the
+/// output and kernel features, so it is cheap enough to run before any of the expensive body validation.
+pub fn check_coinbase_counts(body: &AggregateBody) -> Result<(), ValidationError> {
+    if body.outputs().is_empty() || body.kernels().is_empty() {
+        return Err(ValidationError::EmptyBlock);
+    }
+    if !body.outputs().iter().any(|output| output.is_coinbase()) {
+        return Err(BlockValidationError::TransactionError(TransactionError::NoCoinbase).into());
+    }
+    let coinbase_kernels = body.kernels().iter().filter(|kernel| kernel.is_coinbase()).count();
+    if coinbase_kernels != 1 {
+        warn!(
+            target: LOG_TARGET,
+            "{} coinbase kernels found in body. Only a single coinbase kernel is permitted.", coinbase_kernels,
+        );
+        return Err(BlockValidationError::TransactionError(TransactionError::MoreThanOneCoinbaseKernel).into());
+    }
+    Ok(())
+}
+
+/// Checks that the features of the kernel are consistent with its contents: a coinbase kernel may not carry a fee
+/// and a kernel has a burn commitment if and only if it has the burn feature.
+pub fn check_kernel_features(kernel: &TransactionKernel) -> Result<(), ValidationError> {
+    if kernel.is_coinbase() && kernel.fee != MicroMinotari::zero() {
+        return Err(ValidationError::CoinbaseKernelHasFee {
+            kernel: kernel.hash().to_hex(),
+        });
+    }
+    match (kernel.is_burned(), &kernel.burn_commitment) {
+        (true, None) => Err(ValidationError::BurnKernelWithoutCommitment {
+            kernel: kernel.hash().to_hex(),
+        }),
+        (false, Some(_)) => Err(ValidationError::UnexpectedBurnCommitment {
+            kernel: kernel.hash().to_hex(),
+        }),
+        _ => Ok(()),
+    }
+}
+
+/// Checks that an output with a revealed value is not below the minimum output value. The values of other outputs are
+/// hidden in their commitments, so they cannot be checked. Burned outputs never enter the UTXO set and are exempt.
+pub fn check_min_output_value(
+    constants: &ConsensusConstants,
+    output: &TransactionOutput,
+) -> Result<(), ValidationError> {
+    if output.features.range_proof_type != RangeProofType::RevealedValue || output.is_burned() {
+        return Ok(());
+    }
+    let min = constants.min_output_value();
+    if output.minimum_value_promise < min {
+        return Err(ValidationError::DustOutput {
+            value: output.minimum_value_promise,
+            min,
+        });
+    }
+
+    Ok(())
+}
+
+/// Checks that the maturity of an output does not lie further beyond the height of its block than the consensus
+/// constants permit, so that an output cannot be locked up for an arbitrary time.
+pub fn check_output_maturity(
+    constants: &ConsensusConstants,
+    height: u64,
+    output: &TransactionOutput,
+) -> Result<(), ValidationError> {
+    let max_maturity = height.saturating_add(constants.max_output_maturity_offset());
+    if output.features.maturity > max_maturity {
+        warn!(
+            target: LOG_TARGET,
+            "Output {} has maturity {} beyond the highest permitted maturity {}",
+            output.commitment.to_hex(),
+            output.features.maturity,
+            max_maturity
+        );
+        return Err(ValidationError::InvalidOutputActivationHeight {
+            maturity: output.features.maturity,
+            max_maturity,
+        });
+    }
+
+    Ok(())
+}
+
 pub fn check_covenant_length(covenant: &Covenant, max_token_len: u32) -> Result<(), ValidationError> {
     if covenant.num_tokens() > max_token_len as usize {
         return Err(ValidationError::CovenantTooLarge {
@@ -489,12 +834,27 @@ pub fn validate_kernel_version(
     Ok(())
 }
 
+/// Builds the set of hashes of the outputs created in the given window of pending blocks that are still unspent at
+/// the end of the window. Outputs that are created and spent within the window are left out, so they can never satisfy
+/// an input from outside the window.
+pub fn build_pending_output_set(blocks: &[Block]) -> HashSet<HashOutput> {
+    let mut outputs = blocks
+        .iter()
+        .flat_map(|block| block.body.outputs())
+        .map(|output| output.hash())
+        .collect::<HashSet<_>>();
+    for input in blocks.iter().flat_map(|block| block.body.inputs()) {
+        outputs.remove(&input.output_hash());
+    }
+    outputs
+}
+
 #[cfg(test)]
 mod test {
     use tari_test_utils::unpack_enum;
 
     use super::*;
-    use crate::transactions::{test_helpers, test_helpers::TestParams, CryptoFactories};
+    use crate::transactions::{test_helpers, test_helpers::TestParams};
 
     mod is_all_unique_and_sorted {
         use super::*;
@@ -618,6 +978,35 @@ mod test {
             unpack_enum!(TransactionError::InvalidCoinbaseMaturity = err);
         }
 
+        #[tokio::test]
+        async fn it_returns_error_for_coinbase_maturity_one_block_short() {
+            let height = 1;
+            let key_manager = create_memory_db_key_manager().unwrap();
+            let test_params = TestParams::new(&key_manager).await;
+            let rules = test_helpers::create_consensus_manager();
+            let coinbase_lock_height = rules.consensus_constants(height).coinbase_min_maturity();
+            let mut coinbase =
+                test_helpers::create_coinbase_wallet_output(&test_params, height, None, RangeProofType::RevealedValue)
+                    .await;
+            let coinbase_kernel = test_helpers::create_coinbase_kernel(&coinbase.spending_key_id, &key_manager).await;
+
+            coinbase.features.maturity = height + coinbase_lock_height - 1;
+            let coinbase_output = coinbase.to_transaction_output(&key_manager).await.unwrap();
+            let body = AggregateBody::new(vec![], vec![coinbase_output], vec![coinbase_kernel.clone()]);
+            let reward = rules.calculate_coinbase_and_fees(height, body.kernels()).unwrap();
+            let err = body
+                .check_coinbase_output(reward, coinbase_lock_height, &CryptoFactories::default(), height)
+                .unwrap_err();
+            unpack_enum!(TransactionError::InvalidCoinbaseMaturity = err);
+
+            // The maturity at exactly the lock height is accepted
+            coinbase.features.maturity = height + coinbase_lock_height;
+            let coinbase_output = coinbase.to_transaction_output(&key_manager).await.unwrap();
+            let body = AggregateBody::new(vec![], vec![coinbase_output], vec![coinbase_kernel]);
+            body.check_coinbase_output(reward, coinbase_lock_height, &CryptoFactories::default(), height)
+                .unwrap();
+        }
+
         #[tokio::test]
         async fn it_returns_error_for_invalid_coinbase_reward() {
             let height = 1;
@@ -645,4 +1034,285 @@ mod test {
             unpack_enum!(TransactionError::InvalidCoinbase = err);
         }
     }
+
+    mod check_coinbase_reward {
+        use super::*;
+        use crate::transactions::{
+            key_manager::create_memory_db_key_manager,
+            transaction_components::{RangeProofType, TransactionError},
+        };
+
+        #[tokio::test]
+        async fn it_checks_the_coinbase_against_the_reward_and_fees() {
+            let height = 1;
+            let key_manager = create_memory_db_key_manager().unwrap();
+            let test_params = TestParams::new(&key_manager).await;
+            let rules = test_helpers::create_consensus_manager();
+            let fees = MicroMinotari::from(100);
+            let mut coinbase = test_helpers::create_coinbase_wallet_output(
+                &test_params,
+                height,
+                None,
+                RangeProofType::BulletProofPlus,
+            )
+            .await;
+            coinbase.value = rules.calculate_coinbase_and_fees(height, &[]).unwrap() + fees;
+            let coinbase_output = coinbase.to_transaction_output(&key_manager).await.unwrap();
+            let coinbase_kernel = test_helpers::create_coinbase_kernel(&coinbase.spending_key_id, &key_manager).await;
+            let factories = CryptoFactories::default();
+
+            check_coinbase_reward(
+                &rules,
+                height,
+                &[coinbase_output.clone()],
+                &coinbase_kernel,
+                fees,
+                &factories,
+            )
+            .unwrap();
+
+            let err = check_coinbase_reward(
+                &rules,
+                height,
+                &[coinbase_output],
+                &coinbase_kernel,
+                MicroMinotari::from(0),
+                &factories,
+            )
+            .unwrap_err();
+            assert!(matches!(
+                err,
+                ValidationError::TransactionError(TransactionError::InvalidCoinbase)
+            ));
+        }
+    }
+
+    mod check_balance_only {
+        use super::*;
+        use crate::transactions::{
+            key_manager::create_memory_db_key_manager,
+            tari_amount::uT,
+            test_helpers::create_tx,
+        };
+
+        #[tokio::test]
+        async fn it_checks_that_the_body_balances() {
+            let key_manager = create_memory_db_key_manager().unwrap();
+            let (tx, _, _) = create_tx(5000 * uT, 3 * uT, 1, 2, 1, 4, Default::default(), &key_manager)
+                .await
+                .unwrap();
+            let body = tx.body();
+            let factories = CryptoFactories::default();
+            check_balance_only(
+                body.inputs(),
+                body.outputs(),
+                body.kernels(),
+                &tx.offset,
+                MicroMinotari::zero(),
+                &factories,
+            )
+            .unwrap();
+
+            // The reward of a transaction is zero, so any other reward does not balance
+            let err = check_balance_only(
+                body.inputs(),
+                body.outputs(),
+                body.kernels(),
+                &tx.offset,
+                MicroMinotari::from(1),
+                &factories,
+            )
+            .unwrap_err();
+            assert!(matches!(err, ValidationError::InvalidAccountingBalance));
+        }
+    }
+
+    mod build_pending_output_set {
+        use super::*;
+        use crate::transactions::{key_manager::create_memory_db_key_manager, tari_amount::T};
+
+        #[tokio::test]
+        async fn it_excludes_outputs_spent_within_the_window() {
+            let key_manager = create_memory_db_key_manager().unwrap();
+            let spent = test_helpers::create_test_input(100 * T, 0, &key_manager, vec![]).await;
+            let unspent = test_helpers::create_test_input(50 * T, 0, &key_manager, vec![]).await;
+            let change = test_helpers::create_test_input(90 * T, 0, &key_manager, vec![]).await;
+
+            let spent_output = spent.to_transaction_output(&key_manager).await.unwrap();
+            let unspent_output = unspent.to_transaction_output(&key_manager).await.unwrap();
+            let change_output = change.to_transaction_output(&key_manager).await.unwrap();
+            let spent_input = spent.to_transaction_input(&key_manager).await.unwrap();
+
+            let block_1 = Block::new(
+                BlockHeader::new(0),
+                AggregateBody::new(vec![], vec![spent_output.clone(), unspent_output.clone()], vec![]),
+            );
+            let block_2 = Block::new(
+                BlockHeader::new(0),
+                AggregateBody::new(vec![spent_input], vec![change_output.clone()], vec![]),
+            );
+
+            let pending = build_pending_output_set(&[block_1.clone()]);
+            assert_eq!(pending.len(), 2);
+            assert!(pending.contains(&spent_output.hash()));
+
+            let pending = build_pending_output_set(&[block_1, block_2]);
+            assert_eq!(pending.len(), 2);
+            assert!(!pending.contains(&spent_output.hash()));
+            assert!(pending.contains(&unspent_output.hash()));
+            assert!(pending.contains(&change_output.hash()));
+        }
+    }
+
+    mod check_coinbase_counts {
+        use super::*;
+        use crate::transactions::{
+            key_manager::create_memory_db_key_manager,
+            transaction_components::{KernelFeatures, OutputType},
+        };
+
+        #[tokio::test]
+        async fn it_requires_a_coinbase_output_and_a_single_coinbase_kernel() {
+            let key_manager = create_memory_db_key_manager().unwrap();
+            let mut output = test_helpers::create_test_input(MicroMinotari::from(100), 0, &key_manager, vec![])
+                .await
+                .to_transaction_output(&key_manager)
+                .await
+                .unwrap();
+            let coinbase_kernel =
+                test_helpers::create_test_kernel(MicroMinotari::zero(), 0, KernelFeatures::create_coinbase());
+
+            let err = check_coinbase_counts(&AggregateBody::empty()).unwrap_err();
+            assert!(matches!(err, ValidationError::EmptyBlock));
+
+            let body = AggregateBody::new(vec![], vec![output.clone()], vec![coinbase_kernel.clone()]);
+            let err = check_coinbase_counts(&body).unwrap_err();
+            assert!(matches!(
+                err,
+                ValidationError::BlockError(BlockValidationError::TransactionError(TransactionError::NoCoinbase))
+            ));
+
+            output.features.output_type = OutputType::Coinbase;
+            let body = AggregateBody::new(vec![], vec![output.clone()], vec![coinbase_kernel.clone()]);
+            check_coinbase_counts(&body).unwrap();
+
+            let body = AggregateBody::new(vec![], vec![output], vec![coinbase_kernel.clone(), coinbase_kernel]);
+            let err = check_coinbase_counts(&body).unwrap_err();
+            assert!(matches!(
+                err,
+                ValidationError::BlockError(BlockValidationError::TransactionError(
+                    TransactionError::MoreThanOneCoinbaseKernel
+                ))
+            ));
+        }
+    }
+
+    mod check_kernel_features {
+        use super::*;
+        use crate::transactions::transaction_components::KernelFeatures;
+
+        #[test]
+        fn it_accepts_consistent_kernels() {
+            let kernel = test_helpers::create_test_kernel(MicroMinotari::from(100), 0, KernelFeatures::empty());
+            check_kernel_features(&kernel).unwrap();
+            let kernel = test_helpers::create_test_kernel(MicroMinotari::zero(), 0, KernelFeatures::create_coinbase());
+            check_kernel_features(&kernel).unwrap();
+            let mut kernel =
+                test_helpers::create_test_kernel(MicroMinotari::from(100), 0, KernelFeatures::create_burn());
+            kernel.burn_commitment = Some(Default::default());
+            check_kernel_features(&kernel).unwrap();
+        }
+
+        #[test]
+        fn it_rejects_a_coinbase_kernel_with_a_fee() {
+            let kernel = test_helpers::create_test_kernel(MicroMinotari::from(1), 0, KernelFeatures::create_coinbase());
+            let err = check_kernel_features(&kernel).unwrap_err();
+            assert!(matches!(err, ValidationError::CoinbaseKernelHasFee { .. }));
+        }
+
+        #[test]
+        fn it_rejects_mismatched_burn_commitments() {
+            let kernel = test_helpers::create_test_kernel(MicroMinotari::from(100), 0, KernelFeatures::create_burn());
+            let err = check_kernel_features(&kernel).unwrap_err();
+            assert!(matches!(err, ValidationError::BurnKernelWithoutCommitment { .. }));
+
+            let mut kernel = test_helpers::create_test_kernel(MicroMinotari::from(100), 0, KernelFeatures::empty());
+            kernel.burn_commitment = Some(Default::default());
+            let err = check_kernel_features(&kernel).unwrap_err();
+            assert!(matches!(err, ValidationError::UnexpectedBurnCommitment { .. }));
+        }
+    }
+
+    mod check_min_output_value {
+        use tari_common::configuration::Network;
+
+        use super::*;
+        use crate::{
+            consensus::ConsensusConstantsBuilder,
+            transactions::{key_manager::create_memory_db_key_manager, tari_amount::T},
+        };
+
+        #[tokio::test]
+        async fn it_rejects_revealed_values_below_the_minimum() {
+            let key_manager = create_memory_db_key_manager().unwrap();
+            let constants = ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_min_output_value(MicroMinotari::from(100))
+                .build();
+            let mut output = test_helpers::create_test_input(T, 0, &key_manager, vec![])
+                .await
+                .to_transaction_output(&key_manager)
+                .await
+                .unwrap();
+            output.features.range_proof_type = RangeProofType::RevealedValue;
+
+            output.minimum_value_promise = MicroMinotari::from(100);
+            check_min_output_value(&constants, &output).unwrap();
+
+            output.minimum_value_promise = MicroMinotari::from(99);
+            let err = check_min_output_value(&constants, &output).unwrap_err();
+            assert!(matches!(err, ValidationError::DustOutput { .. }));
+
+            // The value of outputs with a bullet proof is hidden
+            output.features.range_proof_type = RangeProofType::BulletProofPlus;
+            check_min_output_value(&constants, &output).unwrap();
+        }
+    }
+
+    mod check_output_maturity {
+        use tari_common::configuration::Network;
+
+        use super::*;
+        use crate::{
+            consensus::ConsensusConstantsBuilder,
+            transactions::{key_manager::create_memory_db_key_manager, tari_amount::T},
+        };
+
+        #[tokio::test]
+        async fn it_rejects_maturities_beyond_the_permitted_offset() {
+            let key_manager = create_memory_db_key_manager().unwrap();
+            let constants = ConsensusConstantsBuilder::new(Network::LocalNet)
+                .with_max_output_maturity_offset(10)
+                .build();
+            let mut output = test_helpers::create_test_input(T, 0, &key_manager, vec![])
+                .await
+                .to_transaction_output(&key_manager)
+                .await
+                .unwrap();
+
+            output.features.maturity = 110;
+            check_output_maturity(&constants, 100, &output).unwrap();
+
+            output.features.maturity = 111;
+            let err = check_output_maturity(&constants, 100, &output).unwrap_err();
+            assert!(matches!(err, ValidationError::InvalidOutputActivationHeight {
+                maturity: 111,
+                max_maturity: 110
+            }));
+
+            // Without a limit, any maturity is permitted
+            let constants = ConsensusConstantsBuilder::new(Network::LocalNet).build();
+            output.features.maturity = u64::MAX;
+            check_output_maturity(&constants, 100, &output).unwrap();
+        }
+    }
 }
@@ -41,11 +41,16 @@ use crate::{
     chain_storage::BlockchainBackend,
     proof_of_work::{randomx_factory::RandomXFactory, AchievedTargetDifficulty, Difficulty},
     test_helpers::create_consensus_rules,
-    transactions::transaction_components::Transaction,
-    validation::{error::ValidationError, DifficultyCalculator, FinalHorizonStateValidation},
+    transactions::{aggregated_body::AggregateBody, transaction_components::Transaction},
+    validation::{error::ValidationError, header::ValidatedHeader, DifficultyCalculator, FinalHorizonStateValidation},
     OutputSmt,
 };
 
+/// Takes a header as validated without validating it, for tests that only exercise body validation
+pub fn mock_validated_header(header: BlockHeader) -> ValidatedHeader {
+    ValidatedHeader::new_unchecked(header)
+}
+
 #[derive(Clone)]
 pub struct MockValidator {
     is_valid: Arc<AtomicBool>,
@@ -72,9 +77,15 @@ impl MockValidator {
 }
 
 impl<B: BlockchainBackend> BlockBodyValidator<B> for MockValidator {
-    fn validate_body(&self, _: &B, block: &Block, _: Arc<RwLock<OutputSmt>>) -> Result<Block, ValidationError> {
+    fn validate_body(
+        &self,
+        _: &B,
+        header: ValidatedHeader,
+        body: AggregateBody,
+        _: Arc<RwLock<OutputSmt>>,
+    ) -> Result<Block, ValidationError> {
         if self.is_valid.load(Ordering::SeqCst) {
-            Ok(block.clone())
+            Ok(Block::new(header.into_header(), body))
         } else {
             Err(ValidationError::ConsensusError(
                 "This mock validator always returns an error".to_string(),
@@ -87,12 +98,12 @@ impl<B: BlockchainBackend> CandidateBlockValidator<B> for MockValidator {
     fn validate_body_with_metadata(
         &self,
         _: &B,
-        _: &ChainBlock,
+        block: &ChainBlock,
         _: &ChainMetadata,
         _: Arc<RwLock<OutputSmt>>,
-    ) -> Result<(), ValidationError> {
+    ) -> Result<Block, ValidationError> {
         if self.is_valid.load(Ordering::SeqCst) {
-            Ok(())
+            Ok(block.block().clone())
         } else {
             Err(ValidationError::ConsensusError(
                 "This mock validator always returns an error".to_string(),
@@ -164,6 +164,31 @@ mod header_validators {
             expected: 4
         }));
     }
+
+    #[tokio::test]
+    async fn it_returns_the_header_it_validated() {
+        let consensus_manager = ConsensusManagerBuilder::new(Network::LocalNet).build().unwrap();
+        let db = create_new_blockchain();
+
+        let (_, blocks) = create_main_chain(&db, block_specs!(["1->GB"], ["2->1"], ["3->2"])).await;
+        let last_block = blocks.get("3").unwrap();
+
+        let candidate_header = BlockHeader::from_previous(last_block.header());
+        let difficulty_calculator = DifficultyCalculator::new(consensus_manager.clone(), Default::default());
+        let validator = HeaderFullValidator::new(consensus_manager, difficulty_calculator);
+        let timestamps = db.fetch_block_timestamps(*blocks.get("3").unwrap().hash()).unwrap();
+
+        let (validated_header, _) = validator
+            .validate_header(
+                &*db.db_read_access().unwrap(),
+                candidate_header.clone(),
+                last_block.header(),
+                &timestamps,
+                None,
+            )
+            .unwrap();
+        assert_eq!(validated_header.header(), &candidate_header);
+    }
 }
 
 #[tokio::test]
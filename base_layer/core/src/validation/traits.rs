@@ -30,26 +30,36 @@ use crate::{
     blocks::{Block, BlockHeader, ChainBlock},
     chain_storage::BlockchainBackend,
     proof_of_work::{AchievedTargetDifficulty, Difficulty},
-    transactions::transaction_components::Transaction,
-    validation::error::ValidationError,
+    transactions::{aggregated_body::AggregateBody, transaction_components::Transaction},
+    validation::{error::ValidationError, header::ValidatedHeader},
     OutputSmt,
 };
 
 /// A validator that determines if a block body is valid, assuming that the header has already been
 /// validated
 pub trait BlockBodyValidator<B>: Send + Sync {
-    fn validate_body(&self, backend: &B, block: &Block, smt: Arc<RwLock<OutputSmt>>) -> Result<Block, ValidationError>;
+    /// Validates `body` as the body of the block with the given header and returns the validated block. Only the body
+    /// is checked, so the header must already have been validated.
+    fn validate_body(
+        &self,
+        backend: &B,
+        header: ValidatedHeader,
+        body: AggregateBody,
+        smt: Arc<RwLock<OutputSmt>>,
+    ) -> Result<Block, ValidationError>;
 }
 
 /// A validator that validates a body after it has been determined to be a valid orphan
 pub trait CandidateBlockValidator<B>: Send + Sync {
+    /// Validates the block on top of the chain described by `metadata` and returns the validated block, which is the
+    /// block that gets committed to the chain.
     fn validate_body_with_metadata(
         &self,
         backend: &B,
         block: &ChainBlock,
         metadata: &ChainMetadata,
         smt: Arc<RwLock<OutputSmt>>,
-    ) -> Result<(), ValidationError>;
+    ) -> Result<Block, ValidationError>;
 }
 
 pub trait TransactionValidator: Send + Sync {
@@ -69,6 +79,20 @@ pub trait HeaderChainLinkedValidator<B: BlockchainBackend>: Send + Sync {
         prev_timestamps: &[EpochTime],
         target_difficulty: Option<Difficulty>,
     ) -> Result<AchievedTargetDifficulty, ValidationError>;
+
+    /// Validates the header in the same way as [validate](Self::validate) and returns it as a [ValidatedHeader], which
+    /// body validation requires
+    fn validate_header(
+        &self,
+        db: &B,
+        header: BlockHeader,
+        prev_header: &BlockHeader,
+        prev_timestamps: &[EpochTime],
+        target_difficulty: Option<Difficulty>,
+    ) -> Result<(ValidatedHeader, AchievedTargetDifficulty), ValidationError> {
+        let achieved_target = self.validate(db, &header, prev_header, prev_timestamps, target_difficulty)?;
+        Ok((ValidatedHeader::new_unchecked(header), achieved_target))
+    }
 }
 
 pub trait FinalHorizonStateValidation<B>: Send + Sync {
@@ -28,6 +28,9 @@ use crate::{
     validation::{traits::TransactionValidator, ValidationError},
 };
 
+/// Validates a single transaction against the current chain tip, as is done for mempool admission. This runs the same
+/// aggregate body checks used for block bodies (kernel signatures, range proofs, metadata and script signatures, input
+/// maturity and UTXO existence) but rejects coinbase outputs instead of checking coinbase requirements.
 pub struct TransactionFullValidator<B> {
     db: BlockchainDatabase<B>,
     internal_validator: TransactionInternalConsistencyValidator,
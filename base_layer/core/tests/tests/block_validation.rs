@@ -32,7 +32,7 @@ use tari_common::configuration::Network;
 use tari_common_types::types::FixedHash;
 use tari_core::{
     blocks::{Block, BlockHeaderAccumulatedData, BlockHeaderValidationError, BlockValidationError, ChainBlock},
-    chain_storage::{BlockchainDatabase, BlockchainDatabaseConfig, ChainStorageError, Validators},
+    chain_storage::{BlockchainBackend, BlockchainDatabase, BlockchainDatabaseConfig, ChainStorageError, Validators},
     consensus::{
         consensus_constants::PowAlgorithmConstants,
         emission::Emission,
@@ -65,7 +65,7 @@ use tari_core::{
     validation::{
         block_body::{BlockBodyFullValidator, BlockBodyInternalConsistencyValidator},
         header::HeaderFullValidator,
-        mocks::MockValidator,
+        mocks::{mock_validated_header, MockValidator},
         BlockBodyValidator,
         CandidateBlockValidator,
         DifficultyCalculator,
@@ -251,6 +251,17 @@ fn add_bad_monero_data(tblock: &mut Block, seed_key: &str) {
     tblock.header.pow.pow_data.extend([1u8; 100]);
 }
 
+/// Validates the body of `block`, taking its header as validated
+fn validate_body<B: BlockchainBackend>(
+    validator: &BlockBodyFullValidator,
+    backend: &B,
+    block: &Block,
+    smt: Arc<RwLock<OutputSmt>>,
+) -> Result<Block, ValidationError> {
+    let header = mock_validated_header(block.header.clone());
+    validator.validate_body(backend, header, block.body.clone(), smt)
+}
+
 #[tokio::test]
 async fn inputs_are_not_malleable() {
     let _ = env_logger::try_init();
@@ -327,7 +338,7 @@ async fn inputs_are_not_malleable() {
     let validator = BlockBodyFullValidator::new(blockchain.consensus_manager().clone(), true);
     let txn = blockchain.store().db_read_access().unwrap();
     let smt = blockchain.store().smt();
-    let err = validator.validate_body(&*txn, &block, smt).unwrap_err();
+    let err = validate_body(&validator, &*txn, &block, smt).unwrap_err();
 
     // All validations pass, except the Input MMR.
     unpack_enum!(ValidationError::BlockError(err) = err);
@@ -886,7 +897,7 @@ async fn test_block_sync_body_validator() {
         // `MutexGuard` cannot be held across an `await` point
         let txn = db.db_read_access().unwrap();
         let smt = db.smt();
-        validator.validate_body(&*txn, &new_block, smt).unwrap_err()
+        validate_body(&validator, &*txn, &new_block, smt).unwrap_err()
     };
     assert!(
         matches!(
@@ -906,7 +917,7 @@ async fn test_block_sync_body_validator() {
         // `MutexGuard` cannot be held across an `await` point
         let txn = db.db_read_access().unwrap();
         let smt = db.smt();
-        validator.validate_body(&*txn, &new_block, smt).unwrap();
+        validate_body(&validator, &*txn, &new_block, smt).unwrap();
     }
 
     // lets break the block weight
@@ -932,7 +943,7 @@ async fn test_block_sync_body_validator() {
         // `MutexGuard` cannot be held across an `await` point
         let txn = db.db_read_access().unwrap();
         let smt = db.smt();
-        validator.validate_body(&*txn, &new_block, smt).unwrap_err()
+        validate_body(&validator, &*txn, &new_block, smt).unwrap_err()
     };
     assert!(
         matches!(
@@ -952,7 +963,7 @@ async fn test_block_sync_body_validator() {
         // `MutexGuard` cannot be held across an `await` point
         let txn = db.db_read_access().unwrap();
         let smt = db.smt();
-        validator.validate_body(&*txn, &new_block, smt).unwrap_err();
+        validate_body(&validator, &*txn, &new_block, smt).unwrap_err();
     }
 
     // lets break the sorting
@@ -965,7 +976,7 @@ async fn test_block_sync_body_validator() {
         // `MutexGuard` cannot be held across an `await` point
         let txn = db.db_read_access().unwrap();
         let smt = db.smt();
-        validator.validate_body(&*txn, &new_block, smt).unwrap_err();
+        validate_body(&validator, &*txn, &new_block, smt).unwrap_err();
     }
 
     // lets have unknown inputs;
@@ -1003,7 +1014,7 @@ async fn test_block_sync_body_validator() {
         // `MutexGuard` cannot be held across an `await` point
         let txn = db.db_read_access().unwrap();
         let smt = db.smt();
-        validator.validate_body(&*txn, &new_block, smt).unwrap_err();
+        validate_body(&validator, &*txn, &new_block, smt).unwrap_err();
     }
 
     // lets check duplicate txos
@@ -1018,7 +1029,7 @@ async fn test_block_sync_body_validator() {
         // `MutexGuard` cannot be held across an `await` point
         let txn = db.db_read_access().unwrap();
         let smt = db.smt();
-        validator.validate_body(&*txn, &new_block, smt).unwrap_err();
+        validate_body(&validator, &*txn, &new_block, smt).unwrap_err();
     }
 
     // let break coinbase value
@@ -1042,7 +1053,7 @@ async fn test_block_sync_body_validator() {
         // `MutexGuard` cannot be held across an `await` point
         let txn = db.db_read_access().unwrap();
         let smt = db.smt();
-        validator.validate_body(&*txn, &new_block, smt).unwrap_err();
+        validate_body(&validator, &*txn, &new_block, smt).unwrap_err();
     }
 
     // let break coinbase lock height
@@ -1066,7 +1077,7 @@ async fn test_block_sync_body_validator() {
         // `MutexGuard` cannot be held across an `await` point
         let txn = db.db_read_access().unwrap();
         let smt = db.smt();
-        validator.validate_body(&*txn, &new_block, smt).unwrap();
+        validate_body(&validator, &*txn, &new_block, smt).unwrap();
     }
 
     // lets break accounting
@@ -1079,7 +1090,7 @@ async fn test_block_sync_body_validator() {
         // `MutexGuard` cannot be held across an `await` point
         let txn = db.db_read_access().unwrap();
         let smt = db.smt();
-        validator.validate_body(&*txn, &new_block, smt).unwrap_err();
+        validate_body(&validator, &*txn, &new_block, smt).unwrap_err();
     }
 
     // lets the mmr root
@@ -1090,7 +1101,7 @@ async fn test_block_sync_body_validator() {
         // `MutexGuard` cannot be held across an `await` point
         let txn = db.db_read_access().unwrap();
         let smt = db.smt();
-        validator.validate_body(&*txn, &new_block, smt).unwrap_err();
+        validate_body(&validator, &*txn, &new_block, smt).unwrap_err();
     }
 }
 